@@ -7,7 +7,6 @@ use api_crawler::output::{
     OutputConfig, OutputFormat, print_hierarchical_summary, serialize_result,
 };
 use api_crawler::prelude::*;
-use serde_json::json;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -0,0 +1,530 @@
+//! Authentication strategies applied to crawler requests
+//!
+//! [`Auth`] describes how to authenticate and is stored on
+//! [`CrawlerConfig`](crate::types::CrawlerConfig). Secret values are held in
+//! [`SecretSource`], whose `Debug` output never reveals them, so configuration
+//! snapshots stay safe to write to disk.
+
+use crate::error::{CrawlerError, Result};
+use crate::host::HostPattern;
+use crate::transport::{Transport, TransportRequest};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderName, HeaderValue};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+use url::Url;
+
+/// Placeholder shown in place of secret values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Refresh OAuth2 tokens this long before they expire
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+/// Check whether a header name is likely to carry credentials
+pub fn is_sensitive_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    matches!(
+        name.as_str(),
+        "authorization" | "proxy-authorization" | "cookie" | "set-cookie"
+    ) || ["token", "secret", "key", "password", "session", "auth"]
+        .iter()
+        .any(|needle| name.contains(needle))
+}
+
+/// Where a secret value comes from
+///
+/// In configuration files a secret is either a literal string or a table
+/// naming its source: `{ env = "API_TOKEN" }` or `{ file = "/run/secrets/token" }`.
+#[derive(Clone, PartialEq, Eq)]
+pub enum SecretSource {
+    /// The secret itself
+    Value(String),
+    /// Name of an environment variable holding the secret
+    Env(String),
+    /// Path to a file whose trimmed contents are the secret
+    File(PathBuf),
+}
+
+impl SecretSource {
+    /// Read the secret value
+    pub fn resolve(&self) -> Result<String> {
+        match self {
+            Self::Value(value) => Ok(value.clone()),
+            Self::Env(var) => std::env::var(var).map_err(|_| {
+                CrawlerError::config(format!("Environment variable {} is not set", var))
+            }),
+            Self::File(path) => std::fs::read_to_string(path)
+                .map(|s| s.trim().to_string())
+                .map_err(|e| {
+                    CrawlerError::config(format!(
+                        "Failed to read secret from {}: {}",
+                        path.display(),
+                        e
+                    ))
+                }),
+        }
+    }
+}
+
+/// Configuration file representation of a [`SecretSource`]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SecretSourceRepr {
+    Value(String),
+    Env { env: String },
+    File { file: PathBuf },
+}
+
+impl<'de> Deserialize<'de> for SecretSource {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        SecretSourceRepr::deserialize(deserializer)
+            .map(|repr| match repr {
+                SecretSourceRepr::Value(value) => Self::Value(value),
+                SecretSourceRepr::Env { env } => Self::Env(env),
+                SecretSourceRepr::File { file } => Self::File(file),
+            })
+            .map_err(|_| {
+                serde::de::Error::custom(
+                    "expected a secret string, { env = \"VAR\" } or { file = \"PATH\" }",
+                )
+            })
+    }
+}
+
+impl fmt::Debug for SecretSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(_) => f.write_str(REDACTED),
+            Self::Env(var) => write!(f, "Env({})", var),
+            Self::File(path) => write!(f, "File({})", path.display()),
+        }
+    }
+}
+
+/// Where an API key is sent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyLocation {
+    /// As a request header
+    #[default]
+    Header,
+    /// As a query string parameter
+    Query,
+}
+
+/// OAuth2 client-credentials grant settings
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OAuth2ClientCredentials {
+    /// Token endpoint URL
+    pub token_url: String,
+
+    /// Client identifier
+    pub client_id: String,
+
+    /// Client secret, sent with HTTP Basic client authentication
+    pub client_secret: SecretSource,
+
+    /// Scopes to request (empty means server default)
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+/// Authentication strategy for crawler requests
+///
+/// Configuration files name the strategy as the key, e.g.
+/// `auth = { bearer = { env = "API_TOKEN" } }`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Auth {
+    /// HTTP Basic authentication
+    Basic {
+        username: String,
+        password: SecretSource,
+    },
+    /// Bearer token in the Authorization header
+    Bearer(SecretSource),
+    /// API key sent as a header or query parameter
+    ApiKey {
+        name: String,
+        value: SecretSource,
+        #[serde(default)]
+        location: ApiKeyLocation,
+    },
+    /// OAuth2 client-credentials grant with automatic token refresh
+    #[serde(rename = "oauth2")]
+    OAuth2(OAuth2ClientCredentials),
+}
+
+/// Headers and credentials sent only to hosts matching a pattern
+///
+/// The first scope whose pattern matches a request's host adds its headers and,
+/// if set, replaces the crawler-wide [`Auth`]. Hosts matching no scope receive
+/// only the unscoped configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CredentialScope {
+    /// Hosts this scope applies to
+    pub hosts: HostPattern,
+
+    /// Headers sent to matching hosts
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+
+    /// Authentication strategy for matching hosts
+    #[serde(default)]
+    pub auth: Option<Auth>,
+}
+
+impl CredentialScope {
+    /// Create an empty scope for hosts matching `pattern`
+    pub fn new(pattern: &str) -> Result<Self> {
+        Ok(Self {
+            hosts: HostPattern::parse(pattern)?,
+            headers: HashMap::new(),
+            auth: None,
+        })
+    }
+
+    /// Add a header sent to matching hosts
+    pub fn header(mut self, key: String, value: String) -> Self {
+        self.headers.insert(key, value);
+        self
+    }
+
+    /// Set the authentication strategy for matching hosts
+    pub fn auth(mut self, auth: Auth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+}
+
+/// An access token obtained from an OAuth2 token endpoint
+struct CachedToken {
+    access_token: String,
+    expires_at: Option<Instant>,
+}
+
+impl CachedToken {
+    fn is_fresh(&self) -> bool {
+        self.expires_at
+            .is_none_or(|expires_at| Instant::now() + TOKEN_EXPIRY_MARGIN < expires_at)
+    }
+}
+
+/// Token endpoint response body
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+/// Static credentials resolved when the crawler is created
+enum ResolvedAuth {
+    Header(HeaderName, HeaderValue),
+    Query(String, String),
+    OAuth2 {
+        settings: OAuth2ClientCredentials,
+        basic: HeaderValue,
+    },
+}
+
+/// Applies an [`Auth`] strategy to outgoing requests
+pub(crate) struct Authenticator {
+    resolved: Option<ResolvedAuth>,
+    token: Mutex<Option<CachedToken>>,
+}
+
+/// Build a `Basic` Authorization header value
+fn basic_header(username: &str, password: &str) -> Result<HeaderValue> {
+    let encoded = BASE64.encode(format!("{}:{}", username, password));
+    let mut value = HeaderValue::from_str(&format!("Basic {}", encoded))
+        .map_err(|_| CrawlerError::config("Invalid basic auth credentials"))?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+impl Authenticator {
+    /// Resolve secrets for the configured strategy
+    pub(crate) fn new(auth: Option<&Auth>) -> Result<Self> {
+        let resolved = match auth {
+            None => None,
+            Some(Auth::Basic { username, password }) => Some(ResolvedAuth::Header(
+                AUTHORIZATION,
+                basic_header(username, &password.resolve()?)?,
+            )),
+            Some(Auth::Bearer(token)) => {
+                let mut value = HeaderValue::from_str(&format!("Bearer {}", token.resolve()?))
+                    .map_err(|_| CrawlerError::config("Invalid bearer token"))?;
+                value.set_sensitive(true);
+                Some(ResolvedAuth::Header(AUTHORIZATION, value))
+            }
+            Some(Auth::ApiKey {
+                name,
+                value,
+                location: ApiKeyLocation::Header,
+            }) => {
+                let header_name: HeaderName = name
+                    .parse()
+                    .map_err(|_| CrawlerError::config(format!("Invalid header name: {}", name)))?;
+                let mut header_value = HeaderValue::from_str(&value.resolve()?)
+                    .map_err(|_| CrawlerError::config("Invalid API key value"))?;
+                header_value.set_sensitive(true);
+                Some(ResolvedAuth::Header(header_name, header_value))
+            }
+            Some(Auth::ApiKey {
+                name,
+                value,
+                location: ApiKeyLocation::Query,
+            }) => Some(ResolvedAuth::Query(name.clone(), value.resolve()?)),
+            Some(Auth::OAuth2(settings)) => {
+                Url::parse(&settings.token_url)?;
+                Some(ResolvedAuth::OAuth2 {
+                    basic: basic_header(&settings.client_id, &settings.client_secret.resolve()?)?,
+                    settings: settings.clone(),
+                })
+            }
+        };
+
+        Ok(Self {
+            resolved,
+            token: Mutex::new(None),
+        })
+    }
+
+    /// Whether a 401 response can be retried after refreshing credentials
+    pub(crate) fn can_refresh(&self) -> bool {
+        matches!(self.resolved, Some(ResolvedAuth::OAuth2 { .. }))
+    }
+
+    /// Drop the cached OAuth2 token so the next request fetches a new one
+    pub(crate) async fn invalidate(&self) {
+        *self.token.lock().await = None;
+    }
+
+    /// Add credentials to a request
+    pub(crate) async fn apply(
+        &self,
+        request: &mut TransportRequest,
+        transport: &dyn Transport,
+    ) -> Result<()> {
+        match &self.resolved {
+            None => {}
+            Some(ResolvedAuth::Header(name, value)) => {
+                request.headers.insert(name.clone(), value.clone());
+            }
+            Some(ResolvedAuth::Query(name, value)) => {
+                let mut url = Url::parse(&request.url)?;
+                url.query_pairs_mut().append_pair(name, value);
+                request.url = url.to_string();
+            }
+            Some(ResolvedAuth::OAuth2 { settings, basic }) => {
+                let mut token = self.token.lock().await;
+                if !token.as_ref().is_some_and(CachedToken::is_fresh) {
+                    *token = Some(Self::fetch_token(settings, basic, transport).await?);
+                }
+                if let Some(token) = token.as_ref() {
+                    let mut value =
+                        HeaderValue::from_str(&format!("Bearer {}", token.access_token)).map_err(
+                            |_| CrawlerError::invalid_response("Invalid OAuth2 access token"),
+                        )?;
+                    value.set_sensitive(true);
+                    request.headers.insert(AUTHORIZATION, value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Request a new access token with the client-credentials grant
+    async fn fetch_token(
+        settings: &OAuth2ClientCredentials,
+        basic: &HeaderValue,
+        transport: &dyn Transport,
+    ) -> Result<CachedToken> {
+        debug!("Requesting OAuth2 token from {}", settings.token_url);
+
+        // The serializer isn't `Send`, so finish it before awaiting
+        let body = {
+            let mut form = url::form_urlencoded::Serializer::new(String::new());
+            form.append_pair("grant_type", "client_credentials");
+            if !settings.scopes.is_empty() {
+                form.append_pair("scope", &settings.scopes.join(" "));
+            }
+            form.finish()
+        };
+
+        let mut request = TransportRequest::get(settings.token_url.clone());
+        request.method = reqwest::Method::POST;
+        request.headers.insert(AUTHORIZATION, basic.clone());
+        request.headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        );
+        request.body = Some(body.into_bytes());
+
+        let response = transport.fetch(&request).await?;
+        if !response.is_success() {
+            return Err(CrawlerError::HttpStatus {
+                status: response.status,
+                url: settings.token_url.clone(),
+            });
+        }
+
+        let token: TokenResponse = serde_json::from_slice(&response.body)?;
+        info!("Obtained OAuth2 access token from {}", settings.token_url);
+
+        Ok(CachedToken {
+            access_token: token.access_token,
+            expires_at: token
+                .expires_in
+                .map(|secs| Instant::now() + Duration::from_secs(secs)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::stub::StubTransport;
+    use serde_json::json;
+
+    #[test]
+    fn test_secret_source_debug_is_redacted() {
+        let auth = Auth::Basic {
+            username: "alice".to_string(),
+            password: SecretSource::Value("hunter2".to_string()),
+        };
+        let debug = format!("{:?}", auth);
+        assert!(debug.contains("alice"));
+        assert!(!debug.contains("hunter2"));
+        assert!(debug.contains(REDACTED));
+
+        let env = format!("{:?}", SecretSource::Env("API_TOKEN".to_string()));
+        assert_eq!(env, "Env(API_TOKEN)");
+    }
+
+    #[test]
+    fn test_is_sensitive_header() {
+        assert!(is_sensitive_header("Authorization"));
+        assert!(is_sensitive_header("X-Api-Key"));
+        assert!(is_sensitive_header("X-Auth-Token"));
+        assert!(!is_sensitive_header("Accept"));
+        assert!(!is_sensitive_header("User-Agent"));
+    }
+
+    #[tokio::test]
+    async fn test_basic_and_query_auth() {
+        let transport = StubTransport::new();
+
+        let basic = Authenticator::new(Some(&Auth::Basic {
+            username: "Aladdin".to_string(),
+            password: SecretSource::Value("open sesame".to_string()),
+        }))
+        .unwrap();
+        let mut request = TransportRequest::get("http://example.com/");
+        basic.apply(&mut request, &transport).await.unwrap();
+        assert_eq!(
+            request.headers.get(AUTHORIZATION).unwrap(),
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ=="
+        );
+
+        let query = Authenticator::new(Some(&Auth::ApiKey {
+            name: "api_key".to_string(),
+            value: SecretSource::Value("abc 123".to_string()),
+            location: ApiKeyLocation::Query,
+        }))
+        .unwrap();
+        let mut request = TransportRequest::get("http://example.com/items?page=2");
+        query.apply(&mut request, &transport).await.unwrap();
+        assert_eq!(
+            request.url,
+            "http://example.com/items?page=2&api_key=abc+123"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oauth2_token_is_cached_and_refreshed() {
+        let token_url = "POST http://auth.example.com/token";
+        let transport = StubTransport::new()
+            .json(
+                token_url,
+                json!({"access_token": "token-1", "expires_in": 3600}),
+            )
+            .json(
+                token_url,
+                json!({"access_token": "token-2", "expires_in": 3600}),
+            );
+        let requests = transport.log();
+        let auth = Authenticator::new(Some(&Auth::OAuth2(OAuth2ClientCredentials {
+            token_url: "http://auth.example.com/token".to_string(),
+            client_id: "client".to_string(),
+            client_secret: SecretSource::Value("shh".to_string()),
+            scopes: vec!["read".to_string(), "write".to_string()],
+        })))
+        .unwrap();
+        assert!(auth.can_refresh());
+
+        let mut first = TransportRequest::get("http://example.com/a");
+        auth.apply(&mut first, &transport).await.unwrap();
+        let mut second = TransportRequest::get("http://example.com/b");
+        auth.apply(&mut second, &transport).await.unwrap();
+        assert_eq!(first.headers.get(AUTHORIZATION).unwrap(), "Bearer token-1");
+        assert_eq!(second.headers.get(AUTHORIZATION).unwrap(), "Bearer token-1");
+
+        // A 401 mid-crawl invalidates the token and the next request refreshes it
+        auth.invalidate().await;
+        let mut third = TransportRequest::get("http://example.com/c");
+        auth.apply(&mut third, &transport).await.unwrap();
+        assert_eq!(third.headers.get(AUTHORIZATION).unwrap(), "Bearer token-2");
+
+        let token_requests = requests.requests();
+        assert_eq!(token_requests.len(), 2);
+        assert_eq!(
+            token_requests[0].headers.get(AUTHORIZATION).unwrap(),
+            &basic_header("client", "shh").unwrap()
+        );
+        assert_eq!(
+            token_requests[0].body.as_deref(),
+            Some(&b"grant_type=client_credentials&scope=read+write"[..])
+        );
+    }
+
+    #[test]
+    fn test_missing_env_secret_is_config_error() {
+        let result = Authenticator::new(Some(&Auth::Bearer(SecretSource::Env(
+            "API_CRAWLER_TEST_UNSET_VARIABLE".to_string(),
+        ))));
+        assert!(matches!(result, Err(CrawlerError::Config { .. })));
+    }
+
+    #[test]
+    fn test_config_snapshot_redacts_secrets() {
+        use crate::types::{CrawlResult, CrawlerConfig};
+
+        let config = CrawlerConfig::default()
+            .add_header("Authorization".to_string(), "Bearer abc123".to_string())
+            .add_header("Accept".to_string(), "application/json".to_string())
+            .auth(Auth::ApiKey {
+                name: "X-Api-Key".to_string(),
+                value: SecretSource::Value("key-456".to_string()),
+                location: ApiKeyLocation::Header,
+            });
+
+        let result = CrawlResult::new("http://example.com".to_string(), &config);
+
+        assert!(!result.config_snapshot.contains("abc123"));
+        assert!(!result.config_snapshot.contains("key-456"));
+        assert!(result.config_snapshot.contains("application/json"));
+        assert!(result.config_snapshot.contains("X-Api-Key"));
+    }
+}
@@ -0,0 +1,391 @@
+//! Loading crawler and output settings from TOML, YAML or JSON files
+//!
+//! A configuration file has an optional start `url` and `crawler` and `output`
+//! sections mirroring [`CrawlerConfig`] and [`OutputConfig`]:
+//!
+//! ```toml
+//! url = "https://api.example.com/"
+//!
+//! [crawler]
+//! max_depth = 5
+//! headers = { Accept = "application/hal+json" }
+//! auth = { bearer = { env = "API_TOKEN" } }
+//!
+//! [output]
+//! format = "tree"
+//! ```
+//!
+//! `${VAR}` anywhere in a string value is replaced with the environment
+//! variable `VAR`, so secrets don't have to be stored in the file. Every
+//! invalid field is reported at once in a single [`CrawlerError::Config`].
+
+use crate::error::{CrawlerError, Result};
+use crate::output::OutputConfig;
+use crate::types::CrawlerConfig;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+/// Supported configuration file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Detect the format from a file extension
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("toml") => Ok(Self::Toml),
+            Some("yaml") | Some("yml") => Ok(Self::Yaml),
+            Some("json") => Ok(Self::Json),
+            _ => Err(CrawlerError::config(format!(
+                "Unsupported configuration file {} (expected .toml, .yaml, .yml or .json)",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Parse a document into a JSON value tree
+    pub(crate) fn parse(self, content: &str) -> std::result::Result<Value, String> {
+        match self {
+            Self::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+            Self::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+            Self::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Settings loaded from a configuration file
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFile {
+    /// Starting URL for the crawl
+    pub url: Option<String>,
+
+    /// Crawler settings
+    pub crawler: CrawlerConfig,
+
+    /// Output settings
+    pub output: OutputConfig,
+}
+
+impl ConfigFile {
+    /// Load and validate a configuration file, picking the format from its extension
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let document = read_document(path)?;
+        let mut problems = Vec::new();
+        let config = Self::from_value(document, &mut problems);
+        check_problems(problems).map_err(|e| in_file(path, e))?;
+        info!("Loaded configuration from: {}", path.display());
+        Ok(config)
+    }
+
+    /// Parse and validate configuration text
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<Self> {
+        let document = format.parse(content).map_err(CrawlerError::config)?;
+        let mut problems = Vec::new();
+        let config = Self::from_value(document, &mut problems);
+        check_problems(problems)?;
+        Ok(config)
+    }
+
+    /// Build from a parsed document, recording every problem found
+    ///
+    /// The result is only meaningful when no problems were recorded.
+    pub(crate) fn from_value(mut document: Value, problems: &mut Vec<String>) -> Self {
+        interpolate_env(&mut document, "", problems);
+
+        let Value::Object(mut root) = document else {
+            problems.push("expected a table of settings".to_string());
+            return Self::default();
+        };
+
+        let url = match root.remove("url") {
+            None => None,
+            Some(Value::String(url)) => {
+                if let Err(e) = url::Url::parse(&url) {
+                    problems.push(format!("url: {}", e));
+                }
+                Some(url)
+            }
+            Some(_) => {
+                problems.push("url: expected a string".to_string());
+                None
+            }
+        };
+
+        let crawler = section::<CrawlerConfig>(&mut root, "crawler", problems);
+        let output = section::<OutputConfig>(&mut root, "output", problems);
+
+        let mut unknown: Vec<&String> = root.keys().collect();
+        unknown.sort();
+        for key in unknown {
+            problems.push(format!(
+                "{}: unknown section, expected `url`, `crawler` or `output`",
+                key
+            ));
+        }
+
+        if let Some(crawler) = &crawler {
+            problems.extend(
+                crawler
+                    .validation_errors()
+                    .into_iter()
+                    .map(|problem| format!("crawler.{}", problem)),
+            );
+        }
+
+        Self {
+            url,
+            crawler: crawler.unwrap_or_default(),
+            output: output.unwrap_or_default(),
+        }
+    }
+}
+
+/// Read and parse a configuration document, picking the format from its extension
+pub(crate) fn read_document(path: &Path) -> Result<Value> {
+    let format = ConfigFormat::from_path(path)?;
+    let content = fs::read_to_string(path)
+        .map_err(|e| CrawlerError::config(format!("Failed to read {}: {}", path.display(), e)))?;
+    format
+        .parse(&content)
+        .map_err(|e| CrawlerError::config(format!("{}: {}", path.display(), e)))
+}
+
+/// Name the file a configuration error came from
+pub(crate) fn in_file(path: &Path, error: CrawlerError) -> CrawlerError {
+    match error {
+        CrawlerError::Config { message } => {
+            CrawlerError::config(format!("{}: {}", path.display(), message))
+        }
+        other => other,
+    }
+}
+
+/// Turn collected problems into a single configuration error
+pub(crate) fn check_problems(problems: Vec<String>) -> Result<()> {
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(CrawlerError::config(format!(
+        "Invalid configuration:\n  - {}",
+        problems.join("\n  - ")
+    )))
+}
+
+/// Deserialize a section, checking each key on its own so every bad field is reported
+///
+/// Keys that fail are left out, so the rest of the section can still be validated.
+fn section<T: DeserializeOwned>(
+    root: &mut Map<String, Value>,
+    name: &str,
+    problems: &mut Vec<String>,
+) -> Option<T> {
+    let table = match root.remove(name) {
+        None => Map::new(),
+        Some(Value::Object(table)) => table,
+        Some(_) => {
+            problems.push(format!("{}: expected a table", name));
+            return None;
+        }
+    };
+
+    let mut keys: Vec<String> = table.keys().cloned().collect();
+    keys.sort();
+    let mut valid = Map::new();
+    for key in keys {
+        let mut single = Map::new();
+        single.insert(key.clone(), table[&key].clone());
+        match serde_json::from_value::<T>(Value::Object(single)) {
+            Ok(_) => {
+                valid.insert(key.clone(), table[&key].clone());
+            }
+            Err(e) => problems.push(format!("{}.{}: {}", name, key, e)),
+        }
+    }
+
+    match serde_json::from_value(Value::Object(valid)) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            problems.push(format!("{}: {}", name, e));
+            None
+        }
+    }
+}
+
+/// Replace `${VAR}` in every string with the environment variable's value
+fn interpolate_env(value: &mut Value, path: &str, problems: &mut Vec<String>) {
+    match value {
+        Value::String(s) if s.contains("${") => match expand_env(s) {
+            Ok(expanded) => *s = expanded,
+            Err(e) => problems.push(format!("{}: {}", path, e)),
+        },
+        Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                interpolate_env(item, &format!("{}[{}]", path, i), problems);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                interpolate_env(item, &child, problems);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Expand `${VAR}` references in a string
+fn expand_env(input: &str) -> std::result::Result<String, String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find("${") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| format!("unterminated `${{` in '{}'", input))?;
+        let name = &after[..end];
+        let value =
+            std::env::var(name).map_err(|_| format!("environment variable {} is not set", name))?;
+        output.push_str(&value);
+        rest = &after[end + 1..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{Auth, SecretSource};
+    use crate::output::OutputFormat;
+
+    #[test]
+    fn test_parse_toml_yaml_and_json() {
+        let toml = r#"
+            url = "https://api.example.com/"
+
+            [crawler]
+            max_depth = 3
+            allowed_domains = ["api.example.com"]
+            headers = { Accept = "application/hal+json" }
+            auth = { bearer = { env = "API_TOKEN" } }
+
+            [[crawler.credential_scopes]]
+            hosts = "*.internal.example.com"
+            auth = { basic = { username = "svc", password = { file = "/run/secrets/pw" } } }
+
+            [output]
+            format = "tree"
+        "#;
+        let config = ConfigFile::parse(toml, ConfigFormat::Toml).unwrap();
+        assert_eq!(config.url.as_deref(), Some("https://api.example.com/"));
+        assert_eq!(config.crawler.max_depth, 3);
+        assert_eq!(config.crawler.max_urls, CrawlerConfig::default().max_urls);
+        assert_eq!(
+            config.crawler.auth,
+            Some(Auth::Bearer(SecretSource::Env("API_TOKEN".to_string())))
+        );
+        assert_eq!(config.crawler.credential_scopes.len(), 1);
+        assert!(matches!(config.output.format, OutputFormat::Tree));
+
+        let yaml = "crawler:\n  max_depth: 4\n  proxy:\n    url: socks5h://127.0.0.1:1080\noutput:\n  format: junit\n";
+        let config = ConfigFile::parse(yaml, ConfigFormat::Yaml).unwrap();
+        assert_eq!(config.crawler.max_depth, 4);
+        assert!(config.crawler.proxy.url.is_some());
+        assert!(matches!(config.output.format, OutputFormat::JUnit));
+
+        let json = r#"{"crawler": {"login": {"url": "https://legacy.example.com/login",
+            "body": {"form": [["user", "alice"], ["password", {"env": "PW"}]]}}}}"#;
+        let config = ConfigFile::parse(json, ConfigFormat::Json).unwrap();
+        assert_eq!(config.crawler.login.unwrap().method, "POST");
+    }
+
+    #[test]
+    fn test_env_interpolation() {
+        // SAFETY: the variable name is unique to this test
+        unsafe { std::env::set_var("API_CRAWLER_TEST_TENANT", "acme") };
+        let toml = r#"
+            [crawler]
+            user_agent = "crawler/${API_CRAWLER_TEST_TENANT}"
+            headers = { X-Missing = "${API_CRAWLER_TEST_UNSET}" }
+        "#;
+
+        let error = ConfigFile::parse(toml, ConfigFormat::Toml).unwrap_err();
+        assert!(error.to_string().contains(
+            "crawler.headers.X-Missing: environment variable API_CRAWLER_TEST_UNSET is not set"
+        ));
+
+        let config = ConfigFile::parse(
+            "[crawler]\nuser_agent = \"crawler/${API_CRAWLER_TEST_TENANT}\"",
+            ConfigFormat::Toml,
+        )
+        .unwrap();
+        assert_eq!(config.crawler.user_agent, "crawler/acme");
+    }
+
+    #[test]
+    fn test_all_invalid_fields_reported_at_once() {
+        let toml = r#"
+            url = "not a url"
+            unexpected = true
+
+            [crawler]
+            max_depth = "ten"
+            max_concurrent_requests = 0
+            max_urlz = 5
+            headers = { "Bad Header" = "x" }
+            proxy = { url = "ftp://proxy.corp" }
+            filters = { include = ["regex:(unclosed"] }
+
+            [output]
+            format = "yaml"
+        "#;
+
+        let error = ConfigFile::parse(toml, ConfigFormat::Toml).unwrap_err();
+        let CrawlerError::Config { message } = error else {
+            panic!("expected a config error");
+        };
+
+        for field in [
+            "url:",
+            "unexpected: unknown section",
+            "crawler.max_depth:",
+            "crawler.max_urlz: unknown field",
+            "crawler.max_concurrent_requests: must be at least 1",
+            "crawler.headers: invalid header name 'Bad Header'",
+            "crawler.proxy.url: unsupported scheme 'ftp'",
+            "crawler.filters: Invalid pattern 'regex:(unclosed'",
+            "output.format: unknown variant `yaml`",
+        ] {
+            assert!(message.contains(field), "missing {} in {}", field, message);
+        }
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("crawl.yml")).unwrap(),
+            ConfigFormat::Yaml
+        );
+        assert!(ConfigFormat::from_path(Path::new("crawl.ini")).is_err());
+    }
+}
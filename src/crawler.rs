@@ -1,12 +1,31 @@
 //! Core API crawler implementation
 
+use crate::auth::{Auth, Authenticator, is_sensitive_header};
 use crate::error::{CrawlerError, Result};
-use crate::types::{ApiEndpoint, CrawlResult, CrawlerConfig, QueueItem};
-use reqwest::Client;
+use crate::filter::FilterRule;
+use crate::graphql::{self, GraphqlEndpoint, GraphqlSchema};
+use crate::har::{Har, HarContent, HarEntry, HarRequest, HarResponse, HarTimings, har_headers};
+use crate::host::HostPattern;
+use crate::html;
+use crate::media::MediaType;
+use crate::methods::SupportedMethods;
+use crate::pagination;
+use crate::routes;
+use crate::safety::{LinkSafety, ReadOnlyTransport};
+use crate::sampling::{self, Sampler};
+use crate::schema::{self, Shape};
+use crate::sensitive::{self, SensitiveFinding};
+use crate::session::CookieJar;
+use crate::transport::{HttpTransport, Transport, TransportRequest, TransportResponse};
+use crate::types::{ApiEndpoint, CrawlResult, CrawlerConfig, QueueItem, UrlVisit};
+use crate::xml;
+use futures_util::{StreamExt, stream};
+use reqwest::{Client, Method};
 use serde_json::Value;
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::hash::{Hash, Hasher};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::Semaphore;
 use tokio::time::{Instant, sleep};
@@ -39,10 +58,48 @@ impl EndpointKey {
     }
 }
 
+/// A credential scope with its headers parsed and secrets resolved
+struct ScopedCredentials {
+    hosts: HostPattern,
+    headers: reqwest::header::HeaderMap,
+    authenticator: Option<Authenticator>,
+}
+
+/// Parse configured headers, marking credential-bearing values as sensitive
+fn parse_headers(headers: &HashMap<String, String>) -> Result<reqwest::header::HeaderMap> {
+    let mut parsed = reqwest::header::HeaderMap::new();
+    for (key, value) in headers {
+        let header_name: reqwest::header::HeaderName = key
+            .parse()
+            .map_err(|_| CrawlerError::config(format!("Invalid header name: {}", key)))?;
+        let mut header_value: reqwest::header::HeaderValue = value
+            .parse()
+            .map_err(|_| CrawlerError::config(format!("Invalid header value for {}", key)))?;
+        header_value.set_sensitive(is_sensitive_header(key));
+        parsed.insert(header_name, header_value);
+    }
+    Ok(parsed)
+}
+
 /// The main API crawler
 pub struct ApiCrawler {
-    /// HTTP client for making requests
-    client: Client,
+    /// Transport used to fetch URLs
+    transport: Box<dyn Transport>,
+
+    /// Applies the configured authentication strategy to requests
+    authenticator: Authenticator,
+
+    /// Credential-bearing custom headers, sent like `authenticator` only to the start URL's site
+    site_headers: reqwest::header::HeaderMap,
+
+    /// Per-host headers and credentials, checked in order
+    credential_scopes: Vec<ScopedCredentials>,
+
+    /// Compiled `allowed_domains` rules
+    domain_rules: Vec<HostPattern>,
+
+    /// Site of the current start URL, the default crawl scope
+    site: Option<HostPattern>,
 
     /// Configuration for the crawler
     config: CrawlerConfig,
@@ -55,11 +112,56 @@ pub struct ApiCrawler {
 
     /// Queue of URLs to process
     url_queue: VecDeque<QueueItem>,
+
+    /// Headers sent with every request, kept for HAR recording
+    default_headers: reqwest::header::HeaderMap,
+
+    /// Recorded HAR entries when `record_har` is enabled
+    har_entries: Mutex<Vec<HarEntry>>,
+
+    /// HTTP version of the last recorded response, used for requests that got none
+    har_http_version: Mutex<String>,
+
+    /// GraphQL endpoints detected while processing URLs
+    graphql_endpoints: Mutex<Vec<GraphqlEndpoint>>,
+
+    /// Item totals reported by processed collection responses, by URL
+    collection_totals: Mutex<HashMap<String, usize>>,
+
+    /// Chooses which instances of ID-like URL patterns are followed
+    sampler: Sampler,
+
+    /// Shapes of the JSON bodies fetched, by URL without query, when inferring schemas
+    response_shapes: Mutex<HashMap<String, Shape>>,
+
+    /// Sensitive data found in response bodies when scanning is enabled
+    findings: Mutex<Vec<SensitiveFinding>>,
+
+    /// Cookies shared with the HTTP client when `cookies` is enabled
+    cookie_jar: Option<Arc<CookieJar>>,
+
+    /// Whether a 401 may trigger another login, cleared once logging in again didn't help
+    relogin_allowed: AtomicBool,
 }
 
 impl ApiCrawler {
     /// Create a new API crawler with the given configuration
     pub fn new(config: CrawlerConfig) -> Result<Self> {
+        Self::build(config, None)
+    }
+
+    /// Create a new API crawler that fetches URLs through the given transport
+    ///
+    /// Use this with a [`crate::transport::HarTransport`] or
+    /// [`crate::transport::FixtureTransport`] to crawl recorded responses offline.
+    pub fn with_transport(config: CrawlerConfig, transport: Box<dyn Transport>) -> Result<Self> {
+        Self::build(config, Some(transport))
+    }
+
+    /// Build the crawler, creating an HTTP transport unless one is supplied
+    fn build(config: CrawlerConfig, transport: Option<Box<dyn Transport>>) -> Result<Self> {
+        config.validate()?;
+
         let mut headers = reqwest::header::HeaderMap::new();
 
         // Add user agent
@@ -71,38 +173,191 @@ impl ApiCrawler {
                 .map_err(|_| CrawlerError::config("Invalid user agent"))?,
         );
 
-        // Add custom headers
-        for (key, value) in &config.headers {
-            let header_name: reqwest::header::HeaderName = key
-                .parse()
-                .map_err(|_| CrawlerError::config(format!("Invalid header name: {}", key)))?;
-            let header_value: reqwest::header::HeaderValue = value
-                .parse()
-                .map_err(|_| CrawlerError::config(format!("Invalid header value: {}", value)))?;
-            headers.insert(header_name, header_value);
+        if !config.accept.is_empty() {
+            headers.insert(
+                reqwest::header::ACCEPT,
+                config
+                    .accept
+                    .parse()
+                    .map_err(|_| CrawlerError::config("Invalid Accept header"))?,
+            );
         }
 
-        let client = Client::builder()
-            .timeout(Duration::from_secs(config.timeout_seconds))
-            .default_headers(headers)
-            .redirect(if config.follow_redirects {
-                reqwest::redirect::Policy::limited(10)
-            } else {
-                reqwest::redirect::Policy::none()
-            })
-            .build()?;
+        // Add custom headers, which may override the Accept header; credentials are kept apart
+        let (credential_headers, custom_headers): (HashMap<String, String>, _) = config
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .partition(|(name, _)| is_sensitive_header(name));
+        headers.extend(parse_headers(&custom_headers)?);
+        let site_headers = parse_headers(&credential_headers)?;
+
+        let cookie_jar = if config.cookies {
+            let jar = match &config.cookie_file {
+                Some(path) => CookieJar::load(path)?,
+                None => CookieJar::new(),
+            };
+            Some(Arc::new(jar))
+        } else {
+            None
+        };
+
+        let transport = match transport {
+            Some(transport) => transport,
+            None => Box::new(HttpTransport::new(Self::build_client(
+                &config,
+                &headers,
+                cookie_jar.clone(),
+            )?)),
+        };
+
+        // Enforce read-only mode below everything else; only OAuth2 token requests may POST
+        let transport: Box<dyn Transport> = if config.safety.read_only {
+            let token_urls = config
+                .credential_scopes
+                .iter()
+                .filter_map(|scope| scope.auth.as_ref())
+                .chain(config.auth.as_ref())
+                .filter_map(|auth| match auth {
+                    Auth::OAuth2(oauth2) => Some(oauth2.token_url.clone()),
+                    _ => None,
+                });
+            info!("Read-only mode: only GET, HEAD and OPTIONS requests will be sent");
+            Box::new(
+                token_urls.fold(ReadOnlyTransport::new(transport), |t, url| {
+                    t.allow_post(url)
+                }),
+            )
+        } else {
+            transport
+        };
 
+        let authenticator = Authenticator::new(config.auth.as_ref())?;
+        let credential_scopes = config
+            .credential_scopes
+            .iter()
+            .map(|scope| {
+                Ok(ScopedCredentials {
+                    hosts: scope.hosts.clone(),
+                    headers: parse_headers(&scope.headers)?,
+                    authenticator: match &scope.auth {
+                        Some(auth) => Some(Authenticator::new(Some(auth))?),
+                        None => None,
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let domain_rules = config
+            .allowed_domains
+            .iter()
+            .map(|domain| HostPattern::parse_domain(domain))
+            .collect::<Result<Vec<_>>>()?;
         let semaphore = Arc::new(Semaphore::new(config.max_concurrent_requests));
 
         Ok(Self {
-            client,
+            transport,
+            authenticator,
+            site_headers,
+            credential_scopes,
+            domain_rules,
+            site: None,
             semaphore,
             visited_urls: HashSet::new(),
             url_queue: VecDeque::new(),
+            default_headers: headers,
+            har_entries: Mutex::new(Vec::new()),
+            har_http_version: Mutex::new(String::new()),
+            graphql_endpoints: Mutex::new(Vec::new()),
+            collection_totals: Mutex::new(HashMap::new()),
+            sampler: Sampler::new(config.sampling.clone()),
+            response_shapes: Mutex::new(HashMap::new()),
+            findings: Mutex::new(Vec::new()),
+            cookie_jar,
+            relogin_allowed: AtomicBool::new(true),
             config,
         })
     }
 
+    /// Build the HTTP client used by the default transport
+    fn build_client(
+        config: &CrawlerConfig,
+        headers: &reqwest::header::HeaderMap,
+        cookie_jar: Option<Arc<CookieJar>>,
+    ) -> Result<Client> {
+        let mut builder = Client::builder();
+        if let Some(jar) = cookie_jar {
+            builder = builder.cookie_provider(jar);
+        }
+
+        builder = config.proxy.apply(builder)?;
+
+        let client = config
+            .tls
+            .apply(builder)?
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .default_headers(headers.clone())
+            .redirect(if config.follow_redirects {
+                reqwest::redirect::Policy::limited(10)
+            } else {
+                reqwest::redirect::Policy::none()
+            })
+            .build()?;
+
+        Ok(client)
+    }
+
+    /// Get the configuration this crawler was created with
+    pub fn config(&self) -> &CrawlerConfig {
+        &self.config
+    }
+
+    /// Get the traffic recorded so far as a HAR document
+    ///
+    /// Empty unless `CrawlerConfig::record_har` is enabled.
+    pub fn har(&self) -> Har {
+        let entries = self
+            .har_entries
+            .lock()
+            .map(|entries| entries.clone())
+            .unwrap_or_default();
+        Har::new(entries)
+    }
+
+    /// Get the cookie jar, if cookies are enabled
+    pub fn cookie_jar(&self) -> Option<&CookieJar> {
+        self.cookie_jar.as_deref()
+    }
+
+    /// Run the configured login step, unless `reuse_session` is set and the cookie
+    /// jar already holds a session for its URL
+    async fn login(&self, reuse_session: bool) -> Result<()> {
+        let Some(login) = &self.config.login else {
+            return Ok(());
+        };
+
+        if reuse_session
+            && let Some(jar) = &self.cookie_jar
+            && login.has_session(jar)?
+        {
+            info!("Reusing saved session cookies for {}", login.url);
+            return Ok(());
+        }
+
+        info!("Logging in at {}", login.url);
+        let request = login.request()?;
+        let started_date_time = chrono::Utc::now();
+        let request_started = Instant::now();
+        let response = self.transport.fetch(&request).await?;
+        self.record_har_exchange(
+            &login.url,
+            &request,
+            &response,
+            started_date_time,
+            request_started.elapsed(),
+        );
+        login.check_status(response.status)
+    }
+
     /// Start crawling from the given URL
     pub async fn crawl(&mut self, start_url: &str) -> Result<CrawlResult> {
         info!("Starting crawl from: {}", start_url);
@@ -112,6 +367,10 @@ impl ApiCrawler {
 
         // Validate and normalize the starting URL
         let start_url = self.normalize_url(start_url)?;
+        self.site = HostPattern::site_of(&Url::parse(&start_url)?);
+
+        // Establish a session before the first request
+        self.login(true).await?;
 
         // Add the starting URL to the queue
         self.url_queue.push_back(QueueItem::new(start_url, 0, None));
@@ -133,54 +392,153 @@ impl ApiCrawler {
             }
 
             // Skip if already visited
-            if self.visited_urls.contains(&item.url) {
+            if self.visited_urls.contains(&item.visit_key()) {
                 debug!("Skipping already visited URL: {}", item.url);
                 result.stats.urls_skipped += 1;
                 continue;
             }
 
             // Check domain restrictions
-            if !self.is_domain_allowed(&item.url)? {
+            if !self.is_domain_allowed(&item.url) {
                 debug!("Skipping URL due to domain restriction: {}", item.url);
                 result.stats.urls_skipped += 1;
+                result.stats.skipped_by_rule.domain += 1;
                 continue;
             }
 
             // Mark as visited
-            self.visited_urls.insert(item.url.clone());
+            self.visited_urls.insert(item.visit_key());
 
             // Process the URL
-            match self.process_url(&item).await {
-                Ok(endpoints) => {
-                    result.stats.successful_requests += 1;
-                    result.stats.urls_processed += 1;
-                    result.stats.max_depth_reached = result.stats.max_depth_reached.max(item.depth);
+            let fetch_started = Instant::now();
+            let outcome = self.process_url(&item).await;
+            let mut visit = UrlVisit {
+                url: item.url.clone(),
+                depth: item.depth,
+                parent_url: item.parent_url.clone(),
+                method: item.probe.map(|probe| probe.method().to_string()),
+                status: None,
+                duration_ms: fetch_started.elapsed().as_millis(),
+                endpoints_found: 0,
+                error: None,
+            };
+
+            match outcome {
+                Ok((status, endpoints)) => {
+                    visit.status = Some(status);
+                    visit.endpoints_found = endpoints.len();
+                    if status >= 400 {
+                        // Error bodies are still searched for links, but the fetch counts as failed
+                        let e = CrawlerError::HttpStatus {
+                            status,
+                            url: item.url.clone(),
+                        };
+                        Self::record_failure(&mut result, &mut visit, &e);
+                    } else {
+                        result.stats.successful_requests += 1;
+                        result.stats.urls_processed += 1;
+                        result.stats.max_depth_reached =
+                            result.stats.max_depth_reached.max(item.depth);
+                    }
 
                     info!("Found {} endpoints at {}", endpoints.len(), item.url);
 
-                    for endpoint in endpoints {
+                    // Only a sample of links differing in an ID-like segment is followed
+                    let total = self
+                        .collection_totals
+                        .lock()
+                        .ok()
+                        .and_then(|mut totals| totals.remove(&item.url));
+                    let candidates: Vec<&ApiEndpoint> = endpoints
+                        .iter()
+                        .filter(|e| self.is_sample_candidate(e))
+                        .collect();
+                    let left_out = self
+                        .sampler
+                        .select(&candidates, total.map(|total| (item.url.as_str(), total)));
+
+                    for mut endpoint in endpoints {
+                        if left_out.contains(&endpoint.href) {
+                            debug!("Not following {} (not in sample)", endpoint.href);
+                            result.stats.urls_skipped += 1;
+                            result.stats.skipped_by_rule.record(FilterRule::Sampled);
+                            continue;
+                        }
+
+                        // Further pages of a collection stay at the collection's depth
+                        let is_page = pagination::is_page_link(&endpoint);
+                        if is_page {
+                            endpoint.depth = item.depth;
+                        }
+
                         // Add to results
                         result.add_endpoint(endpoint.clone());
 
                         // Queue for further crawling if it should be crawled
                         if endpoint.should_crawl() {
+                            // Safety rules come first, then the configured filters
+                            let filters = &self.config.filters;
+                            let (probe, rule) = match self.config.safety.check(&endpoint) {
+                                LinkSafety::Safe => (None, filters.check(&endpoint)),
+                                LinkSafety::Probe(probe) => (Some(probe), filters.check(&endpoint)),
+                                LinkSafety::UnsafeMethod => (None, Some(FilterRule::UnsafeMethod)),
+                                LinkSafety::DestructiveRel => {
+                                    (None, Some(FilterRule::DestructiveRel))
+                                }
+                            };
+                            if let Some(rule) = rule {
+                                debug!("Not following {} ({:?} rule)", endpoint.href, rule);
+                                result.stats.urls_skipped += 1;
+                                result.stats.skipped_by_rule.record(rule);
+                                continue;
+                            }
+
+                            let collection = is_page
+                                .then(|| item.collection.clone().unwrap_or(item.url.clone()));
                             let queue_item = QueueItem::new(
                                 endpoint.href.clone(),
-                                item.depth + 1,
+                                endpoint.depth,
                                 Some(item.url.clone()),
-                            );
+                            )
+                            .with_probe(probe)
+                            .with_collection(collection);
 
-                            if !self.visited_urls.contains(&endpoint.href) {
-                                self.url_queue.push_back(queue_item);
+                            if self.visited_urls.contains(&queue_item.visit_key()) {
+                                continue;
                             }
+                            if let Some(collection) = &queue_item.collection {
+                                let known =
+                                    result.collections.iter().find(|c| &c.url == collection);
+                                if *collection == queue_item.url
+                                    || known.is_some_and(|c| c.pages.contains(&queue_item.url))
+                                {
+                                    continue;
+                                }
+                                let pages = known.map_or(0, |c| c.pages.len());
+                                if !self.config.pagination.allows(pages) {
+                                    debug!(
+                                        "Not following {} (page limit of {} reached)",
+                                        queue_item.url, collection
+                                    );
+                                    result.collection_mut(collection).truncated = true;
+                                    result.stats.urls_skipped += 1;
+                                    result.stats.skipped_by_rule.record(FilterRule::PageLimit);
+                                    continue;
+                                }
+                                result
+                                    .collection_mut(collection)
+                                    .pages
+                                    .push(queue_item.url.clone());
+                            }
+                            self.url_queue.push_back(queue_item);
                         }
                     }
                 }
-                Err(e) => {
-                    error!("Failed to process URL {}: {}", item.url, e);
-                    result.stats.failed_requests += 1;
-                    result.stats.errors.push(format!("URL {}: {}", item.url, e));
-                }
+                Err(e) => Self::record_failure(&mut result, &mut visit, &e),
+            }
+
+            if self.config.record_visits {
+                result.visits.push(visit);
             }
 
             // Add delay between requests
@@ -189,8 +547,44 @@ impl ApiCrawler {
             }
         }
 
+        result.graphql = self
+            .graphql_endpoints
+            .lock()
+            .map(|mut endpoints| std::mem::take(&mut *endpoints))
+            .unwrap_or_default();
+        result.sampled_patterns = self.sampler.sampled_patterns();
+        result.findings = self
+            .findings
+            .lock()
+            .map(|mut findings| std::mem::take(&mut *findings))
+            .unwrap_or_default();
+        if self.config.graphql.introspect {
+            self.introspect_graphql(&mut result).await;
+        }
+        if self.config.method_probe.enabled {
+            self.probe_methods(&mut result).await;
+        }
+        if !self.config.representations.is_empty() {
+            self.probe_representations(&mut result).await;
+        }
+
+        routes::infer_routes(&mut result);
+        if self.config.schema.infer {
+            let shapes = self
+                .response_shapes
+                .lock()
+                .map(|mut shapes| std::mem::take(&mut *shapes))
+                .unwrap_or_default();
+            result.schemas = schema::build_schemas(shapes, &result, &self.config.schema);
+        }
         result.complete();
 
+        if let (Some(jar), Some(path)) = (&self.cookie_jar, &self.config.cookie_file)
+            && let Err(e) = jar.save(path)
+        {
+            error!("Failed to save cookies to {}: {}", path.display(), e);
+        }
+
         info!(
             "Crawling completed. Processed {} URLs, found {} endpoints in {}ms",
             result.stats.urls_processed,
@@ -201,8 +595,303 @@ impl ApiCrawler {
         Ok(result)
     }
 
-    /// Process a single URL and extract endpoints
-    async fn process_url(&self, item: &QueueItem) -> Result<Vec<ApiEndpoint>> {
+    /// Count a failed fetch and record its error
+    fn record_failure(result: &mut CrawlResult, visit: &mut UrlVisit, error: &CrawlerError) {
+        error!("Failed to process URL {}: {}", visit.url, error);
+        result.stats.failed_requests += 1;
+        result
+            .stats
+            .errors
+            .push(format!("URL {}: {}", visit.url, error));
+        visit.status = error.status();
+        visit.error = Some(error.to_string());
+    }
+
+    /// Send the introspection query to every detected GraphQL endpoint
+    ///
+    /// Failures are recorded on the endpoint and don't count as failed requests.
+    async fn introspect_graphql(&self, result: &mut CrawlResult) {
+        info!("Introspecting {} GraphQL endpoints", result.graphql.len());
+        for endpoint in &mut result.graphql {
+            match self.introspect(&endpoint.url).await {
+                Ok(schema) => {
+                    debug!(
+                        "{} has {} types, {} queries and {} mutations",
+                        endpoint.url,
+                        schema.types.len(),
+                        schema.queries.len(),
+                        schema.mutations.len()
+                    );
+                    endpoint.schema = Some(schema);
+                }
+                Err(e) => {
+                    debug!("Introspection of {} failed: {}", endpoint.url, e);
+                    endpoint.error = Some(e.to_string());
+                }
+            }
+
+            if self.config.delay_ms > 0 {
+                sleep(Duration::from_millis(self.config.delay_ms)).await;
+            }
+        }
+    }
+
+    /// Send one introspection query: a JSON POST, or a GET in read-only mode
+    async fn introspect(&self, url: &str) -> Result<GraphqlSchema> {
+        let _permit = self
+            .semaphore
+            .acquire()
+            .await
+            .map_err(|_| CrawlerError::config("Failed to acquire semaphore permit"))?;
+
+        // Recorded without the credentials added to the request URL
+        let target = if self.config.safety.read_only {
+            graphql::introspection_url(url)?
+        } else {
+            url.to_string()
+        };
+        let mut request = if self.config.safety.read_only {
+            let mut request = self.authenticated_request(&Method::GET, &target).await?;
+            // Servers with CSRF prevention reject simple GET requests without it
+            request.headers.insert(
+                "apollo-require-preflight",
+                reqwest::header::HeaderValue::from_static("true"),
+            );
+            request
+        } else {
+            let mut request = self.authenticated_request(&Method::POST, &target).await?;
+            request.headers.insert(
+                reqwest::header::CONTENT_TYPE,
+                reqwest::header::HeaderValue::from_static("application/json"),
+            );
+            request.body = Some(graphql::introspection_body());
+            request
+        };
+        request.headers.insert(
+            reqwest::header::ACCEPT,
+            reqwest::header::HeaderValue::from_static(
+                "application/graphql-response+json, application/json",
+            ),
+        );
+
+        let started_date_time = chrono::Utc::now();
+        let request_started = Instant::now();
+        let response = self.transport.fetch(&request).await?;
+        self.record_har_exchange(
+            &target,
+            &request,
+            &response,
+            started_date_time,
+            request_started.elapsed(),
+        );
+
+        // GraphQL servers may answer errors with 4xx, so the body is read first
+        match serde_json::from_slice::<Value>(&response.body) {
+            Ok(json) => GraphqlSchema::from_introspection(&json),
+            Err(_) if !response.is_success() => Err(CrawlerError::HttpStatus {
+                status: response.status,
+                url: url.to_string(),
+            }),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Send OPTIONS to every discovered endpoint in scope and record the methods it supports
+    ///
+    /// Probe failures are logged and don't count as failed requests.
+    async fn probe_methods(&self, result: &mut CrawlResult) {
+        let hrefs = self.probe_targets(result, false);
+        info!("Probing {} endpoints for supported methods", hrefs.len());
+        let outcomes: Vec<_> = stream::iter(hrefs)
+            .map(|href| async move {
+                let outcome = self.probe_endpoint(&href).await;
+                if self.config.delay_ms > 0 {
+                    sleep(Duration::from_millis(self.config.delay_ms)).await;
+                }
+                (href, outcome)
+            })
+            .buffered(self.config.max_concurrent_requests)
+            .collect()
+            .await;
+
+        let mut supported = HashMap::new();
+        for (href, outcome) in outcomes {
+            match outcome {
+                Ok(Some(methods)) => {
+                    debug!("{} supports {}", href, methods.methods.join(", "));
+                    supported.insert(href, methods);
+                }
+                Ok(None) => debug!("{} advertised no methods", href),
+                Err(e) => debug!("Method probe of {} failed: {}", href, e),
+            }
+        }
+
+        let mappings = result.url_mappings.values_mut().flatten();
+        for endpoint in result.endpoints.iter_mut().chain(mappings) {
+            endpoint.supported_methods = supported.get(&endpoint.href).cloned();
+        }
+        result.stats.methods_probed = supported.len();
+    }
+
+    /// Distinct endpoint URLs to probe after the crawl, at most `max_urls` of them
+    ///
+    /// Endpoints outside the allowed domains, rejected by the filters or left out
+    /// of their pattern's sample are skipped. With `get_only`, URLs that any link
+    /// marks as unsafe to GET are left out too.
+    fn probe_targets(&self, result: &CrawlResult, get_only: bool) -> Vec<String> {
+        let unsafe_hrefs: HashSet<&str> = result
+            .endpoints
+            .iter()
+            .filter(|endpoint| get_only && self.config.safety.check(endpoint) != LinkSafety::Safe)
+            .map(|endpoint| endpoint.href.as_str())
+            .collect();
+
+        let mut hrefs: Vec<String> = Vec::new();
+        for endpoint in &result.endpoints {
+            let href = &endpoint.href;
+            if hrefs.contains(href)
+                || unsafe_hrefs.contains(href.as_str())
+                || !self.is_domain_allowed(href)
+                || self.config.filters.check(endpoint).is_some()
+                || self.sampler.is_sampled_out(href)
+            {
+                continue;
+            }
+            hrefs.push(href.clone());
+        }
+        if self.config.max_urls > 0 {
+            hrefs.truncate(self.config.max_urls);
+        }
+        hrefs
+    }
+
+    /// Request every configured media type from each endpoint and record the ones served
+    ///
+    /// A representation counts as offered when the server answers 2xx with a
+    /// matching `Content-Type`; probe failures are logged and otherwise ignored.
+    async fn probe_representations(&self, result: &mut CrawlResult) {
+        let hrefs = self.probe_targets(result, true);
+        info!(
+            "Probing {} endpoints for {} representations",
+            hrefs.len(),
+            self.config.representations.len()
+        );
+
+        let probes: Vec<(String, MediaType)> = hrefs
+            .iter()
+            .flat_map(|href| {
+                self.config
+                    .representations
+                    .iter()
+                    .map(|wanted| (href.clone(), wanted.clone()))
+            })
+            .collect();
+        let outcomes: Vec<_> = stream::iter(probes)
+            .map(|(href, wanted)| async move {
+                let outcome = self.probe_representation(&href, &wanted).await;
+                if self.config.delay_ms > 0 {
+                    sleep(Duration::from_millis(self.config.delay_ms)).await;
+                }
+                (href, wanted, outcome)
+            })
+            .buffered(self.config.max_concurrent_requests)
+            .collect()
+            .await;
+
+        let mut offered: HashMap<String, Vec<String>> = HashMap::new();
+        for (href, wanted, outcome) in outcomes {
+            match outcome {
+                Ok(Some(served)) => {
+                    debug!("{} serves {}", href, served);
+                    let served_types = offered.entry(href.clone()).or_default();
+                    if !served_types.contains(&served) {
+                        served_types.push(served);
+                    }
+                }
+                Ok(None) => debug!("{} doesn't serve {}", href, wanted),
+                Err(e) => debug!("Representation probe of {} failed: {}", href, e),
+            }
+        }
+
+        let mappings = result.url_mappings.values_mut().flatten();
+        for endpoint in result.endpoints.iter_mut().chain(mappings) {
+            endpoint.representations = offered.get(&endpoint.href).cloned().unwrap_or_default();
+        }
+    }
+
+    /// GET a URL asking only for one media type, returning the served type if it matches
+    async fn probe_representation(&self, url: &str, wanted: &MediaType) -> Result<Option<String>> {
+        let _permit = self
+            .semaphore
+            .acquire()
+            .await
+            .map_err(|_| CrawlerError::config("Failed to acquire semaphore permit"))?;
+
+        let mut request = self.authenticated_request(&Method::GET, url).await?;
+        request.headers.insert(
+            reqwest::header::ACCEPT,
+            wanted
+                .essence()
+                .parse()
+                .map_err(|_| CrawlerError::config("Invalid media type"))?,
+        );
+
+        let started_date_time = chrono::Utc::now();
+        let request_started = Instant::now();
+        let response = self.transport.fetch(&request).await?;
+        self.record_har_exchange(
+            url,
+            &request,
+            &response,
+            started_date_time,
+            request_started.elapsed(),
+        );
+
+        Ok(response
+            .media_type()
+            .filter(|served| response.is_success() && served.satisfies(wanted))
+            .map(|served| served.essence()))
+    }
+
+    /// Send one OPTIONS request, as a CORS preflight when an origin is configured
+    async fn probe_endpoint(&self, url: &str) -> Result<Option<SupportedMethods>> {
+        let _permit = self
+            .semaphore
+            .acquire()
+            .await
+            .map_err(|_| CrawlerError::config("Failed to acquire semaphore permit"))?;
+
+        let mut request = self.authenticated_request(&Method::OPTIONS, url).await?;
+        if let Some(origin) = &self.config.method_probe.cors_origin {
+            request.headers.insert(
+                reqwest::header::ORIGIN,
+                origin
+                    .parse()
+                    .map_err(|_| CrawlerError::config("Invalid CORS origin"))?,
+            );
+            request.headers.insert(
+                reqwest::header::ACCESS_CONTROL_REQUEST_METHOD,
+                reqwest::header::HeaderValue::from_static("GET"),
+            );
+        }
+
+        let started_date_time = chrono::Utc::now();
+        let request_started = Instant::now();
+        let response = self.transport.fetch(&request).await?;
+        self.record_har_exchange(
+            url,
+            &request,
+            &response,
+            started_date_time,
+            request_started.elapsed(),
+        );
+
+        // 405 responses must carry `Allow`, so headers are read whatever the status
+        Ok(SupportedMethods::from_headers(&response.headers))
+    }
+
+    /// Process a single URL and extract endpoints, returning the response status with them
+    async fn process_url(&self, item: &QueueItem) -> Result<(u16, Vec<ApiEndpoint>)> {
         let _permit = self
             .semaphore
             .acquire()
@@ -212,27 +901,346 @@ impl ApiCrawler {
         debug!("Processing URL at depth {}: {}", item.depth, item.url);
 
         // Make HTTP request
-        let response = self.client.get(&item.url).send().await?;
+        let started_date_time = chrono::Utc::now();
+        let request_started = Instant::now();
+        let method = item.probe.map_or(Method::GET, |probe| probe.method());
+        let (request, response) = match self.fetch_authenticated(method, &item.url).await {
+            Ok(exchange) => exchange,
+            Err(e) => {
+                self.record_har_failure(item, started_date_time, request_started, &e);
+                return Err(e);
+            }
+        };
+        let elapsed = request_started.elapsed();
+
+        // Check the response is JSON (including `+json` types such as HAL), XML or HTML
+        let media_type = response.media_type();
 
-        // Check if response is JSON
-        let content_type = response
-            .headers()
-            .get(reqwest::header::CONTENT_TYPE)
-            .and_then(|ct| ct.to_str().ok())
-            .unwrap_or("");
+        self.record_har_exchange(&item.url, &request, &response, started_date_time, elapsed);
 
-        if !content_type.contains("application/json")
-            && !content_type.contains("application/hal+json")
+        // Detected whatever the status, as GraphQL servers answer a plain GET with 400 or 405
+        if item.probe.is_none()
+            && let Some(detected_by) =
+                graphql::detect(&item.url, media_type.as_ref(), &response.body)
         {
-            debug!("Skipping non-JSON response from {}", item.url);
-            return Ok(Vec::new());
+            self.record_graphql(&item.url, detected_by);
         }
 
-        // Parse JSON response
-        let json: Value = response.json().await?;
+        if item.probe.is_some() {
+            debug!(
+                "Probed {} {}: {}",
+                request.method, item.url, response.status
+            );
+            return Ok((response.status, Vec::new()));
+        }
 
-        // Extract endpoints from JSON
-        self.extract_endpoints_from_json(&json, item)
+        // Page links in `Link` headers apply whatever the body
+        let header_pages = pagination::link_header_endpoints(&response.headers, item);
+
+        let Some(media_type) = media_type.filter(|m| m.is_json() || m.is_xml() || m.is_html())
+        else {
+            debug!(
+                "Skipping response from {} with no extractable links",
+                item.url
+            );
+            return Ok((response.status, header_pages));
+        };
+        let body = media_type.utf8_body(&response.body);
+
+        // XHTML is also XML, so HTML is checked first
+        let mut endpoints = if media_type.is_html() {
+            let page = String::from_utf8_lossy(&body);
+            let in_scope = |url: &str| self.is_domain_allowed(url);
+            html::extract_endpoints(&page, item, in_scope)
+        } else if media_type.is_xml() {
+            let xml = String::from_utf8_lossy(&body);
+            match xml::extract_endpoints(&xml, item, &self.config.xml_selectors) {
+                Ok(endpoints) => endpoints,
+                // The status is the more useful failure than an unparseable error page
+                Err(_) if !response.is_success() => return Ok((response.status, header_pages)),
+                Err(e) => return Err(e),
+            }
+        } else {
+            // Parse JSON response and extract endpoints, including pagination fields
+            let json: Value = match serde_json::from_slice(&body) {
+                Ok(json) => json,
+                Err(_) if !response.is_success() => return Ok((response.status, header_pages)),
+                Err(e) => return Err(e.into()),
+            };
+            if let Some(total) = sampling::reported_total(&json)
+                && let Ok(mut totals) = self.collection_totals.lock()
+            {
+                totals.insert(item.url.clone(), total);
+            }
+            // Error bodies have their own shape, so only successful responses describe the route
+            if self.config.schema.infer && response.is_success() {
+                self.record_shape(&item.url, &json);
+            }
+            if self.config.sensitive.enabled
+                && let Ok(mut findings) = self.findings.lock()
+            {
+                findings.extend(sensitive::scan(&item.url, &json, &self.config.sensitive));
+            }
+            let mut endpoints = self.extract_endpoints_from_json(&json, item)?;
+            resolve_relative_hrefs(&mut endpoints, item);
+            let pages = pagination::body_endpoints(&json, item);
+            // A `next_page_url` field is also found as a plain URL field
+            endpoints.retain(|e| e.rel.is_some() || !pages.iter().any(|p| p.href == e.href));
+            endpoints.extend(pages);
+            endpoints
+        };
+
+        for page in header_pages {
+            if !endpoints
+                .iter()
+                .any(|e| e.href == page.href && e.rel == page.rel)
+            {
+                endpoints.push(page);
+            }
+        }
+        Ok((response.status, endpoints))
+    }
+
+    /// Scoped headers and the authenticator to use for a URL
+    ///
+    /// The first credential scope matching the URL's host wins; its authenticator
+    /// replaces the crawler-wide one when it has its own `Auth`. Hosts matching no
+    /// scope get the crawler-wide credentials only if they are on the start URL's site.
+    fn credentials_for(
+        &self,
+        url: &str,
+    ) -> (Option<&reqwest::header::HeaderMap>, Option<&Authenticator>) {
+        let Ok(parsed) = Url::parse(url) else {
+            return (None, None);
+        };
+        let scope = self
+            .credential_scopes
+            .iter()
+            .find(|scope| scope.hosts.matches_url(&parsed));
+
+        match scope {
+            Some(scope) => (
+                Some(&scope.headers),
+                Some(scope.authenticator.as_ref().unwrap_or(&self.authenticator)),
+            ),
+            None if self.site.as_ref().is_some_and(|s| s.matches_url(&parsed)) => {
+                (Some(&self.site_headers), Some(&self.authenticator))
+            }
+            None => (None, None),
+        }
+    }
+
+    /// Build a request for a URL with host-scoped credentials applied
+    async fn authenticated_request(&self, method: &Method, url: &str) -> Result<TransportRequest> {
+        let (headers, authenticator) = self.credentials_for(url);
+        let mut request = TransportRequest::get(url);
+        request.method = method.clone();
+        if let Some(headers) = headers {
+            request.headers.extend(headers.clone());
+        }
+        if let Some(authenticator) = authenticator {
+            authenticator
+                .apply(&mut request, self.transport.as_ref())
+                .await?;
+        }
+        Ok(request)
+    }
+
+    /// Fetch a URL with credentials applied, refreshing them once on a 401
+    async fn fetch_authenticated(
+        &self,
+        method: Method,
+        url: &str,
+    ) -> Result<(TransportRequest, TransportResponse)> {
+        let request = self.authenticated_request(&method, url).await?;
+        let response = self.transport.fetch(&request).await?;
+
+        if response.status == 401
+            && let (_, Some(authenticator)) = self.credentials_for(url)
+            && authenticator.can_refresh()
+        {
+            info!("Received 401 from {}, refreshing credentials", url);
+            authenticator.invalidate().await;
+
+            let retry = self.authenticated_request(&method, url).await?;
+            let response = self.transport.fetch(&retry).await?;
+            return Ok((retry, response));
+        }
+
+        // The session may have expired, or saved cookies may be stale
+        if response.status == 401
+            && self.config.login.is_some()
+            && self.relogin_allowed.load(Ordering::SeqCst)
+        {
+            info!("Received 401 from {}, logging in again", url);
+            self.login(false).await?;
+
+            let retry = self.authenticated_request(&method, url).await?;
+            let response = self.transport.fetch(&retry).await?;
+            if response.status == 401 {
+                debug!("Still 401 after logging in again; not logging in for further 401s");
+                self.relogin_allowed.store(false, Ordering::SeqCst);
+            }
+            return Ok((retry, response));
+        }
+
+        Ok((request, response))
+    }
+
+    /// Whether a discovered link would be followed, and so counts towards its pattern's sample
+    fn is_sample_candidate(&self, endpoint: &ApiEndpoint) -> bool {
+        endpoint.should_crawl()
+            && !pagination::is_page_link(endpoint)
+            && self.config.safety.check(endpoint) == LinkSafety::Safe
+            && self.config.filters.check(endpoint).is_none()
+            && self.is_domain_allowed(&endpoint.href)
+    }
+
+    /// Remember a detected GraphQL endpoint, once per URL
+    fn record_graphql(&self, url: &str, detected_by: graphql::Detection) {
+        if let Ok(mut endpoints) = self.graphql_endpoints.lock()
+            && !endpoints.iter().any(|e| e.url == url)
+        {
+            info!("Detected GraphQL endpoint {} (by {})", url, detected_by);
+            endpoints.push(GraphqlEndpoint {
+                url: url.to_string(),
+                detected_by,
+                schema: None,
+                error: None,
+            });
+        }
+    }
+
+    /// Merge a JSON body into the shape recorded for its URL
+    fn record_shape(&self, url: &str, json: &Value) {
+        if let Some(key) = schema::shape_key(url)
+            && let Ok(mut shapes) = self.response_shapes.lock()
+        {
+            shapes
+                .entry(key)
+                .or_default()
+                .merge(json, self.config.schema.max_enum_values);
+        }
+    }
+
+    /// Record a HAR entry if recording is enabled
+    fn push_har_entry(&self, entry: HarEntry) {
+        if let Ok(mut entries) = self.har_entries.lock() {
+            entries.push(entry);
+        }
+    }
+
+    /// Record a completed request and its response if HAR recording is enabled
+    fn record_har_exchange(
+        &self,
+        url: &str,
+        request: &TransportRequest,
+        response: &TransportResponse,
+        started_date_time: chrono::DateTime<chrono::Utc>,
+        elapsed: Duration,
+    ) {
+        if !self.config.record_har {
+            return;
+        }
+
+        let redirect_url = response
+            .headers
+            .get(reqwest::header::LOCATION)
+            .and_then(|l| l.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+        let mut request_headers = self.default_headers.clone();
+        request_headers.extend(request.headers.clone());
+        if let Ok(mut version) = self.har_http_version.lock() {
+            version.clone_from(&response.http_version);
+        }
+
+        self.push_har_entry(HarEntry {
+            started_date_time: started_date_time.to_rfc3339(),
+            time: elapsed.as_secs_f64() * 1000.0,
+            request: HarRequest::new(
+                request.method.as_str(),
+                url,
+                &response.http_version,
+                har_headers(&request_headers),
+            ),
+            response: HarResponse {
+                status: response.status,
+                status_text: reqwest::StatusCode::from_u16(response.status)
+                    .ok()
+                    .and_then(|s| s.canonical_reason())
+                    .unwrap_or("")
+                    .to_string(),
+                http_version: response.http_version.clone(),
+                cookies: Vec::new(),
+                headers: har_headers(&response.headers),
+                content: HarContent::from_body(
+                    &response.body,
+                    response.content_type(),
+                    self.config.har_body_limit,
+                ),
+                redirect_url,
+                headers_size: -1,
+                body_size: response.body.len() as i64,
+            },
+            cache: Default::default(),
+            timings: HarTimings::new(elapsed.as_secs_f64() * 1000.0, 0.0),
+            comment: None,
+        });
+    }
+
+    /// Record a request that failed before any response was received
+    fn record_har_failure(
+        &self,
+        item: &QueueItem,
+        started_date_time: chrono::DateTime<chrono::Utc>,
+        request_started: Instant,
+        error: &CrawlerError,
+    ) {
+        if !self.config.record_har {
+            return;
+        }
+
+        let elapsed_ms = request_started.elapsed().as_secs_f64() * 1000.0;
+        // Unknown until the transport has answered a request
+        let http_version = self
+            .har_http_version
+            .lock()
+            .map(|version| version.clone())
+            .unwrap_or_default();
+        self.push_har_entry(HarEntry {
+            started_date_time: started_date_time.to_rfc3339(),
+            time: elapsed_ms,
+            request: HarRequest::new(
+                item.probe
+                    .map_or(Method::GET, |probe| probe.method())
+                    .as_str(),
+                &item.url,
+                &http_version,
+                har_headers(&self.default_headers),
+            ),
+            response: HarResponse {
+                status: 0,
+                status_text: String::new(),
+                http_version: String::new(),
+                cookies: Vec::new(),
+                headers: Vec::new(),
+                content: HarContent {
+                    size: 0,
+                    mime_type: String::new(),
+                    text: None,
+                    encoding: None,
+                    truncated: false,
+                    comment: None,
+                },
+                redirect_url: String::new(),
+                headers_size: -1,
+                body_size: -1,
+            },
+            cache: Default::default(),
+            timings: HarTimings::new(elapsed_ms, 0.0),
+            comment: Some(error.to_string()),
+        });
     }
 
     /// Extract API endpoints from a JSON response
@@ -352,31 +1360,31 @@ impl ApiCrawler {
 
         // Look for URL patterns in other fields
         for (key, value) in obj {
-            if key.contains("url") || key.contains("uri") || key.ends_with("_link") {
-                if let Some(url_str) = value.as_str() {
-                    if self.looks_like_url(url_str) {
-                        let endpoint = ApiEndpoint::new(url_str.to_string(), parent_item.depth + 1)
-                            .with_parent(Some(parent_item.url.clone()))
-                            .with_metadata(format!("source_field"), Value::String(key.clone()));
-
-                        temp_endpoints.push(endpoint);
-                    }
-                }
+            if (key.contains("url") || key.contains("uri") || key.ends_with("_link"))
+                && let Some(url_str) = value.as_str()
+                && self.looks_like_url(url_str)
+            {
+                let endpoint = ApiEndpoint::new(url_str.to_string(), parent_item.depth + 1)
+                    .with_parent(Some(parent_item.url.clone()))
+                    .with_metadata("source_field".to_string(), Value::String(key.clone()));
+
+                temp_endpoints.push(endpoint);
             }
         }
 
-        // Recursively process nested objects and arrays, but skip the links array to avoid duplicates
+        // Recursively process nested objects and arrays, but skip links already processed above;
+        // re-walking them would yield rel-less duplicates that bypass rel and method filters
         for (key, value) in obj {
-            if key == "links" {
-                continue; // We already processed this above
+            if key == "links" || key == "_links" {
+                continue;
             }
             match value {
                 Value::Object(nested_obj) => {
                     self.extract_from_object(nested_obj, parent_item, &mut temp_endpoints)?;
                 }
-                Value::Array(arr) => {
+                Value::Array(arr)
                     // Skip processing arrays that are links arrays
-                    if key != "_links" {
+                    if key != "_links" => {
                         for item in arr {
                             if let Value::Object(nested_obj) = item {
                                 self.extract_from_object(
@@ -387,7 +1395,6 @@ impl ApiCrawler {
                             }
                         }
                     }
-                }
                 _ => {}
             }
         }
@@ -472,17 +1479,37 @@ impl ApiCrawler {
         Ok(parsed.to_string())
     }
 
-    /// Check if a domain is allowed based on configuration
-    fn is_domain_allowed(&self, url: &str) -> Result<bool> {
-        if self.config.allowed_domains.is_empty() {
-            return Ok(true);
+    /// Check if a URL's host is in scope: `allowed_domains` if any, otherwise the start URL's site
+    ///
+    /// URLs that can't be parsed are never in scope.
+    fn is_domain_allowed(&self, url: &str) -> bool {
+        let Ok(parsed) = Url::parse(url) else {
+            return false;
+        };
+        if !self.domain_rules.is_empty() {
+            return self
+                .domain_rules
+                .iter()
+                .any(|rule| rule.matches_url(&parsed));
+        }
+
+        match &self.site {
+            Some(site) if self.config.same_site => site.matches_url(&parsed),
+            _ => true,
         }
+    }
+}
 
-        let parsed = Url::parse(url)?;
-        if let Some(domain) = parsed.domain() {
-            Ok(self.config.allowed_domains.contains(domain))
-        } else {
-            Ok(false)
+/// Resolve relative hrefs found in a JSON body against the URL it was fetched from
+fn resolve_relative_hrefs(endpoints: &mut [ApiEndpoint], item: &QueueItem) {
+    let Ok(base) = Url::parse(&item.url) else {
+        return;
+    };
+    for endpoint in endpoints {
+        if Url::parse(&endpoint.href).is_err()
+            && let Ok(resolved) = base.join(&endpoint.href)
+        {
+            endpoint.href = resolved.to_string();
         }
     }
 }
@@ -642,4 +1669,1280 @@ mod tests {
             assert_eq!(next_endpoint.href, "http://example.com/next");
         }
     }
+
+    #[tokio::test]
+    async fn test_har_replay_records_failures_with_transport_version() {
+        use crate::transport::HarTransport;
+
+        let body = json!({"_links": {"gone": {"href": "http://example.com/gone"}}}).to_string();
+        let recorded = HarEntry {
+            started_date_time: "2024-01-01T00:00:00Z".to_string(),
+            time: 1.0,
+            request: HarRequest::new("GET", "http://example.com/", "HTTP/2.0", Vec::new()),
+            response: HarResponse {
+                status: 200,
+                status_text: "OK".to_string(),
+                http_version: "HTTP/2.0".to_string(),
+                cookies: Vec::new(),
+                headers: Vec::new(),
+                content: HarContent::from_body(body.as_bytes(), "application/json", 0),
+                redirect_url: String::new(),
+                headers_size: -1,
+                body_size: body.len() as i64,
+            },
+            cache: Default::default(),
+            timings: HarTimings::new(1.0, 0.0),
+            comment: None,
+        };
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            ..CrawlerConfig::default()
+        }
+        .record_har(0);
+        let transport = HarTransport::new(Har::new(vec![recorded]));
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+        let result = crawler.crawl("http://example.com/").await.unwrap();
+        assert_eq!(result.stats.failed_requests, 1);
+
+        let entries = crawler.har().log.entries;
+        assert_eq!(entries[0].response.content.text.as_deref(), Some(&*body));
+        let failed = &entries[1];
+        assert_eq!(failed.request.url, "http://example.com/gone");
+        assert_eq!(failed.response.status, 0);
+        assert_eq!(failed.request.http_version, "HTTP/2.0");
+    }
+
+    #[tokio::test]
+    async fn test_offline_crawl_from_fixtures() {
+        use crate::transport::FixtureTransport;
+
+        let dir = tempfile::tempdir().unwrap();
+        let api_dir = dir.path().join("example.com").join("api");
+        std::fs::create_dir_all(&api_dir).unwrap();
+        std::fs::write(
+            dir.path().join("example.com").join("api.json"),
+            json!({
+                "_links": {
+                    "self": {"href": "http://example.com/api"},
+                    "users": {"href": "http://example.com/api/users"},
+                    "broken": {"href": "http://example.com/api/broken"}
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        std::fs::write(
+            api_dir.join("users.json"),
+            json!({"_links": {"first": {"href": "http://example.com/api/users/1"}}}).to_string(),
+        )
+        .unwrap();
+        std::fs::create_dir_all(api_dir.join("users")).unwrap();
+        std::fs::write(api_dir.join("users").join("1.json"), "{}").unwrap();
+
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            record_visits: true,
+            ..CrawlerConfig::default()
+        };
+        let transport = FixtureTransport::new(dir.path()).unwrap();
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+
+        let result = crawler.crawl("http://example.com/api").await.unwrap();
+
+        let visited: Vec<&str> = result.visits.iter().map(|v| v.url.as_str()).collect();
+        assert_eq!(
+            visited,
+            vec![
+                "http://example.com/api",
+                "http://example.com/api/broken",
+                "http://example.com/api/users",
+                "http://example.com/api/users/1",
+            ]
+        );
+        assert_eq!(result.stats.successful_requests, 3);
+        assert_eq!(result.stats.failed_requests, 1);
+        assert_eq!(result.visits[1].status, Some(404));
+        assert!(
+            result
+                .endpoints
+                .iter()
+                .any(|e| e.href == "http://example.com/api/users/1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_responses_fail_but_their_links_are_followed() {
+        use crate::transport::stub::StubTransport;
+
+        let transport = StubTransport::new().respond(
+            "http://example.com/api",
+            404,
+            &[("content-type", "application/problem+json")],
+            json!({"title": "Not Found", "_links": {"home": {"href": "http://example.com/"}}})
+                .to_string(),
+        );
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            ..CrawlerConfig::default()
+        };
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+        let result = crawler.crawl("http://example.com/api").await.unwrap();
+
+        assert_eq!(result.stats.failed_requests, 1);
+        assert_eq!(result.stats.successful_requests, 1);
+        assert_eq!(
+            result.stats.errors,
+            ["URL http://example.com/api: HTTP status 404 returned by http://example.com/api"]
+        );
+        assert!(
+            result
+                .endpoints
+                .iter()
+                .any(|e| e.href == "http://example.com/")
+        );
+        // Visits are only kept when asked for
+        assert!(result.visits.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_crawl_follows_atom_and_xml_links() {
+        use crate::transport::FixtureTransport;
+        use crate::xml::XmlSelector;
+
+        let dir = tempfile::tempdir().unwrap();
+        let site = dir.path().join("example.com");
+        std::fs::create_dir_all(site.join("posts")).unwrap();
+        std::fs::write(
+            site.join("service.xml"),
+            r#"<service xmlns="http://www.w3.org/2007/app" xmlns:atom="http://www.w3.org/2005/Atom">
+                 <workspace><collection href="/posts.atom"><atom:title>Posts</atom:title></collection></workspace>
+               </service>"#,
+        )
+        .unwrap();
+        std::fs::write(
+            site.join("posts.atom"),
+            r#"<feed xmlns="http://www.w3.org/2005/Atom">
+                 <link rel="related" href="catalog.xml"/>
+                 <entry><link href="posts/1"/></entry>
+               </feed>"#,
+        )
+        .unwrap();
+        std::fs::write(
+            site.join("catalog.xml"),
+            r#"<catalog><resource uri="/posts/2"/></catalog>"#,
+        )
+        .unwrap();
+        std::fs::write(site.join("posts").join("1.json"), "{}").unwrap();
+        std::fs::write(site.join("posts").join("2.json"), "{}").unwrap();
+
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            record_visits: true,
+            ..CrawlerConfig::default()
+        }
+        .xml_selector(XmlSelector::parse("//resource/@uri").unwrap());
+        let transport = FixtureTransport::new(dir.path()).unwrap();
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+        let result = crawler
+            .crawl("http://example.com/service.xml")
+            .await
+            .unwrap();
+
+        let visited: Vec<&str> = result.visits.iter().map(|v| v.url.as_str()).collect();
+        assert_eq!(
+            visited,
+            [
+                "http://example.com/service.xml",
+                "http://example.com/posts.atom",
+                "http://example.com/catalog.xml",
+                "http://example.com/posts/1",
+                "http://example.com/posts/2",
+            ]
+        );
+        assert_eq!(result.stats.failed_requests, 0);
+        let collection = &result.endpoints[0];
+        assert_eq!(collection.rel.as_deref(), Some("collection"));
+        assert_eq!(collection.title.as_deref(), Some("Posts"));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_bridges_from_html_portal() {
+        use crate::transport::FixtureTransport;
+
+        let dir = tempfile::tempdir().unwrap();
+        let site = dir.path().join("example.com");
+        std::fs::create_dir_all(&site).unwrap();
+        std::fs::write(
+            site.join("docs.html"),
+            r#"<html><body>
+                 <a href="/api">Browse the API</a>
+                 <a href="https://github.com/example/api">Source</a>
+                 <form action="/api/session" method="post"><input name="token"></form>
+               </body></html>"#,
+        )
+        .unwrap();
+        std::fs::write(
+            site.join("api.json"),
+            json!({"_links": {"users": {"href": "http://example.com/api/users"}}}).to_string(),
+        )
+        .unwrap();
+
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            record_visits: true,
+            ..CrawlerConfig::default()
+        };
+        let transport = FixtureTransport::new(dir.path()).unwrap();
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+        let result = crawler.crawl("http://example.com/docs.html").await.unwrap();
+
+        let visited: Vec<&str> = result.visits.iter().map(|v| v.url.as_str()).collect();
+        assert_eq!(
+            visited,
+            [
+                "http://example.com/docs.html",
+                "http://example.com/api",
+                "http://example.com/api/users",
+            ]
+        );
+        assert!(!result.endpoints.iter().any(|e| e.href.contains("github")));
+        // The POST form is recorded but never submitted
+        assert!(
+            result
+                .endpoints
+                .iter()
+                .any(|e| e.href == "http://example.com/api/session")
+        );
+        assert_eq!(result.stats.skipped_by_rule.unsafe_method, 1);
+    }
+
+    #[tokio::test]
+    async fn test_filters_skip_links_before_queueing() {
+        use crate::filter::UrlFilters;
+        use crate::transport::FixtureTransport;
+
+        let dir = tempfile::tempdir().unwrap();
+        let host = dir.path().join("example.com");
+        std::fs::create_dir_all(host.join("api")).unwrap();
+        std::fs::write(
+            host.join("api.json"),
+            json!({
+                "_links": {
+                    "users": {"href": "http://example.com/api/users"},
+                    "audit": {"href": "http://example.com/api/audit"},
+                    "admin": {"href": "http://example.com/api/admin"},
+                    "docs": {"href": "http://example.com/docs"},
+                    "check": {"href": "http://example.com/api/health", "method": "HEAD"}
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        std::fs::write(host.join("api").join("users.json"), "{}").unwrap();
+
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            record_visits: true,
+            ..CrawlerConfig::default()
+        }
+        .filters(
+            UrlFilters::new()
+                .include("/api/**")
+                .unwrap()
+                .exclude("/api/admin")
+                .unwrap()
+                .deny_rel("audit")
+                .deny_method("HEAD"),
+        );
+        let transport = FixtureTransport::new(dir.path()).unwrap();
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+
+        let result = crawler.crawl("http://example.com/api").await.unwrap();
+
+        let visited: Vec<&str> = result.visits.iter().map(|v| v.url.as_str()).collect();
+        assert_eq!(
+            visited,
+            vec!["http://example.com/api", "http://example.com/api/users"]
+        );
+        let skips = result.stats.skipped_by_rule;
+        assert_eq!(
+            (skips.not_included, skips.excluded, skips.rel, skips.method),
+            (1, 1, 1, 1)
+        );
+        // Skipped links are still reported as discovered endpoints
+        assert!(
+            result
+                .endpoints
+                .iter()
+                .any(|e| e.href == "http://example.com/docs")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_domain_scope_defaults_to_start_site() {
+        use crate::transport::stub::StubTransport;
+
+        fn link_server() -> StubTransport {
+            StubTransport::new().json(
+                "https://api.example.com/",
+                json!({"_links": {
+                    "docs": {"href": "https://docs.example.com/"},
+                    "status": {"href": "http://10.0.0.5:8080/status"},
+                    "metrics": {"href": "http://10.0.0.5:9090/metrics"},
+                    "other": {"href": "https://example.org/"}
+                }}),
+            )
+        }
+
+        async fn visited(config: CrawlerConfig) -> (Vec<String>, usize) {
+            let config = CrawlerConfig {
+                delay_ms: 0,
+                record_visits: true,
+                ..config
+            };
+            let mut crawler = ApiCrawler::with_transport(config, Box::new(link_server())).unwrap();
+            let result = crawler.crawl("https://api.example.com/").await.unwrap();
+            let mut urls: Vec<String> = result.visits.into_iter().map(|v| v.url).collect();
+            urls.sort();
+            (urls, result.stats.skipped_by_rule.domain)
+        }
+
+        // Same site as the start URL by default
+        let (urls, skipped) = visited(CrawlerConfig::default()).await;
+        assert_eq!(
+            urls,
+            ["https://api.example.com/", "https://docs.example.com/"]
+        );
+        assert_eq!(skipped, 3);
+
+        // Explicit rules: an IP host with a port, and an exact host via its domain
+        let config = CrawlerConfig::default()
+            .allow_domain("api.example.com".to_string())
+            .allow_domain("10.0.0.5:8080".to_string());
+        let (urls, _) = visited(config).await;
+        assert_eq!(
+            urls,
+            ["http://10.0.0.5:8080/status", "https://api.example.com/"]
+        );
+
+        let (urls, skipped) = visited(CrawlerConfig::default().any_domain()).await;
+        assert_eq!(urls.len(), 5);
+        assert_eq!(skipped, 0);
+
+        let invalid = CrawlerConfig::default().allow_domain("api.*.com".to_string());
+        assert!(matches!(
+            ApiCrawler::new(invalid),
+            Err(CrawlerError::Config { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unsafe_links_are_never_fetched() {
+        use crate::safety::ProbeMethod;
+        use crate::session::LoginStep;
+        use crate::transport::stub::StubTransport;
+
+        async fn requests(config: CrawlerConfig) -> (Vec<String>, CrawlResult) {
+            let transport = StubTransport::new().json(
+                "http://example.com/orders",
+                json!({"_links": {
+                    "next": {"href": "http://example.com/orders?page=2"},
+                    "create": {"href": "http://example.com/orders/new", "method": "POST"},
+                    "cancel": {"href": "http://example.com/orders/1/cancel"},
+                    "ea:logout": {"href": "http://example.com/logout"}
+                }}),
+            );
+            let log = transport.log();
+            let config = CrawlerConfig {
+                delay_ms: 0,
+                record_visits: true,
+                ..config
+            };
+            let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+            let result = crawler.crawl("http://example.com/orders").await.unwrap();
+            let mut seen = log.lines();
+            seen.sort();
+            (seen, result)
+        }
+
+        let (seen, result) = requests(CrawlerConfig::default()).await;
+        assert_eq!(
+            seen,
+            [
+                "GET http://example.com/orders",
+                "GET http://example.com/orders?page=2"
+            ]
+        );
+        let skips = result.stats.skipped_by_rule;
+        assert_eq!((skips.unsafe_method, skips.destructive_rel), (1, 2));
+
+        // Probing sends OPTIONS for the POST link; destructive rels are still skipped
+        let (seen, result) =
+            requests(CrawlerConfig::default().probe_unsafe(ProbeMethod::Options)).await;
+        assert!(seen.contains(&"OPTIONS http://example.com/orders/new".to_string()));
+        assert!(
+            !seen
+                .iter()
+                .any(|r| r.contains("cancel") || r.contains("logout"))
+        );
+        let probe = result
+            .visits
+            .iter()
+            .find(|v| v.url == "http://example.com/orders/new")
+            .unwrap();
+        assert_eq!(probe.method.as_deref(), Some("OPTIONS"));
+
+        // Read-only mode refuses configurations that would need an unsafe request
+        let login = CrawlerConfig::default()
+            .read_only()
+            .login(LoginStep::new("http://example.com/login"));
+        let error = ApiCrawler::new(login).err().unwrap();
+        assert!(error.to_string().contains("safety.read_only"));
+    }
+
+    #[tokio::test]
+    async fn test_graphql_detection_and_introspection() {
+        use crate::graphql::Detection;
+        use crate::transport::stub::StubTransport;
+
+        fn graphql_server() -> StubTransport {
+            let json = [("content-type", "application/json")];
+            let schema = json!({"data": {"__schema": {
+                "queryType": {"name": "Query"},
+                "mutationType": null,
+                "subscriptionType": null,
+                "types": [{"kind": "OBJECT", "name": "Query", "fields": [
+                    {"name": "me", "args": [], "type": {"kind": "OBJECT", "name": "User"}}
+                ]}]
+            }}});
+            let disabled = json!({"errors": [{"message": "Introspection is disabled"}]});
+            let introspection = |url| graphql::introspection_url(url).unwrap();
+            StubTransport::new()
+                .json(
+                    "http://example.com/",
+                    json!({"_links": {
+                        "graphql": {"href": "http://example.com/graphql"},
+                        "search": {"href": "http://example.com/search"}
+                    }}),
+                )
+                .respond(
+                    "GET http://example.com/graphql",
+                    400,
+                    &json,
+                    json!({"errors": [{"message": "GET query missing."}]}).to_string(),
+                )
+                .json("POST http://example.com/graphql", schema.clone())
+                .json(&introspection("http://example.com/graphql"), schema)
+                .respond(
+                    "GET http://example.com/search",
+                    405,
+                    &json,
+                    json!({"errors": [{"message": "Must provide a query string."}]}).to_string(),
+                )
+                .json("POST http://example.com/search", disabled.clone())
+                .json(&introspection("http://example.com/search"), disabled)
+        }
+
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            ..CrawlerConfig::default()
+        };
+        let transport = graphql_server();
+        let seen = transport.log();
+        let mut crawler = ApiCrawler::with_transport(config.clone(), Box::new(transport)).unwrap();
+        let result = crawler.crawl("http://example.com/").await.unwrap();
+
+        // Detection alone never sends a query
+        let detected: Vec<(&str, Detection)> = result
+            .graphql
+            .iter()
+            .map(|g| (g.url.as_str(), g.detected_by))
+            .collect();
+        assert_eq!(
+            detected,
+            [
+                ("http://example.com/graphql", Detection::ErrorResponse),
+                ("http://example.com/search", Detection::ErrorResponse),
+            ]
+        );
+        assert!(result.graphql.iter().all(|g| g.schema.is_none()));
+        assert!(seen.lines().iter().all(|r| r.starts_with("GET")));
+
+        let transport = graphql_server();
+        let seen = transport.log();
+        let mut crawler =
+            ApiCrawler::with_transport(config.clone().introspect_graphql(), Box::new(transport))
+                .unwrap();
+        let result = crawler.crawl("http://example.com/").await.unwrap();
+        let schema = result.graphql[0].schema.as_ref().unwrap();
+        assert_eq!(schema.queries[0].signature(), "me: User");
+        assert_eq!(
+            result.graphql[1].error.as_deref(),
+            Some("Invalid response format: Introspection failed: Introspection is disabled")
+        );
+        assert!(
+            seen.lines()
+                .contains(&"POST http://example.com/graphql".to_string())
+        );
+
+        // Read-only mode introspects with GET
+        let transport = graphql_server();
+        let seen = transport.log();
+        let mut crawler = ApiCrawler::with_transport(
+            config.read_only().introspect_graphql(),
+            Box::new(transport),
+        )
+        .unwrap();
+        let result = crawler.crawl("http://example.com/").await.unwrap();
+        assert!(result.graphql[0].schema.is_some());
+        assert!(seen.lines().iter().all(|r| r.starts_with("GET")));
+    }
+
+    #[tokio::test]
+    async fn test_pagination_follows_pages_at_collection_depth() {
+        use crate::transport::stub::StubTransport;
+
+        let mut transport = StubTransport::new()
+            .json(
+                "http://example.com/",
+                json!({"_links": {
+                    "orders": {"href": "http://example.com/orders"},
+                    "events": {"href": "http://example.com/events"}
+                }}),
+            )
+            .json(
+                "http://example.com/events",
+                json!({"data": [], "meta": {"next_cursor": "c2"}}),
+            )
+            .json(
+                "http://example.com/events?cursor=c2",
+                json!({"data": [], "meta": {"next_cursor": "c3", "has_more": false}}),
+            );
+        for (url, page) in [
+            ("http://example.com/orders", 1),
+            ("http://example.com/orders?page=2", 2),
+            ("http://example.com/orders?page=5", 5),
+        ] {
+            let link = format!(
+                "</orders?page={}>; rel=\"next\", </orders?page=5>; rel=\"last\"",
+                page + 1
+            );
+            transport = transport.respond(
+                url,
+                200,
+                &[("content-type", "application/json"), ("link", &link)],
+                json!({"items": [{"href": format!("http://example.com/orders/{}", page)}]})
+                    .to_string(),
+            );
+        }
+
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            record_visits: true,
+            max_depth: 3,
+            ..CrawlerConfig::default()
+        }
+        .max_pages(3);
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+        let result = crawler.crawl("http://example.com/").await.unwrap();
+
+        let visits: Vec<(&str, usize)> = result
+            .visits
+            .iter()
+            .map(|v| (v.url.as_str(), v.depth))
+            .collect();
+        assert_eq!(
+            visits,
+            [
+                ("http://example.com/", 0),
+                ("http://example.com/events", 1),
+                ("http://example.com/orders", 1),
+                ("http://example.com/events?cursor=c2", 1),
+                ("http://example.com/orders/1", 2),
+                ("http://example.com/orders?page=2", 1),
+                ("http://example.com/orders?page=5", 1),
+                ("http://example.com/orders/2", 2),
+                ("http://example.com/orders/5", 2),
+            ]
+        );
+
+        assert_eq!(result.collections.len(), 2);
+        let orders = &result.collections[1];
+        assert_eq!(orders.url, "http://example.com/orders");
+        assert_eq!(
+            orders.pages,
+            [
+                "http://example.com/orders?page=2",
+                "http://example.com/orders?page=5"
+            ]
+        );
+        assert!(orders.truncated);
+        assert_eq!(result.stats.skipped_by_rule.page_limit, 2);
+
+        let events = &result.collections[0];
+        assert_eq!(events.pages, ["http://example.com/events?cursor=c2"]);
+        assert!(!events.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_sampling_follows_only_sample_of_instances() {
+        use crate::sampling::SampleStrategy;
+        use crate::transport::stub::StubTransport;
+
+        let transport = StubTransport::new().json(
+            "http://example.com/users",
+            json!({
+                "count": 50000,
+                "results": (1..=20)
+                    .map(|i| json!({"url": format!("http://example.com/users/{}", i)}))
+                    .collect::<Vec<_>>(),
+                "_links": {"me": {"href": "http://example.com/users/me"}}
+            }),
+        );
+        let seen = transport.log();
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            ..CrawlerConfig::default()
+        }
+        .sample(3, SampleStrategy::First);
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+        let result = crawler.crawl("http://example.com/users").await.unwrap();
+
+        let urls: Vec<String> = seen.requests().into_iter().map(|r| r.url).collect();
+        assert_eq!(
+            urls,
+            [
+                "http://example.com/users",
+                "http://example.com/users/me",
+                "http://example.com/users/1",
+                "http://example.com/users/2",
+                "http://example.com/users/3",
+            ]
+        );
+        assert!(
+            !result
+                .endpoints
+                .iter()
+                .any(|e| e.href == "http://example.com/users/4")
+        );
+        assert_eq!(result.stats.skipped_by_rule.sampled, 17);
+
+        let pattern = &result.sampled_patterns[0];
+        assert_eq!(pattern.pattern, "http://example.com/users/{id}");
+        assert_eq!(pattern.estimated_count, 50000);
+        assert_eq!((pattern.sampled.len(), pattern.skipped), (3, 17));
+    }
+
+    #[tokio::test]
+    async fn test_schema_inference_per_route() {
+        use crate::transport::stub::StubTransport;
+
+        fn orders_server() -> StubTransport {
+            StubTransport::new()
+                .json(
+                    "http://example.com/orders",
+                    json!({
+                        "_links": {
+                            "first": {"href": "http://example.com/orders/1"},
+                            "second": {"href": "http://example.com/orders/2"}
+                        }
+                    }),
+                )
+                .json(
+                    "http://example.com/orders/1",
+                    json!({"id": 1, "status": "open", "note": "gift"}),
+                )
+                .json(
+                    "http://example.com/orders/2",
+                    json!({"id": 2, "status": "open"}),
+                )
+        }
+
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            ..CrawlerConfig::default()
+        }
+        .infer_schemas();
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(orders_server())).unwrap();
+        let result = crawler.crawl("http://example.com/orders").await.unwrap();
+
+        let routes: Vec<(&str, usize)> = result
+            .schemas
+            .iter()
+            .map(|s| (s.route.as_str(), s.samples))
+            .collect();
+        assert_eq!(
+            routes,
+            [
+                ("http://example.com/orders", 1),
+                ("http://example.com/orders/{id}", 2)
+            ]
+        );
+        let order = &result.schemas[1].schema;
+        assert_eq!(order["required"], json!(["id", "status"]));
+        assert_eq!(order["properties"]["status"]["enum"], json!(["open"]));
+        assert_eq!(order["properties"]["note"]["type"], "string");
+
+        // Without inference, no shapes are kept
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            ..CrawlerConfig::default()
+        };
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(orders_server())).unwrap();
+        let result = crawler.crawl("http://example.com/orders").await.unwrap();
+        assert!(result.schemas.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sensitive_data_scan_reports_findings_per_url() {
+        use crate::transport::stub::StubTransport;
+
+        let users_server = || {
+            StubTransport::new()
+                .json(
+                    "http://example.com/users",
+                    json!({
+                        "_links": {"jane": {"href": "http://example.com/users/1"}},
+                        "total": 1234567890
+                    }),
+                )
+                .json(
+                    "http://example.com/users/1",
+                    json!({"id": 1, "email": "jane@example.com", "phone": "+1 555-010-9999"}),
+                )
+        };
+
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            ..CrawlerConfig::default()
+        }
+        .scan_sensitive_data();
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(users_server())).unwrap();
+        let result = crawler.crawl("http://example.com/users").await.unwrap();
+        let found: Vec<(&str, &str, &str)> = result
+            .findings
+            .iter()
+            .map(|f| (f.url.as_str(), f.path.as_str(), f.rule.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("http://example.com/users/1", "$.email", "email"),
+                ("http://example.com/users/1", "$.phone", "phone"),
+            ]
+        );
+
+        // Nothing is scanned unless enabled
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            ..CrawlerConfig::default()
+        };
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(users_server())).unwrap();
+        let result = crawler.crawl("http://example.com/users").await.unwrap();
+        assert!(result.findings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_relative_hrefs_are_resolved_and_crawled() {
+        use crate::transport::stub::StubTransport;
+
+        let transport = StubTransport::new()
+            .json(
+                "http://example.com/api/",
+                json!({"_links": {
+                    "users": {"href": "/api/users"},
+                    "orders": {"href": "orders"},
+                    "broken": {"href": "http://[broken"}
+                }}),
+            )
+            .json("http://example.com/api/users", json!({}))
+            .json("http://example.com/api/orders", json!({}));
+        let seen = transport.log();
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            ..CrawlerConfig::default()
+        };
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+        let result = crawler.crawl("http://example.com/api/").await.unwrap();
+
+        let mut fetched = seen.lines();
+        fetched.sort();
+        assert_eq!(
+            fetched,
+            [
+                "GET http://example.com/api/",
+                "GET http://example.com/api/orders",
+                "GET http://example.com/api/users"
+            ]
+        );
+        // A URL that can't be parsed is skipped as out of scope
+        assert_eq!(result.stats.skipped_by_rule.domain, 1);
+        assert_eq!(result.stats.failed_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_method_probe_records_supported_methods() {
+        use crate::transport::stub::StubTransport;
+
+        let transport = StubTransport::new()
+            .json(
+                "GET http://example.com/",
+                json!({"_links": {
+                    "orders": {"href": "http://example.com/orders"},
+                    "status": {"href": "http://example.com/status"},
+                    "docs": {"href": "http://other.example.org/docs"}
+                }}),
+            )
+            .respond(
+                "OPTIONS http://example.com/orders",
+                204,
+                &[
+                    ("allow", "GET, HEAD, POST"),
+                    ("accept-post", "application/json"),
+                    ("access-control-allow-methods", "GET, POST"),
+                ],
+                "",
+            )
+            .respond(
+                "OPTIONS http://example.com/status",
+                405,
+                &[("allow", "GET")],
+                "",
+            );
+        let seen = transport.log();
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            ..CrawlerConfig::default()
+        }
+        .cors_origin("https://app.example.com");
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+        let result = crawler.crawl("http://example.com/").await.unwrap();
+
+        let orders = result
+            .endpoints
+            .iter()
+            .find(|e| e.href == "http://example.com/orders")
+            .and_then(|e| e.supported_methods.as_ref())
+            .unwrap();
+        assert_eq!(orders.methods, ["GET", "HEAD", "POST"]);
+        assert_eq!(orders.accept_post, ["application/json"]);
+        assert_eq!(orders.cors_methods, ["GET", "POST"]);
+
+        // A 405 still carries the Allow header
+        let status = &result.url_mappings["http://example.com/"]
+            .iter()
+            .find(|e| e.href == "http://example.com/status")
+            .unwrap();
+        assert_eq!(status.supported_methods.as_ref().unwrap().methods, ["GET"]);
+        assert_eq!(result.stats.methods_probed, 2);
+        assert_eq!(result.stats.failed_requests, 0);
+
+        // Out-of-scope endpoints are never probed; probes are CORS preflights
+        let probes: Vec<String> = seen
+            .requests()
+            .iter()
+            .filter(|r| r.method == Method::OPTIONS)
+            .map(|r| format!("{} {:?}", r.url, r.headers["origin"]))
+            .collect();
+        assert_eq!(
+            probes,
+            [
+                "http://example.com/orders \"https://app.example.com\"",
+                "http://example.com/status \"https://app.example.com\"",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_method_probes_follow_crawl_rules() {
+        use crate::filter::UrlFilters;
+        use crate::sampling::SampleStrategy;
+        use crate::transport::stub::StubTransport;
+
+        let probed = |max_urls: usize| async move {
+            let mut unsafe_user = json!({"href": "http://example.com/users/9"});
+            unsafe_user["method"] = json!("POST");
+            let transport = StubTransport::new().json(
+                "GET http://example.com/",
+                json!({"_links": {
+                    "users": [
+                        {"href": "http://example.com/users/1"},
+                        {"href": "http://example.com/users/2"},
+                        unsafe_user
+                    ],
+                    "status": {"href": "http://example.com/status"},
+                    "admin": {"href": "http://example.com/admin"}
+                }}),
+            );
+            let seen = transport.log();
+            let config = CrawlerConfig {
+                delay_ms: 0,
+                max_urls,
+                ..CrawlerConfig::default()
+            }
+            .probe_methods()
+            .sample(1, SampleStrategy::First)
+            .filters(UrlFilters::new().exclude("/admin").unwrap());
+            let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+            crawler.crawl("http://example.com/").await.unwrap();
+            seen.lines()
+                .into_iter()
+                .filter(|line| line.starts_with("OPTIONS"))
+                .collect::<Vec<_>>()
+        };
+
+        // Excluded URLs and instances left out of a full sample aren't probed
+        assert_eq!(
+            probed(0).await,
+            [
+                "OPTIONS http://example.com/status",
+                "OPTIONS http://example.com/users/1"
+            ]
+        );
+        assert_eq!(probed(1).await, ["OPTIONS http://example.com/status"]);
+    }
+
+    #[tokio::test]
+    async fn test_json_variants_and_representation_probes() {
+        use crate::media::{MediaType, PROBE_MEDIA_TYPES};
+        use crate::transport::stub::StubTransport;
+
+        let orders_url = "http://example.com/orders";
+        let problem = [("content-type", "application/problem+json")];
+        let transport = StubTransport::new()
+            .respond(
+                "http://example.com/",
+                200,
+                &[("content-type", "Application/HAL+JSON; charset=\"utf-8\"")],
+                format!(
+                    "\u{feff}{}",
+                    json!({"_links": {
+                        "orders": {"href": orders_url},
+                        "logout": {"href": "http://example.com/logout"}
+                    }})
+                ),
+            )
+            .respond(
+                orders_url,
+                200,
+                &[("content-type", "application/hal+json")],
+                json!({"_links": {"audit": {"href": "http://example.com/audit"}}}).to_string(),
+            )
+            .respond(
+                orders_url,
+                200,
+                &[("content-type", "text/xml")],
+                "<orders/>",
+            )
+            .when_header("accept", "application/xml")
+            .respond(orders_url, 406, &problem, "{}")
+            .when_header("accept", "text/csv")
+            .respond(
+                orders_url,
+                200,
+                &[("content-type", "application/json")],
+                "{}",
+            )
+            .when_header("accept", "application/vnd.api+json")
+            .respond("http://example.com/audit", 200, &problem, "{}");
+        let seen = transport.log();
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            ..CrawlerConfig::default()
+        }
+        .probe_representations(
+            PROBE_MEDIA_TYPES
+                .iter()
+                .map(|t| MediaType::parse(t).unwrap()),
+        );
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+        let result = crawler.crawl("http://example.com/").await.unwrap();
+
+        // Quirky HAL content types are still parsed as JSON and followed
+        let orders = result
+            .endpoints
+            .iter()
+            .find(|e| e.href == "http://example.com/orders")
+            .unwrap();
+        assert!(
+            result
+                .endpoints
+                .iter()
+                .any(|e| e.href == "http://example.com/audit")
+        );
+        assert_eq!(orders.representations, ["application/hal+json", "text/xml"]);
+
+        // Links that aren't safe to GET are never probed
+        let seen = seen.requests();
+        assert!(!seen.iter().any(|r| r.url.contains("logout")));
+        assert_eq!(
+            seen.iter().filter(|r| r.url == orders_url).count(),
+            1 + PROBE_MEDIA_TYPES.len()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oauth2_token_refreshed_after_401() {
+        use crate::auth::{Auth, OAuth2ClientCredentials, SecretSource};
+        use crate::transport::stub::StubTransport;
+
+        // The first token issued is rejected as expired
+        let token = "POST http://auth.example.com/token";
+        let transport = StubTransport::new()
+            .json(token, json!({"access_token": "token-1"}))
+            .json(token, json!({"access_token": "token-2"}))
+            .respond("http://example.com/api", 401, &[], "")
+            .when_header("authorization", "Bearer token-1");
+
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            record_visits: true,
+            ..CrawlerConfig::default()
+        }
+        .auth(Auth::OAuth2(OAuth2ClientCredentials {
+            token_url: "http://auth.example.com/token".to_string(),
+            client_id: "client".to_string(),
+            client_secret: SecretSource::Value("hunter2".to_string()),
+            scopes: Vec::new(),
+        }));
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+
+        let result = crawler.crawl("http://example.com/api").await.unwrap();
+
+        assert_eq!(result.stats.failed_requests, 0);
+        assert_eq!(result.visits[0].status, Some(200));
+        assert!(!result.config_snapshot.contains("hunter2"));
+    }
+
+    #[tokio::test]
+    async fn test_query_api_key_is_kept_out_of_errors() {
+        use crate::auth::{ApiKeyLocation, Auth, SecretSource};
+
+        let mut config = CrawlerConfig {
+            delay_ms: 0,
+            timeout_seconds: 5,
+            record_visits: true,
+            ..CrawlerConfig::default()
+        }
+        .auth(Auth::ApiKey {
+            name: "api_key".to_string(),
+            value: SecretSource::Value("s3cret-key".to_string()),
+            location: ApiKeyLocation::Query,
+        });
+        config.proxy.use_env = false;
+        let mut crawler = ApiCrawler::new(config).unwrap();
+
+        // Nothing listens on port 1, so the request fails with a connection error
+        let result = crawler.crawl("http://127.0.0.1:1/api").await.unwrap();
+
+        assert_eq!(result.stats.failed_requests, 1);
+        assert!(result.stats.errors[0].starts_with("URL http://127.0.0.1:1/api: "));
+        assert!(!result.stats.errors[0].contains("s3cret-key"));
+        assert!(
+            !result.visits[0]
+                .error
+                .as_ref()
+                .unwrap()
+                .contains("s3cret-key")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_credentials_scoped_by_host() {
+        use crate::auth::{Auth, CredentialScope, SecretSource};
+        use crate::transport::stub::StubTransport;
+
+        let transport = StubTransport::new().json(
+            "http://api.internal.example.com/",
+            json!({"_links": {
+                "internal": {"href": "http://data.internal.example.com/items"},
+                "partner": {"href": "http://partner.example.org/feed"},
+                "docs": {"href": "http://docs.example.com/guide"},
+                "other": {"href": "http://tracker.example.net/pixel"}
+            }}),
+        );
+        let seen = transport.log();
+        let config = CrawlerConfig {
+            delay_ms: 0,
+            ..CrawlerConfig::default()
+        }
+        .scope_credentials(
+            CredentialScope::new("*.internal.example.com")
+                .unwrap()
+                .header("X-Tenant".to_string(), "acme".to_string())
+                .auth(Auth::Bearer(SecretSource::Value("token-a".to_string()))),
+        )
+        .scope_credentials(
+            CredentialScope::new("partner.example.org")
+                .unwrap()
+                .auth(Auth::Bearer(SecretSource::Value("token-b".to_string()))),
+        )
+        .auth(Auth::Bearer(SecretSource::Value(
+            "token-global".to_string(),
+        )))
+        .add_header("X-Api-Key".to_string(), "global-key".to_string())
+        .any_domain();
+        let mut crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+
+        crawler
+            .crawl("http://api.internal.example.com/")
+            .await
+            .unwrap();
+
+        let seen = seen.requests();
+        let credentials = |host: &str| {
+            let request = seen
+                .iter()
+                .find(|r| Url::parse(&r.url).unwrap().host_str() == Some(host))
+                .unwrap();
+            let header = |name: &str| {
+                request
+                    .headers
+                    .get(name)
+                    .map(|v| v.to_str().unwrap().to_string())
+            };
+            (
+                header("authorization"),
+                header("x-tenant"),
+                header("x-api-key"),
+            )
+        };
+        let bearer = |token: &str| Some(format!("Bearer {}", token));
+
+        let acme = Some("acme".to_string());
+        assert_eq!(
+            credentials("api.internal.example.com"),
+            (bearer("token-a"), acme.clone(), None)
+        );
+        assert_eq!(
+            credentials("data.internal.example.com"),
+            (bearer("token-a"), acme, None)
+        );
+        assert_eq!(
+            credentials("partner.example.org"),
+            (bearer("token-b"), None, None)
+        );
+        // Unscoped credentials only go to the start URL's site
+        assert_eq!(
+            credentials("docs.example.com"),
+            (bearer("token-global"), None, Some("global-key".to_string()))
+        );
+        assert_eq!(credentials("tracker.example.net"), (None, None, None));
+    }
+
+    #[tokio::test]
+    async fn test_login_runs_before_crawl() {
+        use crate::auth::SecretSource;
+        use crate::session::LoginStep;
+        use crate::transport::stub::StubTransport;
+
+        let crawl_with_login_status = |status: u16| {
+            let config = CrawlerConfig {
+                delay_ms: 0,
+                ..CrawlerConfig::default()
+            }
+            .login(
+                LoginStep::new("http://legacy.example.com/login")
+                    .form_field("password", SecretSource::Value("open sesame".to_string())),
+            );
+            let transport = StubTransport::new().respond(
+                "POST http://legacy.example.com/login",
+                status,
+                &[],
+                "",
+            );
+            let requests = transport.log();
+            let crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+            (crawler, requests)
+        };
+
+        let (mut crawler, requests) = crawl_with_login_status(200);
+        assert!(crawler.cookie_jar().is_some());
+        crawler
+            .crawl("http://legacy.example.com/api")
+            .await
+            .unwrap();
+        assert_eq!(
+            requests.lines(),
+            [
+                "POST http://legacy.example.com/login",
+                "GET http://legacy.example.com/api"
+            ]
+        );
+        assert_eq!(
+            requests.requests()[0].body.as_deref(),
+            Some(&b"password=open+sesame"[..])
+        );
+
+        let (mut crawler, requests) = crawl_with_login_status(403);
+        let error = crawler
+            .crawl("http://legacy.example.com/api")
+            .await
+            .unwrap_err();
+        assert_eq!(error.status(), Some(403));
+        assert_eq!(requests.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_login_renews_missing_or_stale_sessions() {
+        use crate::session::{CookieJar, LoginStep};
+        use crate::transport::stub::StubTransport;
+
+        let dir = tempfile::tempdir().unwrap();
+        let site = Url::parse("http://legacy.example.com/").unwrap();
+        let crawl_with_saved_cookie = |cookie: &str| {
+            let cookie_file = dir.path().join(format!("{}.json", cookie));
+            let saved = CookieJar::new();
+            saved.add_cookie_str(cookie, &site);
+            saved.save(&cookie_file).unwrap();
+
+            let config = CrawlerConfig {
+                delay_ms: 0,
+                ..CrawlerConfig::default()
+            }
+            .cookie_file(cookie_file)
+            .login(LoginStep::new("http://legacy.example.com/login").session_cookie("sid"))
+            .record_har(0);
+            let transport = StubTransport::new()
+                .respond("GET http://legacy.example.com/api", 401, &[], "")
+                .json("GET http://legacy.example.com/api", json!({}));
+            let requests = transport.log();
+            let crawler = ApiCrawler::with_transport(config, Box::new(transport)).unwrap();
+            (crawler, requests)
+        };
+
+        // A saved cookie other than the session cookie doesn't skip the login
+        let (mut crawler, requests) = crawl_with_saved_cookie("theme=dark");
+        crawler
+            .crawl("http://legacy.example.com/api")
+            .await
+            .unwrap();
+        assert_eq!(requests.lines()[0], "POST http://legacy.example.com/login");
+
+        // A stale session cookie skips it, and the 401 it earns triggers a new login
+        let (mut crawler, requests) = crawl_with_saved_cookie("sid=expired-on-server");
+        let result = crawler
+            .crawl("http://legacy.example.com/api")
+            .await
+            .unwrap();
+        assert_eq!(
+            requests.lines(),
+            [
+                "GET http://legacy.example.com/api",
+                "POST http://legacy.example.com/login",
+                "GET http://legacy.example.com/api"
+            ]
+        );
+        assert_eq!(result.stats.failed_requests, 0);
+
+        // The login exchange is recorded with the crawl traffic
+        let har: Vec<(String, u16)> = crawler
+            .har()
+            .log
+            .entries
+            .iter()
+            .map(|e| (e.request.method.clone(), e.response.status))
+            .collect();
+        assert_eq!(har, [("POST".to_string(), 200), ("GET".to_string(), 200)]);
+    }
 }
@@ -11,12 +11,21 @@ pub enum CrawlerError {
     #[error("JSON parsing failed: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("XML parsing failed: {0}")]
+    Xml(#[from] roxmltree::Error),
+
     #[error("URL parsing failed: {0}")]
     Url(#[from] url::ParseError),
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("HTTP status {status} returned by {url}")]
+    HttpStatus { status: u16, url: String },
+
+    #[error("No recorded response for {method} {url}")]
+    NotRecorded { method: String, url: String },
+
     #[error("Invalid response format: {message}")]
     InvalidResponse { message: String },
 
@@ -29,6 +38,9 @@ pub enum CrawlerError {
     #[error("Timeout occurred")]
     Timeout,
 
+    #[error("Refusing {method} {url}: the crawler is in read-only mode")]
+    UnsafeRequest { method: String, url: String },
+
     #[error("Configuration error: {message}")]
     Config { message: String },
 }
@@ -44,10 +56,27 @@ impl CrawlerError {
         }
     }
 
+    /// HTTP status code associated with this error, if any
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Http(e) => e.status().map(|s| s.as_u16()),
+            Self::HttpStatus { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
     /// Create a new Config error
     pub fn config(message: impl Into<String>) -> Self {
         Self::Config {
             message: message.into(),
         }
     }
+
+    /// Convert into a deserialization error, without the "Configuration error" prefix
+    pub(crate) fn into_de_error<E: serde::de::Error>(self) -> E {
+        match self {
+            Self::Config { message } => E::custom(message),
+            other => E::custom(other),
+        }
+    }
 }
@@ -0,0 +1,531 @@
+//! Exporters that turn crawl results into request collections for API clients
+//!
+//! Configured headers are never written verbatim: each one is replaced by a
+//! `{{variable}}` reference so that secrets such as bearer tokens stay in the
+//! user's client environment instead of the exported file. Postman collections
+//! carry the configured [`Auth`] and per-host credentials the same way.
+
+use crate::auth::{ApiKeyLocation, Auth, CredentialScope};
+use crate::error::{CrawlerError, Result};
+use crate::host::HostPattern;
+use crate::types::{ApiEndpoint, CrawlResult, CrawlerConfig};
+use indexmap::IndexMap;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use tracing::info;
+use url::Url;
+
+/// Postman collection schema URL for format v2.1
+const POSTMAN_SCHEMA_V21: &str =
+    "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+/// Derive a client variable name from a header name (e.g. `X-Api-Key` -> `x_api_key`)
+pub fn header_variable_name(header: &str) -> String {
+    header
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Resolve an endpoint href against its parent URL, falling back to the raw href
+fn resolve_href(endpoint: &ApiEndpoint, start_url: &str) -> String {
+    if Url::parse(&endpoint.href).is_ok() {
+        return endpoint.href.clone();
+    }
+
+    let base = endpoint.parent_url.as_deref().unwrap_or(start_url);
+    Url::parse(base)
+        .and_then(|base| base.join(&endpoint.href))
+        .map(|u| u.to_string())
+        .unwrap_or_else(|_| endpoint.href.clone())
+}
+
+/// HTTP method for an endpoint, defaulting to GET
+fn endpoint_method(endpoint: &ApiEndpoint) -> String {
+    endpoint
+        .method
+        .as_deref()
+        .map(|m| m.to_ascii_uppercase())
+        .unwrap_or_else(|| "GET".to_string())
+}
+
+/// Human-readable request name for an endpoint
+fn endpoint_name(endpoint: &ApiEndpoint, url: &str) -> String {
+    endpoint
+        .title
+        .clone()
+        .or_else(|| endpoint.rel.clone())
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// A single exported request
+struct ExportRequest {
+    name: String,
+    method: String,
+    url: String,
+}
+
+/// Group unique requests by the parent URL they were discovered at, in discovery order
+fn group_requests(result: &CrawlResult) -> IndexMap<String, Vec<ExportRequest>> {
+    let mut groups: IndexMap<String, Vec<ExportRequest>> = IndexMap::new();
+    let mut seen = HashSet::new();
+
+    for endpoint in &result.endpoints {
+        let parent = endpoint
+            .parent_url
+            .clone()
+            .unwrap_or_else(|| result.start_url.clone());
+        let url = resolve_href(endpoint, &result.start_url);
+        let method = endpoint_method(endpoint);
+
+        if !seen.insert((parent.clone(), method.clone(), url.clone())) {
+            continue;
+        }
+
+        groups.entry(parent).or_default().push(ExportRequest {
+            name: endpoint_name(endpoint, &url),
+            method,
+            url,
+        });
+    }
+
+    groups
+}
+
+/// Sorted header names paired with their variable names
+fn header_variables(headers: &HashMap<String, String>) -> Vec<(String, String)> {
+    let mut names: Vec<(String, String)> = headers
+        .keys()
+        .map(|k| (k.clone(), header_variable_name(k)))
+        .collect();
+    names.sort();
+    names
+}
+
+/// A `{{variable}}` reference
+fn variable_ref(name: &str) -> String {
+    format!("{{{{{}}}}}", name)
+}
+
+/// Postman `auth` block for an authentication strategy
+///
+/// Secrets become `{{variable}}` references named after `prefix`, and the names
+/// are added to `variables`.
+fn postman_auth(auth: &Auth, prefix: &str, variables: &mut Vec<String>) -> Value {
+    let mut secret = |name: &str| {
+        let var = format!("{}{}", prefix, name);
+        let reference = variable_ref(&var);
+        variables.push(var);
+        reference
+    };
+    let pairs = |pairs: Vec<(&str, String)>| -> Vec<Value> {
+        pairs
+            .into_iter()
+            .map(|(key, value)| json!({ "key": key, "value": value, "type": "string" }))
+            .collect()
+    };
+
+    match auth {
+        Auth::Basic { username, .. } => json!({
+            "type": "basic",
+            "basic": pairs(vec![
+                ("username", username.clone()),
+                ("password", secret("password")),
+            ])
+        }),
+        Auth::Bearer(_) => json!({
+            "type": "bearer",
+            "bearer": pairs(vec![("token", secret("bearer_token"))])
+        }),
+        Auth::ApiKey { name, location, .. } => json!({
+            "type": "apikey",
+            "apikey": pairs(vec![
+                ("key", name.clone()),
+                ("value", secret("api_key")),
+                (
+                    "in",
+                    match location {
+                        ApiKeyLocation::Header => "header",
+                        ApiKeyLocation::Query => "query",
+                    }
+                    .to_string(),
+                ),
+            ])
+        }),
+        Auth::OAuth2(settings) => json!({
+            "type": "oauth2",
+            "oauth2": pairs(vec![
+                ("grant_type", "client_credentials".to_string()),
+                ("accessTokenUrl", settings.token_url.clone()),
+                ("clientId", settings.client_id.clone()),
+                ("clientSecret", secret("client_secret")),
+                ("scope", settings.scopes.join(" ")),
+                ("client_authentication", "header".to_string()),
+            ])
+        }),
+    }
+}
+
+/// Variable name prefix for a credential scope, e.g. `api_example_com_`
+fn scope_prefix(scope: &CredentialScope) -> String {
+    format!("{}_", header_variable_name(&scope.hosts.to_string()))
+}
+
+/// Build a Postman v2.1 collection for the crawl results
+///
+/// Requests are grouped into one folder per parent URL. Each configured header
+/// is sent as `{{variable}}` and declared as an empty collection variable. The
+/// crawler-wide `auth` becomes the collection's auth; a request to a host with
+/// a credential scope gets that scope's headers and auth instead, and requests
+/// off the start URL's site matching no scope are sent without auth.
+pub fn postman_collection(result: &CrawlResult, config: &CrawlerConfig) -> Value {
+    let variables = header_variables(&config.headers);
+    let mut secrets: Vec<String> = variables.iter().map(|(_, var)| var.clone()).collect();
+
+    let request_headers: Vec<Value> = variables
+        .iter()
+        .map(|(header, var)| {
+            json!({
+                "key": header,
+                "value": variable_ref(var),
+                "type": "text"
+            })
+        })
+        .collect();
+
+    // Scoped headers and auth, in scope order
+    let scopes: Vec<(&CredentialScope, Vec<Value>, Option<Value>)> = config
+        .credential_scopes
+        .iter()
+        .map(|scope| {
+            let prefix = scope_prefix(scope);
+            let headers = header_variables(&scope.headers)
+                .into_iter()
+                .map(|(header, var)| {
+                    let var = format!("{}{}", prefix, var);
+                    let header = json!({
+                        "key": header,
+                        "value": variable_ref(&var),
+                        "type": "text"
+                    });
+                    secrets.push(var);
+                    header
+                })
+                .collect();
+            let auth = scope
+                .auth
+                .as_ref()
+                .map(|auth| postman_auth(auth, &prefix, &mut secrets));
+            (scope, headers, auth)
+        })
+        .collect();
+    let collection_auth = config
+        .auth
+        .as_ref()
+        .map(|auth| postman_auth(auth, "", &mut secrets));
+    let site = Url::parse(&result.start_url)
+        .ok()
+        .and_then(|url| HostPattern::site_of(&url));
+
+    let folders: Vec<Value> = group_requests(result)
+        .into_iter()
+        .map(|(parent, requests)| {
+            let items: Vec<Value> = requests
+                .into_iter()
+                .map(|req| {
+                    let url = Url::parse(&req.url).ok();
+                    let scope = url.as_ref().and_then(|url| {
+                        scopes
+                            .iter()
+                            .find(|(scope, _, _)| scope.hosts.matches_url(url))
+                    });
+                    let mut headers = request_headers.clone();
+                    let mut request = json!({ "method": req.method, "url": req.url });
+                    match scope {
+                        Some((_, scope_headers, auth)) => {
+                            headers.extend(scope_headers.iter().cloned());
+                            if let Some(auth) = auth {
+                                request["auth"] = auth.clone();
+                            }
+                        }
+                        None if collection_auth.is_some()
+                            && !url.as_ref().is_some_and(|url| {
+                                site.as_ref().is_some_and(|s| s.matches_url(url))
+                            }) =>
+                        {
+                            request["auth"] = json!({ "type": "noauth" });
+                        }
+                        None => {}
+                    }
+                    request["header"] = Value::Array(headers);
+                    json!({ "name": req.name, "request": request })
+                })
+                .collect();
+            json!({ "name": parent, "item": items })
+        })
+        .collect();
+
+    let collection_variables: Vec<Value> = secrets
+        .iter()
+        .map(|var| json!({ "key": var, "value": "", "type": "string" }))
+        .collect();
+
+    let mut collection = json!({
+        "info": {
+            "name": format!("API crawl of {}", result.start_url),
+            "description": format!(
+                "Generated by api_crawler on {}",
+                result.completed_at.to_rfc3339()
+            ),
+            "schema": POSTMAN_SCHEMA_V21
+        },
+        "item": folders,
+        "variable": collection_variables
+    });
+    if let Some(auth) = collection_auth {
+        collection["auth"] = auth;
+    }
+    collection
+}
+
+/// Serialize the crawl results as a pretty-printed Postman v2.1 collection
+pub fn serialize_postman_collection(
+    result: &CrawlResult,
+    config: &CrawlerConfig,
+) -> Result<String> {
+    serde_json::to_string_pretty(&postman_collection(result, config)).map_err(CrawlerError::from)
+}
+
+/// Serialize the crawl results as an `.http` request file for editor REST clients
+///
+/// The output uses the `###` request separator understood by both the VS Code REST
+/// Client and JetBrains HTTP Client. Header values are `{{variable}}` references
+/// that must be defined in the client's environment file.
+pub fn serialize_http_requests(result: &CrawlResult, headers: &HashMap<String, String>) -> String {
+    let variables = header_variables(headers);
+    let mut out = String::new();
+
+    out.push_str(&format!("# API crawl of {}\n", result.start_url));
+    out.push_str(&format!(
+        "# Generated by api_crawler on {}\n",
+        result.completed_at.to_rfc3339()
+    ));
+    if !variables.is_empty() {
+        out.push_str("#\n# Define these variables in your REST client environment:\n");
+        for (header, var) in &variables {
+            out.push_str(&format!("#   {} ({})\n", var, header));
+        }
+    }
+
+    for (parent, requests) in group_requests(result) {
+        out.push_str(&format!("\n# ── {} ──\n", parent));
+        for req in requests {
+            out.push_str(&format!("\n### {}\n", req.name.replace('\n', " ")));
+            out.push_str(&format!("{} {}\n", req.method, req.url));
+            for (header, var) in &variables {
+                out.push_str(&format!("{}: {{{{{}}}}}\n", header, var));
+            }
+        }
+    }
+
+    out
+}
+
+/// Create the parent directory of an export path if it doesn't exist
+fn ensure_parent_dir(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+        && !parent.exists()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(())
+}
+
+/// Write the crawl results to a file as a Postman v2.1 collection
+pub fn save_postman_collection<P: AsRef<Path>>(
+    result: &CrawlResult,
+    config: &CrawlerConfig,
+    file_path: P,
+) -> Result<()> {
+    let path = file_path.as_ref();
+    ensure_parent_dir(path)?;
+    fs::write(path, serialize_postman_collection(result, config)?)?;
+    info!("Postman collection saved to: {}", path.display());
+    Ok(())
+}
+
+/// Write the crawl results to a file as `.http` requests
+pub fn save_http_requests<P: AsRef<Path>>(
+    result: &CrawlResult,
+    headers: &HashMap<String, String>,
+    file_path: P,
+) -> Result<()> {
+    let path = file_path.as_ref();
+    ensure_parent_dir(path)?;
+    fs::write(path, serialize_http_requests(result, headers))?;
+    info!("HTTP request file saved to: {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::SecretSource;
+
+    fn sample_result() -> CrawlResult {
+        let mut result =
+            CrawlResult::new("http://example.com".to_string(), &CrawlerConfig::default());
+
+        result.add_endpoint(
+            ApiEndpoint::new("http://example.com/users".to_string(), 1)
+                .with_rel(Some("users".to_string()))
+                .with_parent(Some("http://example.com".to_string())),
+        );
+        // Same request discovered twice under the same parent
+        result.add_endpoint(
+            ApiEndpoint::new("http://example.com/users".to_string(), 1)
+                .with_rel(Some("collection".to_string()))
+                .with_parent(Some("http://example.com".to_string())),
+        );
+
+        let mut delete = ApiEndpoint::new("/users/1".to_string(), 2)
+            .with_rel(Some("delete".to_string()))
+            .with_parent(Some("http://example.com/users".to_string()));
+        delete.method = Some("delete".to_string());
+        result.add_endpoint(delete);
+
+        result
+    }
+
+    fn secret_headers() -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        headers.insert("Authorization".to_string(), "Bearer s3cr3t".to_string());
+        headers.insert("X-Api-Key".to_string(), "k3y".to_string());
+        headers
+    }
+
+    fn secret_config() -> CrawlerConfig {
+        CrawlerConfig::default()
+            .add_header("Authorization".to_string(), "Bearer s3cr3t".to_string())
+            .add_header("X-Api-Key".to_string(), "k3y".to_string())
+    }
+
+    #[test]
+    fn test_header_variable_name() {
+        assert_eq!(header_variable_name("Authorization"), "authorization");
+        assert_eq!(header_variable_name("X-Api-Key"), "x_api_key");
+    }
+
+    #[test]
+    fn test_postman_collection() {
+        let collection = postman_collection(&sample_result(), &secret_config());
+
+        assert_eq!(collection["info"]["schema"], POSTMAN_SCHEMA_V21);
+
+        let folders = collection["item"].as_array().unwrap();
+        assert_eq!(folders.len(), 2);
+        assert_eq!(folders[0]["name"], "http://example.com");
+        assert_eq!(folders[0]["item"].as_array().unwrap().len(), 1);
+
+        let delete = &folders[1]["item"][0]["request"];
+        assert_eq!(delete["method"], "DELETE");
+        assert_eq!(delete["url"], "http://example.com/users/1");
+        assert_eq!(delete["header"][0]["key"], "Authorization");
+        assert_eq!(delete["header"][0]["value"], "{{authorization}}");
+
+        let variables = collection["variable"].as_array().unwrap();
+        assert_eq!(variables.len(), 2);
+
+        let serialized = serialize_postman_collection(&sample_result(), &secret_config()).unwrap();
+        assert!(!serialized.contains("s3cr3t"));
+        assert!(!serialized.contains("k3y"));
+    }
+
+    #[test]
+    fn test_postman_collection_auth() {
+        let mut result = sample_result();
+        for url in ["http://partner.io/orders", "http://other.org/feed"] {
+            result.add_endpoint(
+                ApiEndpoint::new(url.to_string(), 1)
+                    .with_parent(Some("http://example.com".to_string())),
+            );
+        }
+        let config = CrawlerConfig::default()
+            .auth(Auth::Bearer(SecretSource::Value("s3cr3t".to_string())))
+            .scope_credentials(
+                CredentialScope::new("partner.io")
+                    .unwrap()
+                    .header("X-Tenant".to_string(), "t3nant".to_string())
+                    .auth(Auth::Basic {
+                        username: "alice".to_string(),
+                        password: SecretSource::Value("pa55".to_string()),
+                    }),
+            );
+
+        let collection = postman_collection(&result, &config);
+        assert_eq!(collection["auth"]["type"], "bearer");
+        assert_eq!(collection["auth"]["bearer"][0]["value"], "{{bearer_token}}");
+
+        let requests: HashMap<&str, &Value> = collection["item"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|folder| folder["item"].as_array().unwrap())
+            .map(|item| (item["request"]["url"].as_str().unwrap(), &item["request"]))
+            .collect();
+
+        // Start site requests inherit the collection's auth
+        assert!(requests["http://example.com/users"].get("auth").is_none());
+
+        let partner = requests["http://partner.io/orders"];
+        assert_eq!(partner["auth"]["type"], "basic");
+        assert_eq!(partner["auth"]["basic"][0]["value"], "alice");
+        assert_eq!(
+            partner["auth"]["basic"][1]["value"],
+            "{{partner_io_password}}"
+        );
+        assert_eq!(partner["header"][0]["key"], "X-Tenant");
+        assert_eq!(partner["header"][0]["value"], "{{partner_io_x_tenant}}");
+
+        // Unscoped credentials never leave the start site
+        assert_eq!(requests["http://other.org/feed"]["auth"]["type"], "noauth");
+
+        let variables: Vec<&str> = collection["variable"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|var| var["key"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            variables,
+            ["partner_io_x_tenant", "partner_io_password", "bearer_token"]
+        );
+
+        let serialized = serialize_postman_collection(&result, &config).unwrap();
+        for secret in ["s3cr3t", "t3nant", "pa55"] {
+            assert!(!serialized.contains(secret));
+        }
+    }
+
+    #[test]
+    fn test_http_requests() {
+        let http = serialize_http_requests(&sample_result(), &secret_headers());
+
+        assert!(http.contains("### users\nGET http://example.com/users\n"));
+        assert!(http.contains("DELETE http://example.com/users/1\n"));
+        assert!(http.contains("Authorization: {{authorization}}\n"));
+        assert!(http.contains("X-Api-Key: {{x_api_key}}\n"));
+        assert!(!http.contains("s3cr3t"));
+        assert_eq!(http.matches("GET http://example.com/users\n").count(), 1);
+    }
+}
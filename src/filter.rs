@@ -0,0 +1,415 @@
+//! Include/exclude rules deciding which discovered links are followed
+//!
+//! Rules are checked for every discovered endpoint before it is queued. The
+//! endpoint is still recorded in the results; it just isn't fetched.
+
+use crate::error::{CrawlerError, Result};
+use crate::types::ApiEndpoint;
+use globset::{GlobBuilder, GlobMatcher};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use url::Url;
+
+/// A glob or regex pattern matched against a URL
+///
+/// - `regex:<expr>` searches the full URL with a regular expression
+/// - a glob starting with `/` matches the URL path, e.g. `/api/**` or `/**/logout`
+/// - any other glob matches the full URL, e.g. `https://*.example.com/v2/**`
+///
+/// In globs `*` stays within one path segment while `**` spans segments.
+#[derive(Clone)]
+pub struct UrlPattern {
+    source: String,
+    matcher: Matcher,
+}
+
+#[derive(Clone)]
+enum Matcher {
+    Path(GlobMatcher),
+    Url(GlobMatcher),
+    Regex(Regex),
+}
+
+impl UrlPattern {
+    /// Parse a pattern
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let invalid = |e: &dyn fmt::Display| {
+            CrawlerError::config(format!("Invalid pattern '{}': {}", pattern, e))
+        };
+
+        let matcher = if let Some(expr) = pattern.strip_prefix("regex:") {
+            Matcher::Regex(Regex::new(expr).map_err(|e| invalid(&e))?)
+        } else {
+            let glob = GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| invalid(&e))?
+                .compile_matcher();
+            if pattern.starts_with('/') {
+                Matcher::Path(glob)
+            } else {
+                Matcher::Url(glob)
+            }
+        };
+
+        Ok(Self {
+            source: pattern.to_string(),
+            matcher,
+        })
+    }
+
+    /// Check whether a parsed URL matches
+    pub fn matches_url(&self, url: &Url) -> bool {
+        match &self.matcher {
+            Matcher::Path(glob) => glob.is_match(url.path()),
+            Matcher::Url(glob) => glob.is_match(url.as_str()),
+            Matcher::Regex(regex) => regex.is_match(url.as_str()),
+        }
+    }
+
+    /// Check whether a URL string matches; unparseable URLs never match
+    pub fn matches(&self, url: &str) -> bool {
+        Url::parse(url).is_ok_and(|u| self.matches_url(&u))
+    }
+}
+
+impl<'de> Deserialize<'de> for UrlPattern {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Self::parse(&pattern).map_err(CrawlerError::into_de_error)
+    }
+}
+
+impl fmt::Display for UrlPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl fmt::Debug for UrlPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "UrlPattern({})", self.source)
+    }
+}
+
+impl PartialEq for UrlPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+/// The rule that stopped a link from being followed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterRule {
+    /// Matched no `include` pattern
+    NotIncluded,
+    /// Matched an `exclude` pattern
+    Excluded,
+    /// Its rel is denied, or not in the allowed list
+    Rel,
+    /// Its advertised method is denied, or not in the allowed list
+    Method,
+    /// It advertises an unsafe method such as `POST` or `DELETE`
+    UnsafeMethod,
+    /// It carries a destructive rel such as `delete` or `logout`
+    DestructiveRel,
+    /// It is a page beyond its collection's `max_pages`
+    PageLimit,
+    /// It is an instance of a URL pattern beyond the sample size
+    Sampled,
+}
+
+/// Rules deciding which discovered links are followed
+///
+/// Empty lists impose no restriction. Links without an advertised method count
+/// as `GET`. Rels match case-insensitively, either exactly or as the last
+/// segment of an extension rel (`ea:logout`, `https://example.com/rels/logout`).
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UrlFilters {
+    /// Only follow links matching at least one of these patterns
+    pub include: Vec<UrlPattern>,
+
+    /// Never follow links matching any of these patterns
+    pub exclude: Vec<UrlPattern>,
+
+    /// Only follow links with one of these rels
+    pub allow_rels: Vec<String>,
+
+    /// Never follow links with these rels
+    pub deny_rels: Vec<String>,
+
+    /// Only follow links advertising one of these methods
+    pub allow_methods: Vec<String>,
+
+    /// Never follow links advertising these methods
+    pub deny_methods: Vec<String>,
+}
+
+impl UrlFilters {
+    /// Create filters with no restrictions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an include pattern
+    pub fn include(mut self, pattern: &str) -> Result<Self> {
+        self.include.push(UrlPattern::parse(pattern)?);
+        Ok(self)
+    }
+
+    /// Add an exclude pattern
+    pub fn exclude(mut self, pattern: &str) -> Result<Self> {
+        self.exclude.push(UrlPattern::parse(pattern)?);
+        Ok(self)
+    }
+
+    /// Only follow links with this rel
+    pub fn allow_rel(mut self, rel: impl Into<String>) -> Self {
+        self.allow_rels.push(rel.into());
+        self
+    }
+
+    /// Never follow links with this rel
+    pub fn deny_rel(mut self, rel: impl Into<String>) -> Self {
+        self.deny_rels.push(rel.into());
+        self
+    }
+
+    /// Only follow links advertising this method
+    pub fn allow_method(mut self, method: impl Into<String>) -> Self {
+        self.allow_methods.push(method.into());
+        self
+    }
+
+    /// Never follow links advertising this method
+    pub fn deny_method(mut self, method: impl Into<String>) -> Self {
+        self.deny_methods.push(method.into());
+        self
+    }
+
+    /// Whether any rule is configured
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check an endpoint against the rules, returning the first one that rejects it
+    pub fn check(&self, endpoint: &ApiEndpoint) -> Option<FilterRule> {
+        let method = endpoint.method.as_deref().unwrap_or("GET");
+        let method_listed = |list: &[String]| list.iter().any(|m| m.eq_ignore_ascii_case(method));
+        if method_listed(&self.deny_methods)
+            || (!self.allow_methods.is_empty() && !method_listed(&self.allow_methods))
+        {
+            return Some(FilterRule::Method);
+        }
+
+        let rel = endpoint.rel.as_deref().unwrap_or("");
+        if self.deny_rels.iter().any(|name| rel_matches(rel, name))
+            || (!self.allow_rels.is_empty()
+                && !self.allow_rels.iter().any(|name| rel_matches(rel, name)))
+        {
+            return Some(FilterRule::Rel);
+        }
+
+        let Ok(url) = Url::parse(&endpoint.href) else {
+            return None;
+        };
+        if self.exclude.iter().any(|p| p.matches_url(&url)) {
+            return Some(FilterRule::Excluded);
+        }
+        if !self.include.is_empty() && !self.include.iter().any(|p| p.matches_url(&url)) {
+            return Some(FilterRule::NotIncluded);
+        }
+
+        None
+    }
+}
+
+/// Whether any of the space-separated rels equals `name` or ends in `:name` / `/name`
+fn rel_matches(rels: &str, name: &str) -> bool {
+    rels.split_whitespace().any(|rel| {
+        let rel = rel.to_ascii_lowercase();
+        let name = name.to_ascii_lowercase();
+        rel == name
+            || rel
+                .strip_suffix(name.as_str())
+                .is_some_and(|prefix| prefix.ends_with(':') || prefix.ends_with('/'))
+    })
+}
+
+/// Links not followed, broken down by the rule that skipped them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleSkips {
+    /// Outside `allowed_domains`
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub domain: usize,
+
+    /// Matched no include pattern
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub not_included: usize,
+
+    /// Matched an exclude pattern
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub excluded: usize,
+
+    /// Rel denied or not allowed
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub rel: usize,
+
+    /// Method denied or not allowed
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub method: usize,
+
+    /// Advertised an unsafe method and wasn't probed
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub unsafe_method: usize,
+
+    /// Carried a destructive rel
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub destructive_rel: usize,
+
+    /// Page beyond its collection's page limit
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub page_limit: usize,
+
+    /// Instance of a URL pattern left out of the sample
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub sampled: usize,
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+impl RuleSkips {
+    /// Count a link skipped by a filter rule
+    pub fn record(&mut self, rule: FilterRule) {
+        match rule {
+            FilterRule::NotIncluded => self.not_included += 1,
+            FilterRule::Excluded => self.excluded += 1,
+            FilterRule::Rel => self.rel += 1,
+            FilterRule::Method => self.method += 1,
+            FilterRule::UnsafeMethod => self.unsafe_method += 1,
+            FilterRule::DestructiveRel => self.destructive_rel += 1,
+            FilterRule::PageLimit => self.page_limit += 1,
+            FilterRule::Sampled => self.sampled += 1,
+        }
+    }
+
+    /// Total links skipped by rules
+    pub fn total(&self) -> usize {
+        self.domain
+            + self.not_included
+            + self.excluded
+            + self.rel
+            + self.method
+            + self.unsafe_method
+            + self.destructive_rel
+            + self.page_limit
+            + self.sampled
+    }
+
+    /// Whether nothing was skipped
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(href: &str, rel: Option<&str>, method: Option<&str>) -> ApiEndpoint {
+        let mut endpoint = ApiEndpoint::new(href.to_string(), 1).with_rel(rel.map(String::from));
+        endpoint.method = method.map(String::from);
+        endpoint
+    }
+
+    #[test]
+    fn test_glob_and_regex_patterns() {
+        let path = UrlPattern::parse("/api/*/items").unwrap();
+        assert!(path.matches("https://example.com/api/v1/items?page=2"));
+        assert!(!path.matches("https://example.com/api/v1/x/items"));
+
+        let deep = UrlPattern::parse("/**/logout").unwrap();
+        assert!(deep.matches("https://example.com/a/b/logout"));
+
+        let full = UrlPattern::parse("https://*.example.com/v2/**").unwrap();
+        assert!(full.matches("https://api.example.com/v2/users/1"));
+        assert!(!full.matches("https://api.example.com/v1/users"));
+
+        let regex = UrlPattern::parse(r"regex:/users/\d+$").unwrap();
+        assert!(regex.matches("https://example.com/users/42"));
+        assert!(!regex.matches("https://example.com/users/me"));
+
+        assert!(UrlPattern::parse("regex:(unclosed").is_err());
+        assert!(UrlPattern::parse("/api/[").is_err());
+    }
+
+    #[test]
+    fn test_filter_rules() {
+        let filters = UrlFilters::new()
+            .include("/api/**")
+            .unwrap()
+            .exclude("regex:/admin")
+            .unwrap()
+            .deny_rel("logout")
+            .deny_rel("edit-form")
+            .allow_method("GET");
+
+        assert_eq!(
+            filters.check(&link("https://example.com/api/users", Some("users"), None)),
+            None
+        );
+        assert_eq!(
+            filters.check(&link("https://example.com/other", None, None)),
+            Some(FilterRule::NotIncluded)
+        );
+        assert_eq!(
+            filters.check(&link("https://example.com/api/admin/x", None, None)),
+            Some(FilterRule::Excluded)
+        );
+        assert_eq!(
+            filters.check(&link(
+                "https://example.com/api/session",
+                Some("ea:logout"),
+                None
+            )),
+            Some(FilterRule::Rel)
+        );
+        assert_eq!(
+            filters.check(&link(
+                "https://example.com/api/users/1",
+                Some("https://example.com/rels/Edit-Form"),
+                None
+            )),
+            Some(FilterRule::Rel)
+        );
+        assert_eq!(
+            filters.check(&link(
+                "https://example.com/api/users/1",
+                None,
+                Some("delete")
+            )),
+            Some(FilterRule::Method)
+        );
+        // A rel that merely ends with a denied name is not denied
+        assert_eq!(
+            filters.check(&link("https://example.com/api/x", Some("nologout"), None)),
+            None
+        );
+
+        let mut skips = RuleSkips::default();
+        skips.record(FilterRule::Rel);
+        skips.record(FilterRule::Rel);
+        skips.record(FilterRule::Method);
+        assert_eq!(skips.total(), 3);
+        assert_eq!(
+            serde_json::to_value(skips).unwrap(),
+            serde_json::json!({"rel": 2, "method": 1})
+        );
+    }
+}
@@ -0,0 +1,450 @@
+//! GraphQL endpoint detection and introspection
+//!
+//! Fetched URLs are recognised as GraphQL endpoints by their path (`/graphql`,
+//! `/gql`), a GraphQL content type, or the error a GraphQL server returns when
+//! fetched without a query. With introspection enabled, each detected endpoint
+//! is sent the standard introspection query and its types, queries and
+//! mutations are recorded in [`crate::types::CrawlResult::graphql`].
+//!
+//! Introspection is sent as a POST, or as a GET with the query in the URL when
+//! the crawler is in read-only mode.
+
+use crate::error::{CrawlerError, Result};
+use crate::media::MediaType;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use url::Url;
+
+/// Standard introspection query, limited to what the crawl results report
+pub const INTROSPECTION_QUERY: &str = "query IntrospectionQuery { __schema { \
+    queryType { name } mutationType { name } subscriptionType { name } \
+    types { kind name description \
+    fields(includeDeprecated: true) { name description isDeprecated \
+    args { name type { ...TypeRef } } type { ...TypeRef } } } } } \
+    fragment TypeRef on __Type { kind name ofType { kind name ofType { kind name \
+    ofType { kind name ofType { kind name } } } } }";
+
+/// GraphQL settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GraphqlConfig {
+    /// Send the introspection query to every detected GraphQL endpoint
+    pub introspect: bool,
+}
+
+/// How an endpoint was recognised as GraphQL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Detection {
+    /// A `graphql` or `gql` path segment
+    Path,
+    /// A GraphQL response content type
+    ContentType,
+    /// A GraphQL-shaped `{"errors": [{"message": ...}]}` body
+    ErrorResponse,
+}
+
+impl fmt::Display for Detection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Path => "path",
+            Self::ContentType => "content type",
+            Self::ErrorResponse => "error response",
+        })
+    }
+}
+
+/// A detected GraphQL endpoint and, when introspected, its schema
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GraphqlEndpoint {
+    /// Endpoint URL
+    pub url: String,
+
+    /// How it was recognised
+    pub detected_by: Detection,
+
+    /// Schema returned by introspection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<GraphqlSchema>,
+
+    /// Why introspection failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The parts of an introspected schema reported in crawl results
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GraphqlSchema {
+    /// Named types, excluding the built-in `__` introspection types
+    pub types: Vec<GraphqlType>,
+
+    /// Fields of the query root type
+    pub queries: Vec<GraphqlField>,
+
+    /// Fields of the mutation root type
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mutations: Vec<GraphqlField>,
+
+    /// Fields of the subscription root type
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subscriptions: Vec<GraphqlField>,
+}
+
+/// A named type in the schema
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GraphqlType {
+    /// Type name
+    pub name: String,
+
+    /// `OBJECT`, `INPUT_OBJECT`, `ENUM`, `SCALAR`, ...
+    pub kind: String,
+
+    /// Field names, for object and interface types
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+}
+
+/// A root field: a query, mutation or subscription
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GraphqlField {
+    /// Field name
+    pub name: String,
+
+    /// Return type in SDL notation, e.g. `[User!]!`
+    #[serde(rename = "type")]
+    pub r#type: String,
+
+    /// Arguments as `name: Type`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+
+    /// Description from the schema
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Whether the field is deprecated
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+}
+
+impl GraphqlField {
+    /// SDL-style signature, e.g. `user(id: ID!): User`
+    pub fn signature(&self) -> String {
+        if self.args.is_empty() {
+            format!("{}: {}", self.name, self.r#type)
+        } else {
+            format!("{}({}): {}", self.name, self.args.join(", "), self.r#type)
+        }
+    }
+}
+
+/// Recognise a GraphQL endpoint from a fetched URL and its response
+pub fn detect(url: &str, media_type: Option<&MediaType>, body: &[u8]) -> Option<Detection> {
+    if media_type.is_some_and(|m| {
+        matches!(
+            m.essence().as_str(),
+            "application/graphql-response+json" | "application/graphql"
+        )
+    }) {
+        return Some(Detection::ContentType);
+    }
+
+    if serde_json::from_slice::<Value>(body).is_ok_and(|json| is_graphql_error(&json)) {
+        return Some(Detection::ErrorResponse);
+    }
+
+    let has_graphql_segment = Url::parse(url).is_ok_and(|url| {
+        url.path_segments().is_some_and(|mut segments| {
+            segments.any(|s| matches!(s.to_ascii_lowercase().as_str(), "graphql" | "gql"))
+        })
+    });
+    has_graphql_segment.then_some(Detection::Path)
+}
+
+/// Whether a body looks like a GraphQL error response rather than a REST error
+///
+/// Only `data`, `errors` and `extensions` may be present, every error needs a
+/// `message`, and something must be GraphQL-specific: a `data` member, error
+/// `locations`, or a message about the query.
+fn is_graphql_error(json: &Value) -> bool {
+    let Some(object) = json.as_object() else {
+        return false;
+    };
+    let Some(errors) = object.get("errors").and_then(Value::as_array) else {
+        return false;
+    };
+    if errors.is_empty()
+        || !object
+            .keys()
+            .all(|key| matches!(key.as_str(), "data" | "errors" | "extensions"))
+    {
+        return false;
+    }
+
+    let messages: Vec<&str> = errors
+        .iter()
+        .filter_map(|error| error.get("message").and_then(Value::as_str))
+        .collect();
+    if messages.len() != errors.len() {
+        return false;
+    }
+
+    object.contains_key("data")
+        || errors.iter().any(|error| error.get("locations").is_some())
+        || messages.iter().any(|message| {
+            let message = message.to_ascii_lowercase();
+            message.contains("query") || message.contains("graphql")
+        })
+}
+
+/// URL for a GET introspection request
+pub fn introspection_url(url: &str) -> Result<String> {
+    let mut url = Url::parse(url)?;
+    url.query_pairs_mut()
+        .append_pair("query", INTROSPECTION_QUERY);
+    Ok(url.to_string())
+}
+
+/// JSON body for a POST introspection request
+pub fn introspection_body() -> Vec<u8> {
+    serde_json::json!({ "query": INTROSPECTION_QUERY })
+        .to_string()
+        .into_bytes()
+}
+
+impl GraphqlSchema {
+    /// Read the schema from an introspection response
+    pub fn from_introspection(response: &Value) -> Result<Self> {
+        let Some(schema) = response.pointer("/data/__schema") else {
+            let message = response
+                .pointer("/errors/0/message")
+                .and_then(Value::as_str)
+                .unwrap_or("response has no data.__schema");
+            return Err(CrawlerError::invalid_response(format!(
+                "Introspection failed: {}",
+                message
+            )));
+        };
+
+        let root_name = |root: &str| {
+            schema
+                .pointer(&format!("/{}/name", root))
+                .and_then(Value::as_str)
+        };
+        let all_types = schema
+            .get("types")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let root_fields = |root: &str| -> Vec<GraphqlField> {
+            let Some(name) = root_name(root) else {
+                return Vec::new();
+            };
+            all_types
+                .iter()
+                .find(|t| t.get("name").and_then(Value::as_str) == Some(name))
+                .and_then(|t| t.get("fields"))
+                .and_then(Value::as_array)
+                .map(|fields| fields.iter().filter_map(parse_field).collect())
+                .unwrap_or_default()
+        };
+
+        let types = all_types
+            .iter()
+            .filter_map(|t| {
+                let name = t.get("name")?.as_str()?;
+                if name.starts_with("__") {
+                    return None;
+                }
+                let fields = t
+                    .get("fields")
+                    .and_then(Value::as_array)
+                    .map(|fields| {
+                        fields
+                            .iter()
+                            .filter_map(|f| f.get("name")?.as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                Some(GraphqlType {
+                    name: name.to_string(),
+                    kind: t.get("kind")?.as_str()?.to_string(),
+                    fields,
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            types,
+            queries: root_fields("queryType"),
+            mutations: root_fields("mutationType"),
+            subscriptions: root_fields("subscriptionType"),
+        })
+    }
+}
+
+/// A root field from its introspection entry
+fn parse_field(field: &Value) -> Option<GraphqlField> {
+    let args = field
+        .get("args")
+        .and_then(Value::as_array)
+        .map(|args| {
+            args.iter()
+                .filter_map(|arg| {
+                    Some(format!(
+                        "{}: {}",
+                        arg.get("name")?.as_str()?,
+                        type_ref(arg.get("type")?)
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(GraphqlField {
+        name: field.get("name")?.as_str()?.to_string(),
+        r#type: type_ref(field.get("type")?),
+        args,
+        description: field
+            .get("description")
+            .and_then(Value::as_str)
+            .filter(|d| !d.is_empty())
+            .map(str::to_string),
+        deprecated: field
+            .get("isDeprecated")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+    })
+}
+
+/// A type reference in SDL notation: `NON_NULL` becomes `!`, `LIST` becomes `[...]`
+fn type_ref(type_ref: &Value) -> String {
+    let inner = || {
+        type_ref
+            .get("ofType")
+            .map(self::type_ref)
+            .unwrap_or_default()
+    };
+    match type_ref.get("kind").and_then(Value::as_str) {
+        Some("NON_NULL") => format!("{}!", inner()),
+        Some("LIST") => format!("[{}]", inner()),
+        _ => type_ref
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or("?")
+            .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_detection() {
+        let json = MediaType::parse("application/json").unwrap();
+        let graphql = MediaType::parse("application/graphql-response+json").unwrap();
+
+        assert_eq!(
+            detect("http://example.com/api", Some(&graphql), b"{}"),
+            Some(Detection::ContentType)
+        );
+        assert_eq!(
+            detect(
+                "http://example.com/api",
+                Some(&json),
+                br#"{"errors":[{"message":"Must provide query string."}]}"#
+            ),
+            Some(Detection::ErrorResponse)
+        );
+        assert_eq!(
+            detect("http://example.com/v1/GraphQL", Some(&json), b"<html/>"),
+            Some(Detection::Path)
+        );
+
+        // REST-style errors and ordinary endpoints are not GraphQL
+        for body in [
+            json!({"errors": [{"message": "Not found"}]}),
+            json!({"errors": [{"detail": "query is invalid"}]}),
+            json!({"errors": [{"message": "Bad query"}], "status": 400}),
+            json!({"_links": {}}),
+        ] {
+            let body = body.to_string();
+            assert_eq!(
+                detect("http://example.com/api", Some(&json), body.as_bytes()),
+                None,
+                "{}",
+                body
+            );
+        }
+    }
+
+    #[test]
+    fn test_schema_from_introspection() {
+        let named = |kind: &str, name: &str| json!({"kind": kind, "name": name});
+        let non_null = |inner: Value| json!({"kind": "NON_NULL", "name": null, "ofType": inner});
+        let list = |inner: Value| json!({"kind": "LIST", "name": null, "ofType": inner});
+
+        let response = json!({"data": {"__schema": {
+            "queryType": {"name": "Query"},
+            "mutationType": {"name": "Mutation"},
+            "subscriptionType": null,
+            "types": [
+                {"kind": "OBJECT", "name": "Query", "fields": [
+                    {"name": "user", "description": "Look up a user", "isDeprecated": false,
+                     "args": [{"name": "id", "type": non_null(named("SCALAR", "ID"))}],
+                     "type": named("OBJECT", "User")},
+                    {"name": "users", "description": "", "isDeprecated": true, "args": [],
+                     "type": non_null(list(non_null(named("OBJECT", "User"))))}
+                ]},
+                {"kind": "OBJECT", "name": "Mutation", "fields": [
+                    {"name": "deleteUser", "isDeprecated": false,
+                     "args": [{"name": "id", "type": non_null(named("SCALAR", "ID"))}],
+                     "type": named("SCALAR", "Boolean")}
+                ]},
+                {"kind": "OBJECT", "name": "User", "fields": [
+                    {"name": "id", "args": [], "type": named("SCALAR", "ID")}
+                ]},
+                {"kind": "SCALAR", "name": "ID", "fields": null},
+                {"kind": "OBJECT", "name": "__Schema", "fields": []}
+            ]
+        }}});
+
+        let schema = GraphqlSchema::from_introspection(&response).unwrap();
+        let signatures: Vec<String> = schema.queries.iter().map(GraphqlField::signature).collect();
+        assert_eq!(signatures, ["user(id: ID!): User", "users: [User!]!"]);
+        assert_eq!(
+            schema.queries[0].description.as_deref(),
+            Some("Look up a user")
+        );
+        assert_eq!(schema.queries[1].description, None);
+        assert!(schema.queries[1].deprecated);
+        assert_eq!(
+            schema.mutations[0].signature(),
+            "deleteUser(id: ID!): Boolean"
+        );
+        assert!(schema.subscriptions.is_empty());
+
+        let types: Vec<(&str, &str)> = schema
+            .types
+            .iter()
+            .map(|t| (t.name.as_str(), t.kind.as_str()))
+            .collect();
+        assert_eq!(
+            types,
+            [
+                ("Query", "OBJECT"),
+                ("Mutation", "OBJECT"),
+                ("User", "OBJECT"),
+                ("ID", "SCALAR")
+            ]
+        );
+        assert_eq!(schema.types[2].fields, ["id"]);
+
+        let disabled = json!({"errors": [{"message": "GraphQL introspection is not allowed"}]});
+        let error = GraphqlSchema::from_introspection(&disabled).unwrap_err();
+        assert!(error.to_string().contains("introspection is not allowed"));
+    }
+}
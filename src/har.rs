@@ -0,0 +1,391 @@
+//! HAR (HTTP Archive) 1.2 recording of crawl traffic
+//!
+//! When `CrawlerConfig::record_har` is enabled the crawler records every request it
+//! makes as a [`HarEntry`]. The resulting [`Har`] can be opened in browser devtools
+//! or any other HAR viewer.
+
+use crate::auth::is_sensitive_header;
+use crate::error::{CrawlerError, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tracing::info;
+use url::Url;
+
+/// HAR format version written by the crawler
+pub const HAR_VERSION: &str = "1.2";
+
+pub use crate::auth::REDACTED;
+
+/// Top-level HAR document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Har {
+    pub log: HarLog,
+}
+
+/// The HAR log containing all recorded entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarLog {
+    pub version: String,
+    pub creator: HarCreator,
+    pub entries: Vec<HarEntry>,
+}
+
+/// The application that created the HAR
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarCreator {
+    pub name: String,
+    pub version: String,
+}
+
+/// A single request/response exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarEntry {
+    /// ISO 8601 timestamp of when the request started
+    pub started_date_time: String,
+
+    /// Total elapsed time of the request in milliseconds
+    pub time: f64,
+
+    pub request: HarRequest,
+    pub response: HarResponse,
+
+    #[serde(default)]
+    pub cache: serde_json::Map<String, serde_json::Value>,
+
+    pub timings: HarTimings,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// A name/value pair used for headers, query parameters and cookies
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HarNameValue {
+    pub name: String,
+    pub value: String,
+}
+
+/// The recorded request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<HarNameValue>,
+    #[serde(default)]
+    pub headers: Vec<HarNameValue>,
+    #[serde(default)]
+    pub query_string: Vec<HarNameValue>,
+    pub headers_size: i64,
+    pub body_size: i64,
+}
+
+/// The recorded response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarResponse {
+    pub status: u16,
+    pub status_text: String,
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<HarNameValue>,
+    #[serde(default)]
+    pub headers: Vec<HarNameValue>,
+    pub content: HarContent,
+    #[serde(rename = "redirectURL", default)]
+    pub redirect_url: String,
+    pub headers_size: i64,
+    pub body_size: i64,
+}
+
+/// Response body details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarContent {
+    /// Length of the full response body in bytes
+    pub size: i64,
+    pub mime_type: String,
+    /// Response body, possibly truncated (see `truncated`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// `base64` when `text` holds a binary body encoded as base64
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    /// Whether `text` was cut short by the body size limit, so the entry can't be replayed
+    #[serde(
+        rename = "_truncated",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub truncated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// Timing breakdown; phases the crawler cannot observe are reported as -1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarTimings {
+    pub blocked: f64,
+    pub dns: f64,
+    pub connect: f64,
+    pub send: f64,
+    pub wait: f64,
+    pub receive: f64,
+}
+
+impl HarTimings {
+    /// Timings with only the wait (time to headers) and receive (body) phases known
+    pub fn new(wait_ms: f64, receive_ms: f64) -> Self {
+        Self {
+            blocked: -1.0,
+            dns: -1.0,
+            connect: -1.0,
+            send: 0.0,
+            wait: wait_ms,
+            receive: receive_ms,
+        }
+    }
+}
+
+impl Har {
+    /// Create a HAR document from recorded entries
+    pub fn new(entries: Vec<HarEntry>) -> Self {
+        Self {
+            log: HarLog {
+                version: HAR_VERSION.to_string(),
+                creator: HarCreator {
+                    name: env!("CARGO_PKG_NAME").to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                },
+                entries,
+            },
+        }
+    }
+
+    /// Load a HAR document from a file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(CrawlerError::from)
+    }
+
+    /// Write the HAR document to a file as pretty-printed JSON
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+            && !parent.exists()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        info!(
+            "HAR with {} entries saved to: {}",
+            self.log.entries.len(),
+            path.display()
+        );
+        Ok(())
+    }
+}
+
+impl HarRequest {
+    /// Create a bodiless request record for a URL with the given headers
+    pub fn new(method: &str, url: &str, http_version: &str, headers: Vec<HarNameValue>) -> Self {
+        let query_string = Url::parse(url)
+            .map(|u| {
+                u.query_pairs()
+                    .map(|(name, value)| HarNameValue {
+                        name: name.into_owned(),
+                        value: value.into_owned(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            method: method.to_string(),
+            url: url.to_string(),
+            http_version: http_version.to_string(),
+            cookies: Vec::new(),
+            headers,
+            query_string,
+            headers_size: -1,
+            body_size: 0,
+        }
+    }
+}
+
+impl HarContent {
+    /// Record a response body, truncating it to `limit` bytes (0 means no limit)
+    ///
+    /// Bodies that aren't valid UTF-8 are stored base64-encoded.
+    pub fn from_body(body: &[u8], mime_type: &str, limit: usize) -> Self {
+        let truncated = limit > 0 && body.len() > limit;
+        let (text, encoding, kept) = match std::str::from_utf8(body) {
+            Ok(text) => {
+                let mut end = if truncated { limit } else { text.len() };
+                while !text.is_char_boundary(end) {
+                    end -= 1;
+                }
+                (text[..end].to_string(), None, end)
+            }
+            Err(_) => {
+                let end = if truncated { limit } else { body.len() };
+                (BASE64.encode(&body[..end]), Some("base64".to_string()), end)
+            }
+        };
+        let comment =
+            truncated.then(|| format!("Body truncated to {} of {} bytes", kept, body.len()));
+
+        Self {
+            size: body.len() as i64,
+            mime_type: mime_type.to_string(),
+            text: Some(text),
+            encoding,
+            truncated,
+            comment,
+        }
+    }
+
+    /// The recorded body bytes, decoding base64 when `encoding` says so
+    pub fn body(&self) -> Result<Vec<u8>> {
+        let text = self.text.as_deref().unwrap_or_default();
+        match self.encoding.as_deref() {
+            None => Ok(text.as_bytes().to_vec()),
+            Some("base64") => BASE64.decode(text).map_err(|e| {
+                CrawlerError::config(format!("Invalid base64 body in HAR entry: {}", e))
+            }),
+            Some(other) => Err(CrawlerError::config(format!(
+                "Unsupported HAR body encoding: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Convert a header map to HAR name/value pairs, redacting credential headers
+///
+/// A header is a credential when its name says so or its value is marked sensitive,
+/// as API keys sent under arbitrary header names are.
+pub fn har_headers(headers: &reqwest::header::HeaderMap) -> Vec<HarNameValue> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let name = name.as_str().to_string();
+            let value = if is_sensitive_header(&name) || value.is_sensitive() {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            HarNameValue { name, value }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
+
+    #[test]
+    fn test_har_headers_redacts_credentials() {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let mut customer_key = HeaderValue::from_static("k-123");
+        customer_key.set_sensitive(true);
+        headers.insert("x-customer-id", customer_key);
+
+        let pairs = har_headers(&headers);
+        let value = |name: &str| pairs.iter().find(|p| p.name == name).unwrap().value.clone();
+
+        assert_eq!(value("authorization"), REDACTED);
+        assert_eq!(value("content-type"), "application/json");
+        // Values marked sensitive are redacted whatever the header is called
+        assert_eq!(value("x-customer-id"), REDACTED);
+    }
+
+    #[test]
+    fn test_content_truncation() {
+        let content = HarContent::from_body("héllo world".as_bytes(), "text/plain", 2);
+        // "é" is two bytes, so the cut falls back to the previous char boundary
+        assert_eq!(content.text.as_deref(), Some("h"));
+        assert_eq!(content.size, 12);
+        assert!(content.truncated);
+        assert!(content.comment.is_some());
+        assert_eq!(serde_json::to_value(&content).unwrap()["_truncated"], true);
+
+        let full = HarContent::from_body(b"{}", "application/json", 0);
+        assert_eq!(full.text.as_deref(), Some("{}"));
+        assert!(!full.truncated);
+        assert!(full.comment.is_none());
+        assert!(
+            serde_json::to_value(&full)
+                .unwrap()
+                .get("_truncated")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_binary_bodies_are_base64_encoded() {
+        let png = b"\x89PNG\r\n\x1a\n\x00\xff";
+        let content = HarContent::from_body(png, "image/png", 0);
+        assert_eq!(content.encoding.as_deref(), Some("base64"));
+        assert_eq!(content.text.as_deref(), Some("iVBORw0KGgoA/w=="));
+        assert_eq!(content.body().unwrap(), png);
+        assert_eq!(
+            serde_json::to_value(&content).unwrap()["encoding"],
+            "base64"
+        );
+
+        let cut = HarContent::from_body(png, "image/png", 4);
+        assert!(cut.truncated);
+        assert_eq!(cut.body().unwrap(), &png[..4]);
+
+        let text = HarContent::from_body(b"{}", "application/json", 0);
+        assert_eq!(text.encoding, None);
+        assert_eq!(text.body().unwrap(), b"{}");
+    }
+
+    #[test]
+    fn test_har_round_trip() {
+        let entry = HarEntry {
+            started_date_time: "2024-01-01T00:00:00Z".to_string(),
+            time: 12.5,
+            request: HarRequest::new("GET", "http://example.com/a?x=1", "HTTP/1.1", Vec::new()),
+            response: HarResponse {
+                status: 200,
+                status_text: "OK".to_string(),
+                http_version: "HTTP/1.1".to_string(),
+                cookies: Vec::new(),
+                headers: Vec::new(),
+                content: HarContent::from_body(b"{}", "application/json", 0),
+                redirect_url: String::new(),
+                headers_size: -1,
+                body_size: 2,
+            },
+            cache: Default::default(),
+            timings: HarTimings::new(10.0, 2.5),
+            comment: None,
+        };
+
+        let har = Har::new(vec![entry]);
+        let json = serde_json::to_value(&har).unwrap();
+
+        assert_eq!(json["log"]["version"], "1.2");
+        let entry = &json["log"]["entries"][0];
+        assert_eq!(entry["startedDateTime"], "2024-01-01T00:00:00Z");
+        assert_eq!(entry["request"]["queryString"][0]["name"], "x");
+        assert_eq!(entry["response"]["redirectURL"], "");
+        assert_eq!(entry["response"]["content"]["mimeType"], "application/json");
+
+        let parsed: Har = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.log.entries.len(), 1);
+    }
+}
@@ -0,0 +1,351 @@
+//! Host patterns used to scope settings to particular hosts
+
+use crate::error::{CrawlerError, Result};
+use std::fmt;
+use url::Url;
+
+/// A pattern matching URL hosts
+///
+/// Supported forms:
+/// - `api.example.com` matches that host exactly
+/// - `*.example.com` matches any subdomain of `example.com`, but not `example.com` itself
+/// - `*` matches every host
+/// - any of the above followed by `:port` additionally requires that port
+///   (default ports count, so `example.com:443` matches `https://example.com/`)
+///
+/// Matching is case-insensitive. IPv6 addresses are written in brackets, e.g. `[::1]:8080`.
+///
+/// [`HostPattern::parse_domain`] parses the same syntax for crawl scoping, where a
+/// bare domain also covers its subdomains.
+#[derive(Clone, PartialEq, Eq)]
+pub struct HostPattern {
+    host: HostMatch,
+    port: Option<u16>,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+enum HostMatch {
+    Any,
+    Exact(String),
+    Subdomain(String),
+    /// The domain itself or any subdomain
+    Domain(String),
+}
+
+impl HostPattern {
+    /// Parse a host pattern
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let pattern = pattern.trim().to_ascii_lowercase();
+        if pattern.is_empty() {
+            return Err(CrawlerError::config("Empty host pattern"));
+        }
+
+        // Split off a trailing port; IPv6 hosts keep their brackets to match `Url::host_str`
+        let (host, port) = if pattern.starts_with('[') {
+            match pattern.find(']') {
+                Some(end) => (&pattern[..=end], &pattern[end + 1..]),
+                None => {
+                    return Err(CrawlerError::config(format!(
+                        "Unterminated IPv6 host pattern: {}",
+                        pattern
+                    )));
+                }
+            }
+        } else {
+            match pattern.matches(':').count() {
+                0 => (pattern.as_str(), ""),
+                1 => pattern.split_at(pattern.find(':').unwrap_or_default()),
+                _ => {
+                    return Err(CrawlerError::config(format!(
+                        "IPv6 host patterns must be bracketed: {}",
+                        pattern
+                    )));
+                }
+            }
+        };
+
+        let port = match port {
+            "" => None,
+            port => Some(
+                port.strip_prefix(':')
+                    .and_then(|p| p.parse::<u16>().ok())
+                    .ok_or_else(|| {
+                        CrawlerError::config(format!("Invalid port in host pattern: {}", pattern))
+                    })?,
+            ),
+        };
+        let host = host.to_string();
+
+        let host = if host == "*" {
+            HostMatch::Any
+        } else if let Some(suffix) = host.strip_prefix("*.") {
+            if suffix.is_empty() || suffix.contains('*') {
+                return Err(CrawlerError::config(format!(
+                    "Invalid wildcard host pattern: {}",
+                    pattern
+                )));
+            }
+            HostMatch::Subdomain(suffix.to_string())
+        } else if host.contains('*') {
+            return Err(CrawlerError::config(format!(
+                "Wildcards are only supported as a leading '*.': {}",
+                pattern
+            )));
+        } else {
+            HostMatch::Exact(host)
+        };
+
+        Ok(Self { host, port })
+    }
+
+    /// Parse a domain scoping rule: like [`HostPattern::parse`], except that a bare
+    /// domain such as `example.com` also matches `api.example.com`
+    ///
+    /// IP addresses and `localhost` still match only themselves.
+    pub fn parse_domain(pattern: &str) -> Result<Self> {
+        let mut parsed = Self::parse(pattern)?;
+        if let HostMatch::Exact(host) = &parsed.host
+            && !is_ip_or_local(host)
+        {
+            parsed.host = HostMatch::Domain(host.clone());
+        }
+        Ok(parsed)
+    }
+
+    /// The site a URL belongs to: its registrable domain and all subdomains,
+    /// ignoring scheme and port
+    ///
+    /// IP addresses and single-label hosts such as `localhost` are their own site.
+    /// Without a public suffix list the registrable domain is approximated as the
+    /// last two labels, or three under two-letter country-code second-level domains
+    /// like `co.uk` and `com.au`. Tenants of well-known hosting platforms
+    /// (`alice.github.io`, `app.herokuapp.com`, S3 buckets, ...) are separate sites;
+    /// platforms missing from [`SHARED_SUFFIXES`] are still treated as one site.
+    pub fn site_of(url: &Url) -> Option<Self> {
+        let host = url.host_str()?.to_ascii_lowercase();
+        let host = if is_ip_or_local(&host) {
+            HostMatch::Exact(host)
+        } else {
+            HostMatch::Domain(registrable_domain(&host).to_string())
+        };
+        Some(Self { host, port: None })
+    }
+
+    /// Check whether a URL's host (and port, if the pattern has one) matches
+    pub fn matches_url(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+
+        if let Some(port) = self.port
+            && url.port_or_known_default() != Some(port)
+        {
+            return false;
+        }
+
+        self.matches_host(host)
+    }
+
+    /// Check whether a URL string matches; unparseable URLs never match
+    pub fn matches(&self, url: &str) -> bool {
+        Url::parse(url).is_ok_and(|u| self.matches_url(&u))
+    }
+
+    /// Check a bare host name against the host part of the pattern
+    pub fn matches_host(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        match &self.host {
+            HostMatch::Any => true,
+            HostMatch::Exact(expected) => host == *expected,
+            HostMatch::Subdomain(suffix) => is_subdomain(&host, suffix),
+            HostMatch::Domain(domain) => host == *domain || is_subdomain(&host, domain),
+        }
+    }
+}
+
+/// Whether `host` is a strict subdomain of `domain`
+fn is_subdomain(host: &str, domain: &str) -> bool {
+    host.strip_suffix(domain)
+        .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.'))
+}
+
+/// Whether a host is an IP literal or has no dots (e.g. `localhost`)
+fn is_ip_or_local(host: &str) -> bool {
+    host.starts_with('[') || host.parse::<std::net::IpAddr>().is_ok() || !host.contains('.')
+}
+
+/// Multi-tenant platform domains whose subdomains belong to different owners,
+/// so each subdomain is a site of its own
+///
+/// Hosts under `amazonaws.com` (S3 buckets, API gateways, ...) are always their own site.
+pub const SHARED_SUFFIXES: &[&str] = &[
+    "github.io",
+    "gitlab.io",
+    "herokuapp.com",
+    "cloudfront.net",
+    "azurewebsites.net",
+    "blob.core.windows.net",
+    "appspot.com",
+    "firebaseapp.com",
+    "web.app",
+    "netlify.app",
+    "vercel.app",
+    "pages.dev",
+    "workers.dev",
+    "fly.dev",
+    "onrender.com",
+    "blogspot.com",
+];
+
+/// Approximate the registrable domain of a host name
+fn registrable_domain(host: &str) -> &str {
+    let trimmed = host.trim_end_matches('.');
+
+    // AWS service hosts nest tenants under regions (`bucket.s3.eu-west-1.amazonaws.com`)
+    if is_subdomain(trimmed, "amazonaws.com") {
+        return host;
+    }
+
+    let labels: Vec<&str> = trimmed.rsplit('.').collect();
+    let shared = SHARED_SUFFIXES
+        .iter()
+        .filter(|suffix| is_subdomain(trimmed, suffix))
+        .map(|suffix| suffix.split('.').count() + 1)
+        .max();
+    let keep = match (shared, labels.as_slice()) {
+        (Some(keep), _) => keep,
+        (None, [tld, second, _, ..])
+            if tld.len() == 2
+                && matches!(*second, "co" | "com" | "net" | "org" | "ac" | "gov" | "edu") =>
+        {
+            3
+        }
+        _ => 2,
+    };
+    if labels.len() <= keep {
+        return host;
+    }
+    let suffix_len: usize = labels[..keep].iter().map(|l| l.len()).sum::<usize>() + keep - 1;
+    &host[host.len() - suffix_len..]
+}
+
+impl<'de> serde::Deserialize<'de> for HostPattern {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Self::parse(&pattern).map_err(CrawlerError::into_de_error)
+    }
+}
+
+impl fmt::Display for HostPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.host {
+            HostMatch::Any => f.write_str("*")?,
+            HostMatch::Exact(host) => f.write_str(host)?,
+            HostMatch::Subdomain(suffix) => write!(f, "*.{}", suffix)?,
+            HostMatch::Domain(domain) => f.write_str(domain)?,
+        }
+        if let Some(port) = self.port {
+            write!(f, ":{}", port)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for HostPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HostPattern({})", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_and_wildcard_patterns() {
+        let exact = HostPattern::parse("partner.example.org").unwrap();
+        assert!(exact.matches("https://partner.example.org/api"));
+        assert!(exact.matches("https://PARTNER.example.org/api"));
+        assert!(!exact.matches("https://evil.partner.example.org/api"));
+
+        let wildcard = HostPattern::parse("*.internal.example.com").unwrap();
+        assert!(wildcard.matches("http://a.internal.example.com/"));
+        assert!(wildcard.matches("http://a.b.internal.example.com/"));
+        assert!(!wildcard.matches("http://internal.example.com/"));
+        assert!(!wildcard.matches("http://notinternal.example.com/"));
+
+        let any = HostPattern::parse("*").unwrap();
+        assert!(any.matches("http://anything.test/"));
+    }
+
+    #[test]
+    fn test_ports_and_ip_hosts() {
+        let with_port = HostPattern::parse("localhost:8080").unwrap();
+        assert!(with_port.matches("http://localhost:8080/"));
+        assert!(!with_port.matches("http://localhost:9090/"));
+
+        let default_port = HostPattern::parse("example.com:443").unwrap();
+        assert!(default_port.matches("https://example.com/"));
+        assert!(!default_port.matches("http://example.com/"));
+
+        let ipv4 = HostPattern::parse("127.0.0.1").unwrap();
+        assert!(ipv4.matches("http://127.0.0.1:3000/"));
+
+        let ipv6 = HostPattern::parse("[::1]:8080").unwrap();
+        assert!(ipv6.matches("http://[::1]:8080/"));
+        assert!(!ipv6.matches("http://[::1]:8081/"));
+        assert_eq!(ipv6.to_string(), "[::1]:8080");
+    }
+
+    #[test]
+    fn test_domain_rules_and_sites() {
+        let domain = HostPattern::parse_domain("example.com").unwrap();
+        assert!(domain.matches("https://example.com/"));
+        assert!(domain.matches("https://api.example.com/"));
+        assert!(!domain.matches("https://notexample.com/"));
+
+        let ip = HostPattern::parse_domain("10.0.0.5:8080").unwrap();
+        assert!(ip.matches("http://10.0.0.5:8080/"));
+        assert!(!ip.matches("http://10.0.0.5/"));
+
+        let site = |url: &str| HostPattern::site_of(&Url::parse(url).unwrap()).unwrap();
+        let api = site("https://api.v2.example.com:8443/root");
+        assert_eq!(api.to_string(), "example.com");
+        assert!(api.matches("http://docs.example.com/"));
+        assert!(!api.matches("https://example.org/"));
+
+        assert_eq!(
+            site("https://shop.example.co.uk/").to_string(),
+            "example.co.uk"
+        );
+        assert_eq!(site("http://127.0.0.1:3000/").to_string(), "127.0.0.1");
+
+        // Tenants of hosting platforms are separate sites
+        let pages = site("https://alice.github.io/project/");
+        assert_eq!(pages.to_string(), "alice.github.io");
+        assert!(pages.matches("https://docs.alice.github.io/"));
+        assert!(!pages.matches("https://bob.github.io/"));
+        assert!(!site("https://app.herokuapp.com/").matches("https://other.herokuapp.com/"));
+        assert!(
+            !site("https://bucket.s3.amazonaws.com/").matches("https://other.s3.amazonaws.com/")
+        );
+        assert!(
+            !site("https://bucket.s3.eu-west-1.amazonaws.com/")
+                .matches("https://other.s3.eu-west-1.amazonaws.com/")
+        );
+        assert_eq!(site("https://github.io/").to_string(), "github.io");
+        assert!(!site("http://127.0.0.1:3000/").matches("http://127.0.0.2/"));
+        assert!(site("http://localhost:3000/").matches("http://localhost:4000/"));
+        assert!(site("http://[::1]:3000/").matches("http://[::1]/"));
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!(HostPattern::parse("").is_err());
+        assert!(HostPattern::parse("api.*.com").is_err());
+        assert!(HostPattern::parse("*.").is_err());
+        assert!(HostPattern::parse("example.com:notaport").is_err());
+    }
+}
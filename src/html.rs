@@ -0,0 +1,259 @@
+//! Link extraction from HTML pages
+//!
+//! API roots often answer with a documentation portal or a browsable API
+//! rather than JSON. Links in these pages bridge into the API itself:
+//!
+//! - `<a href>` becomes an endpoint titled by its text
+//! - `<link rel href>` keeps its rel and type; presentational rels such as
+//!   `stylesheet` and `icon` are ignored
+//! - `<form action method>` keeps its method, honouring a hidden `_method`
+//!   override as used by browsable APIs
+//!
+//! Only links to in-scope hosts are kept, so a portal's references to code
+//! hosts and social sites don't end up in the results.
+
+use crate::types::{ApiEndpoint, QueueItem};
+use scraper::{ElementRef, Html, Selector};
+use serde_json::Value;
+use std::collections::HashSet;
+use url::Url;
+
+/// `<link>` rels that never point at API resources
+const PRESENTATIONAL_RELS: &[&str] = &[
+    "stylesheet",
+    "icon",
+    "shortcut",
+    "apple-touch-icon",
+    "mask-icon",
+    "manifest",
+    "preload",
+    "prefetch",
+    "preconnect",
+    "dns-prefetch",
+    "modulepreload",
+];
+
+/// Extract endpoints from an HTML page fetched for `item`, keeping only URLs `in_scope` accepts
+pub fn extract_endpoints(
+    html: &str,
+    item: &QueueItem,
+    in_scope: impl Fn(&str) -> bool,
+) -> Vec<ApiEndpoint> {
+    let document = Html::parse_document(html);
+    let Ok(page_url) = Url::parse(&item.url) else {
+        return Vec::new();
+    };
+    let base = select(&document, "base[href]")
+        .next()
+        .and_then(|base| page_url.join(base.value().attr("href")?).ok())
+        .unwrap_or_else(|| page_url.clone());
+
+    let mut endpoints = Vec::new();
+    for element in select(&document, "a[href], link[href], form") {
+        let name = element.value().name();
+        let reference = match name {
+            "form" => element.value().attr("action").unwrap_or(""),
+            _ => element.value().attr("href").unwrap_or(""),
+        };
+        let Some(href) = resolve(&base, reference) else {
+            continue;
+        };
+        if !in_scope(&href) {
+            continue;
+        }
+
+        let endpoint = match name {
+            "a" => anchor_endpoint(&element, item, href),
+            "link" => match link_endpoint(&element, item, href) {
+                Some(endpoint) => endpoint,
+                None => continue,
+            },
+            _ => form_endpoint(&element, item, href),
+        };
+        endpoints
+            .push(endpoint.with_metadata("element".to_string(), Value::String(name.to_string())));
+    }
+
+    let mut seen = HashSet::new();
+    endpoints.retain(|e| seen.insert((e.href.clone(), e.rel.clone(), e.method.clone())));
+    endpoints
+}
+
+/// Elements matching a CSS selector known to be valid
+fn select<'a>(document: &'a Html, selector: &str) -> impl Iterator<Item = ElementRef<'a>> {
+    let selector = Selector::parse(selector).expect("valid selector");
+    document.select(&selector).collect::<Vec<_>>().into_iter()
+}
+
+/// Resolve a reference to an absolute http(s) URL without fragment
+///
+/// In-page anchors and `javascript:`, `mailto:` and similar URLs yield `None`.
+fn resolve(base: &Url, reference: &str) -> Option<String> {
+    let reference = reference.trim();
+    if reference.starts_with('#') {
+        return None;
+    }
+    let mut url = base.join(reference).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    url.set_fragment(None);
+    Some(url.to_string())
+}
+
+/// Whitespace-collapsed text inside an element
+fn element_text(element: &ElementRef) -> String {
+    element
+        .text()
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// An endpoint for `<a href>`, titled by its text or `title` attribute
+fn anchor_endpoint(element: &ElementRef, item: &QueueItem, href: String) -> ApiEndpoint {
+    let mut endpoint = ApiEndpoint::new(href, item.depth + 1)
+        .with_rel(element.value().attr("rel").map(str::to_string))
+        .with_parent(Some(item.url.clone()));
+    endpoint.r#type = element.value().attr("type").map(str::to_string);
+    let text = element_text(element);
+    endpoint.title = match element.value().attr("title") {
+        Some(title) => Some(title.to_string()),
+        None if !text.is_empty() => Some(text),
+        None => None,
+    };
+    endpoint
+}
+
+/// An endpoint for `<link rel href>`, unless its rels are all presentational
+fn link_endpoint(element: &ElementRef, item: &QueueItem, href: String) -> Option<ApiEndpoint> {
+    let rel = element.value().attr("rel").unwrap_or("").trim();
+    let rels: Vec<String> = rel
+        .split_whitespace()
+        .map(str::to_ascii_lowercase)
+        .collect();
+    if rels.is_empty()
+        || rels
+            .iter()
+            .all(|r| PRESENTATIONAL_RELS.contains(&r.as_str()))
+    {
+        return None;
+    }
+
+    let mut endpoint = ApiEndpoint::new(href, item.depth + 1)
+        .with_rel(Some(rel.to_string()))
+        .with_parent(Some(item.url.clone()));
+    endpoint.r#type = element.value().attr("type").map(str::to_string);
+    endpoint.title = element.value().attr("title").map(str::to_string);
+    Some(endpoint)
+}
+
+/// An endpoint for `<form>`, with its method and field names
+fn form_endpoint(element: &ElementRef, item: &QueueItem, href: String) -> ApiEndpoint {
+    let fields: Vec<ElementRef> = element
+        .select(
+            &Selector::parse("input[name], select[name], textarea[name]").expect("valid selector"),
+        )
+        .collect();
+    let override_method = fields
+        .iter()
+        .find(|field| field.value().attr("name") == Some("_method"))
+        .and_then(|field| field.value().attr("value"));
+    let method = override_method
+        .or(element.value().attr("method"))
+        .unwrap_or("GET")
+        .trim()
+        .to_ascii_uppercase();
+
+    let mut endpoint = ApiEndpoint::new(href, item.depth + 1)
+        .with_rel(Some("form".to_string()))
+        .with_parent(Some(item.url.clone()));
+    endpoint.method = Some(method);
+    endpoint.r#type = element.value().attr("enctype").map(str::to_string);
+    endpoint.title = element.value().attr("title").map(str::to_string);
+
+    let names: Vec<Value> = fields
+        .iter()
+        .filter_map(|field| field.value().attr("name"))
+        .filter(|name| !name.starts_with('_') && *name != "csrfmiddlewaretoken")
+        .map(|name| Value::String(name.to_string()))
+        .collect();
+    if !names.is_empty() {
+        endpoint = endpoint.with_metadata("fields".to_string(), Value::Array(names));
+    }
+    endpoint
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_links_forms_and_scope() {
+        let page = r##"<!DOCTYPE html>
+            <html><head>
+              <link rel="stylesheet" href="/static/site.css">
+              <link rel="alternate" type="application/json" href="/api/?format=json">
+              <link rel="service-desc" href="/openapi.json">
+            </head><body>
+              <nav><a href="#main">Skip</a> <a href="mailto:api@example.com">Mail</a></nav>
+              <a href="users/">  Users
+                 list </a>
+              <a href="users/#top" title="Users again">Users</a>
+              <a href="https://github.com/example/api">Source</a>
+              <a href="https://docs.example.com/guide" rel="help">Guide</a>
+              <form action="/api/users/" method="post">
+                <input type="hidden" name="csrfmiddlewaretoken" value="x">
+                <input name="username"><textarea name="bio"></textarea>
+              </form>
+              <form action="/api/users/1/" method="POST">
+                <input type="hidden" name="_method" value="delete">
+              </form>
+              <form><input name="q"></form>
+            </body></html>"##;
+        let item = QueueItem::new("https://example.com/api/".to_string(), 0, None);
+        let endpoints = extract_endpoints(page, &item, |url| url.contains("example.com/"));
+
+        let links: Vec<(&str, Option<&str>, Option<&str>)> = endpoints
+            .iter()
+            .map(|e| (e.href.as_str(), e.rel.as_deref(), e.method.as_deref()))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                (
+                    "https://example.com/api/?format=json",
+                    Some("alternate"),
+                    None
+                ),
+                (
+                    "https://example.com/openapi.json",
+                    Some("service-desc"),
+                    None
+                ),
+                ("https://example.com/api/users/", None, None),
+                ("https://docs.example.com/guide", Some("help"), None),
+                ("https://example.com/api/users/", Some("form"), Some("POST")),
+                (
+                    "https://example.com/api/users/1/",
+                    Some("form"),
+                    Some("DELETE")
+                ),
+                ("https://example.com/api/", Some("form"), Some("GET")),
+            ]
+        );
+        assert_eq!(endpoints[2].title.as_deref(), Some("Users list"));
+        assert_eq!(endpoints[2].depth, 1);
+        assert_eq!(endpoints[2].metadata["element"], "a");
+        assert_eq!(
+            endpoints[4].metadata["fields"],
+            serde_json::json!(["username", "bio"])
+        );
+        assert_eq!(endpoints[6].metadata["fields"], serde_json::json!(["q"]));
+
+        let based = r#"<html><head><base href="https://example.com/v2/"></head>
+            <body><a href="orders">Orders</a></body></html>"#;
+        let endpoints = extract_endpoints(based, &item, |_| true);
+        assert_eq!(endpoints[0].href, "https://example.com/v2/orders");
+    }
+}
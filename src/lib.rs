@@ -2,16 +2,38 @@
 //!
 //! A Rust library for crawling REST APIs and mapping their endpoint structure.
 
+pub mod auth;
+pub mod config;
 pub mod crawler;
 pub mod error;
+pub mod export;
+pub mod filter;
+pub mod graphql;
+pub mod har;
+pub mod host;
+pub mod html;
+pub mod media;
+pub mod methods;
 pub mod output;
+pub mod pagination;
+pub mod profile;
+pub mod proxy;
+pub mod routes;
+pub mod safety;
+pub mod sampling;
+pub mod schema;
+pub mod sensitive;
+pub mod session;
+pub mod tls;
+pub mod transport;
 pub mod types;
+pub mod xml;
 
 pub use crawler::ApiCrawler;
 pub use error::{CrawlerError, Result};
-pub use types::{ApiEndpoint, CrawlResult, CrawlerConfig};
+pub use types::{ApiEndpoint, CrawlResult, CrawlerConfig, UrlVisit};
 
 /// Re-export commonly used types
 pub mod prelude {
-    pub use crate::{ApiCrawler, CrawlerError, Result, ApiEndpoint, CrawlResult, CrawlerConfig};
+    pub use crate::{ApiCrawler, ApiEndpoint, CrawlResult, CrawlerConfig, CrawlerError, Result};
 }
@@ -2,30 +2,60 @@
 //!
 //! A command-line tool for crawling REST APIs and mapping their endpoint structure.
 
+use api_crawler::auth::{
+    ApiKeyLocation, Auth, CredentialScope, OAuth2ClientCredentials, SecretSource,
+};
+use api_crawler::config::ConfigFile;
+use api_crawler::export::{save_http_requests, save_postman_collection};
+use api_crawler::filter::UrlPattern;
+use api_crawler::host::HostPattern;
+use api_crawler::media::{MediaType, PROBE_MEDIA_TYPES};
 use api_crawler::output::{
     OutputConfig, OutputFormat, print_endpoints_detailed, print_hierarchical_summary,
-    print_summary, save_results_to_file,
+    print_profiles_summary, print_summary, save_results_to_file,
 };
 use api_crawler::prelude::*;
-use clap::{Parser, ValueEnum};
+use api_crawler::profile::{ProfileSet, run_profiles};
+use api_crawler::proxy::ProxyConfig;
+use api_crawler::safety::ProbeMethod;
+use api_crawler::sampling::SampleStrategy;
+use api_crawler::schema::save_schemas;
+use api_crawler::sensitive::{BuiltinRule, SensitiveRule};
+use api_crawler::session::LoginStep;
+use api_crawler::tls::ClientIdentity;
+use api_crawler::transport::{FixtureTransport, HarTransport, Transport};
+use api_crawler::xml::XmlSelector;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 use std::process;
 use tracing::{Level, error, info};
-use tracing_subscriber;
 
 #[derive(Parser)]
 #[command(
     name = "api_crawler",
     about = "A tool for crawling REST APIs and mapping their endpoint structure",
-    version = "1.0.0"
+    version = "1.0.0",
+    args_conflicts_with_subcommands = true
 )]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// The starting URL to crawl
-    #[arg(help = "Starting URL for the API crawl")]
-    url: String,
+    #[arg(help = "Starting URL for the API crawl (may be set in --config instead)")]
+    url: Option<String>,
+
+    /// Configuration file with crawler and output settings
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Load settings from a TOML, YAML or JSON file; flags override it"
+    )]
+    config: Option<PathBuf>,
 
     /// Output file path (defaults to stdout summary if not provided)
-    #[arg(short, long, help = "Output file path for JSON results")]
+    #[arg(short, long, help = "Output file path for results")]
     output: Option<PathBuf>,
 
     /// Maximum crawling depth (0 = unlimited)
@@ -49,6 +79,39 @@ struct Args {
     #[arg(long, default_value = "1000", help = "Maximum number of URLs to crawl")]
     max_urls: usize,
 
+    /// Maximum pages followed per paginated collection (0 = unlimited)
+    #[arg(
+        long,
+        default_value = "0",
+        help = "Maximum pages fetched per paginated collection, the first included (0 = unlimited)"
+    )]
+    max_pages: usize,
+
+    /// Instances of each ID-like URL pattern to follow
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Follow only N instances of links differing in an ID-like segment, e.g. /users/{id}"
+    )]
+    sample: Option<usize>,
+
+    /// How sampled instances are chosen
+    #[arg(
+        long,
+        value_enum,
+        default_value = "first",
+        help = "Choose sampled instances in discovery order or at random"
+    )]
+    sample_strategy: SampleStrategyArg,
+
+    /// Seed for random sampling
+    #[arg(
+        long,
+        value_name = "SEED",
+        help = "Seed random sampling for reproducible crawls"
+    )]
+    sample_seed: Option<u64>,
+
     /// Delay between requests in milliseconds
     #[arg(
         short,
@@ -71,9 +134,177 @@ struct Args {
     hierarchical: bool,
 
     /// Allowed domains (can be specified multiple times)
-    #[arg(long, help = "Restrict crawling to these domains")]
+    #[arg(
+        long,
+        value_name = "HOST",
+        help = "Restrict crawling to these hosts (example.com, *.example.com, 10.0.0.5:8080)"
+    )]
     allowed_domain: Vec<String>,
 
+    /// Follow links to any host instead of staying on the start URL's site
+    #[arg(
+        long,
+        conflicts_with = "allowed_domain",
+        help = "Follow links to any host (default: same site as the start URL)"
+    )]
+    any_domain: bool,
+
+    /// Only follow links matching these patterns
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        help = "Only follow links matching this glob ('/api/**') or 'regex:...' pattern"
+    )]
+    include: Vec<String>,
+
+    /// Never follow links matching these patterns
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        help = "Never follow links matching this glob or 'regex:...' pattern"
+    )]
+    exclude: Vec<String>,
+
+    /// Only follow links with these rels
+    #[arg(long, value_name = "REL", help = "Only follow links with this rel")]
+    allow_rel: Vec<String>,
+
+    /// Never follow links with these rels
+    #[arg(
+        long,
+        value_name = "REL",
+        help = "Never follow links with this rel (e.g. logout)"
+    )]
+    deny_rel: Vec<String>,
+
+    /// Only follow links advertising these methods
+    #[arg(
+        long,
+        value_name = "METHOD",
+        help = "Only follow links advertising this method"
+    )]
+    allow_method: Vec<String>,
+
+    /// Never follow links advertising these methods
+    #[arg(
+        long,
+        value_name = "METHOD",
+        help = "Never follow links advertising this method"
+    )]
+    deny_method: Vec<String>,
+
+    /// Refuse every request that isn't GET, HEAD or OPTIONS
+    #[arg(
+        long,
+        help = "Refuse every request other than GET, HEAD and OPTIONS (OAuth2 token requests excepted)"
+    )]
+    read_only: bool,
+
+    /// Probe links advertising unsafe methods instead of skipping them
+    #[arg(
+        long,
+        value_enum,
+        value_name = "METHOD",
+        help = "Probe links advertising unsafe methods with HEAD or OPTIONS instead of skipping them"
+    )]
+    probe_unsafe: Option<ProbeMethodArg>,
+
+    /// Additional rels never to follow
+    #[arg(
+        long,
+        value_name = "REL",
+        help = "Treat this rel as destructive and never follow it"
+    )]
+    destructive_rel: Vec<String>,
+
+    /// Probe discovered endpoints with OPTIONS for their supported methods
+    #[arg(
+        long,
+        help = "Send OPTIONS to every discovered endpoint to record its supported methods"
+    )]
+    probe_methods: bool,
+
+    /// Origin to send method probes from as CORS preflights
+    #[arg(
+        long,
+        value_name = "ORIGIN",
+        help = "Send method probes as CORS preflights from this origin (implies --probe-methods)"
+    )]
+    cors_origin: Option<String>,
+
+    /// Accept header sent with every GET
+    #[arg(
+        long,
+        value_name = "MEDIA_TYPES",
+        help = "Accept header sent with every GET (default prefers HAL and JSON)"
+    )]
+    accept: Option<String>,
+
+    /// Media types to probe every endpoint for
+    #[arg(
+        long,
+        value_name = "MEDIA_TYPE",
+        num_args = 0..=1,
+        require_equals = true,
+        value_delimiter = ',',
+        help = "Request these media types from every endpoint and record which are served (--probe-representations=text/csv,...; default: HAL, JSON:API, XML, CSV)"
+    )]
+    probe_representations: Option<Vec<String>>,
+
+    /// Selectors for links in XML responses
+    #[arg(
+        long,
+        value_name = "SELECTOR",
+        help = "Extract links from XML responses with this selector (e.g. '//resource/@uri')"
+    )]
+    xml_selector: Vec<String>,
+
+    /// Introspect detected GraphQL endpoints
+    #[arg(
+        long,
+        help = "Send the introspection query to detected GraphQL endpoints and record their schemas"
+    )]
+    graphql_introspect: bool,
+
+    /// Infer JSON Schemas from response bodies
+    #[arg(
+        long,
+        help = "Infer a JSON Schema for each route from the JSON bodies it returns"
+    )]
+    infer_schemas: bool,
+
+    /// Directory the inferred schemas are written to
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Write one JSON Schema file per route to this directory (implies --infer-schemas)"
+    )]
+    schema_dir: Option<PathBuf>,
+
+    /// Scan response bodies for PII and secrets
+    #[arg(
+        long,
+        help = "Flag emails, phone and card numbers, JWTs, AWS keys and private keys in JSON responses"
+    )]
+    scan_sensitive: bool,
+
+    /// Custom sensitive data rules
+    #[arg(
+        long,
+        value_name = "NAME=REGEX",
+        help = "Also flag response values matching this regex (implies --scan-sensitive)"
+    )]
+    sensitive_rule: Vec<String>,
+
+    /// Built-in sensitive data rules to skip
+    #[arg(
+        long,
+        value_enum,
+        value_name = "RULE",
+        help = "Don't apply this built-in sensitive data rule"
+    )]
+    disable_sensitive_rule: Vec<BuiltinRuleArg>,
+
     /// Custom headers in key:value format
     #[arg(long, help = "Custom headers (format: key:value)")]
     header: Vec<String>,
@@ -97,6 +328,335 @@ struct Args {
     /// Don't follow redirects
     #[arg(long, help = "Don't follow HTTP redirects")]
     no_redirects: bool,
+
+    /// Export discovered endpoints as a Postman v2.1 collection
+    #[arg(long, help = "Write a Postman v2.1 collection to this path")]
+    postman: Option<PathBuf>,
+
+    /// Export discovered endpoints as an .http request file
+    #[arg(long, help = "Write an .http request file to this path")]
+    http_file: Option<PathBuf>,
+
+    /// Include every fetched URL's outcome in the output
+    #[arg(
+        long,
+        help = "Include the status, timing and error of every fetched URL in the output"
+    )]
+    visits: bool,
+
+    /// Record all crawl traffic as a HAR file (defaults to the output path with a .har extension)
+    #[arg(
+        long,
+        num_args = 0..=1,
+        value_name = "PATH",
+        help = "Record crawl traffic to a HAR 1.2 file"
+    )]
+    har: Option<Option<PathBuf>>,
+
+    /// Replay responses from a HAR file instead of using the network
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with = "replay_dir",
+        help = "Crawl offline from a HAR recording"
+    )]
+    replay_har: Option<PathBuf>,
+
+    /// Serve responses from a fixture directory instead of using the network
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Crawl offline from a directory of recorded responses"
+    )]
+    replay_dir: Option<PathBuf>,
+
+    /// HTTP Basic credentials
+    #[arg(long, value_name = "USER:PASSWORD", help = "HTTP Basic authentication")]
+    basic_auth: Option<String>,
+
+    /// Environment variable holding a bearer token
+    #[arg(
+        long,
+        value_name = "VAR",
+        help = "Read a bearer token from this environment variable"
+    )]
+    bearer_token_env: Option<String>,
+
+    /// File holding a bearer token
+    #[arg(long, value_name = "PATH", help = "Read a bearer token from this file")]
+    bearer_token_file: Option<PathBuf>,
+
+    /// Header name to send the API key in
+    #[arg(
+        long,
+        value_name = "NAME",
+        requires = "api_key_env",
+        help = "Send the API key in this header"
+    )]
+    api_key_header: Option<String>,
+
+    /// Query parameter name to send the API key in
+    #[arg(
+        long,
+        value_name = "NAME",
+        requires = "api_key_env",
+        help = "Send the API key in this query parameter"
+    )]
+    api_key_query: Option<String>,
+
+    /// Environment variable holding the API key
+    #[arg(
+        long,
+        value_name = "VAR",
+        help = "Read the API key from this environment variable"
+    )]
+    api_key_env: Option<String>,
+
+    /// OAuth2 token endpoint for the client-credentials grant
+    #[arg(long, value_name = "URL", requires_all = ["oauth2_client_id", "oauth2_client_secret_env"], help = "OAuth2 client-credentials token URL")]
+    oauth2_token_url: Option<String>,
+
+    /// OAuth2 client identifier
+    #[arg(long, value_name = "ID", help = "OAuth2 client ID")]
+    oauth2_client_id: Option<String>,
+
+    /// Environment variable holding the OAuth2 client secret
+    #[arg(
+        long,
+        value_name = "VAR",
+        help = "Read the OAuth2 client secret from this environment variable"
+    )]
+    oauth2_client_secret_env: Option<String>,
+
+    /// OAuth2 scopes (can be specified multiple times)
+    #[arg(long, value_name = "SCOPE", help = "OAuth2 scope to request")]
+    oauth2_scope: Vec<String>,
+
+    /// Headers sent only to hosts matching a pattern
+    #[arg(
+        long,
+        value_name = "HOSTS=KEY:VALUE",
+        help = "Header sent only to matching hosts (e.g. '*.internal.example.com=X-Tenant:acme')"
+    )]
+    scoped_header: Vec<String>,
+
+    /// Bearer tokens for hosts matching a pattern, read from environment variables
+    #[arg(
+        long,
+        value_name = "HOSTS=VAR",
+        help = "Bearer token for matching hosts, read from this environment variable"
+    )]
+    scoped_bearer_env: Vec<String>,
+
+    /// PEM client certificate for mutual TLS
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "client_key",
+        conflicts_with = "client_p12",
+        help = "PEM client certificate for mutual TLS"
+    )]
+    client_cert: Option<PathBuf>,
+
+    /// PKCS#8 PEM private key for the client certificate
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "client_cert",
+        help = "PEM private key for --client-cert"
+    )]
+    client_key: Option<PathBuf>,
+
+    /// PKCS#12 client certificate archive for mutual TLS
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "client_p12_password_env",
+        help = "PKCS#12 client certificate for mutual TLS"
+    )]
+    client_p12: Option<PathBuf>,
+
+    /// Environment variable holding the PKCS#12 password
+    #[arg(
+        long,
+        value_name = "VAR",
+        requires = "client_p12",
+        help = "Read the --client-p12 password from this environment variable"
+    )]
+    client_p12_password_env: Option<String>,
+
+    /// Extra trusted root certificates (can be specified multiple times)
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Trust the CA certificates in this PEM file"
+    )]
+    ca_cert: Vec<PathBuf>,
+
+    /// Disable TLS certificate verification
+    #[arg(
+        long,
+        help = "Disable TLS certificate verification (test environments only!)"
+    )]
+    insecure: bool,
+
+    /// Proxy for all requests
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "Proxy URL (http://, https://, socks5:// or socks5h://)"
+    )]
+    proxy: Option<String>,
+
+    /// Proxy username
+    #[arg(long, value_name = "USER", requires = "proxy", help = "Proxy username")]
+    proxy_user: Option<String>,
+
+    /// Environment variable holding the proxy password
+    #[arg(
+        long,
+        value_name = "VAR",
+        requires = "proxy_user",
+        help = "Read the proxy password from this environment variable"
+    )]
+    proxy_password_env: Option<String>,
+
+    /// Hosts that bypass the proxy (can be specified multiple times)
+    #[arg(
+        long,
+        value_name = "HOSTS",
+        requires = "proxy",
+        help = "Comma-separated hosts, domains or CIDRs that bypass the proxy"
+    )]
+    no_proxy: Vec<String>,
+
+    /// Ignore HTTP_PROXY/HTTPS_PROXY/NO_PROXY environment variables
+    #[arg(long, help = "Ignore proxy environment variables")]
+    no_env_proxy: bool,
+
+    /// Keep cookies set by the server across requests
+    #[arg(long, help = "Keep cookies set by the server across requests")]
+    cookies: bool,
+
+    /// File to load cookies from and save them to after the crawl
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Persist cookies in this file across crawls"
+    )]
+    cookie_file: Option<PathBuf>,
+
+    /// Login endpoint requested before crawling
+    #[arg(long, value_name = "URL", help = "Log in at this URL before crawling")]
+    login_url: Option<String>,
+
+    /// HTTP method for the login request
+    #[arg(
+        long,
+        default_value = "POST",
+        requires = "login_url",
+        help = "Login request method"
+    )]
+    login_method: String,
+
+    /// Login form fields with literal values
+    #[arg(
+        long,
+        value_name = "NAME=VALUE",
+        requires = "login_url",
+        help = "Login form field"
+    )]
+    login_field: Vec<String>,
+
+    /// Login form fields read from environment variables
+    #[arg(
+        long,
+        value_name = "NAME=VAR",
+        requires = "login_url",
+        help = "Login form field read from an environment variable"
+    )]
+    login_field_env: Vec<String>,
+
+    /// File holding a JSON login body
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "login_url",
+        conflicts_with_all = ["login_field", "login_field_env"],
+        help = "Send this file's JSON as the login body"
+    )]
+    login_json_file: Option<PathBuf>,
+
+    /// Status the login must return
+    #[arg(
+        long,
+        value_name = "STATUS",
+        requires = "login_url",
+        help = "Expected login status (default: any 2xx)"
+    )]
+    login_status: Option<u16>,
+
+    /// Cookie holding the login session
+    #[arg(
+        long,
+        value_name = "NAME",
+        requires = "login_url",
+        help = "Only reuse saved cookies for the login when this session cookie is present"
+    )]
+    login_session_cookie: Option<String>,
+
+    /// Maximum response body bytes stored per HAR entry (0 = unlimited)
+    #[arg(
+        long,
+        default_value = "0",
+        help = "Max body bytes per HAR entry (0 = unlimited)"
+    )]
+    har_body_limit: usize,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Crawl named targets from a profiles file
+    Run(RunArgs),
+}
+
+#[derive(clap::Args)]
+struct RunArgs {
+    /// Profiles file
+    #[arg(
+        short,
+        long,
+        value_name = "PATH",
+        default_value = "crawl-profiles.toml",
+        help = "TOML, YAML or JSON file listing the targets"
+    )]
+    file: PathBuf,
+
+    /// Profiles to crawl (can be specified multiple times)
+    #[arg(
+        long,
+        value_name = "NAME",
+        required_unless_present = "all",
+        help = "Crawl this profile"
+    )]
+    profile: Vec<String>,
+
+    /// Crawl every profile in the file
+    #[arg(long, conflicts_with = "profile", help = "Crawl every profile")]
+    all: bool,
+
+    /// Number of profiles crawled at the same time
+    #[arg(
+        long,
+        value_name = "N",
+        default_value = "1",
+        help = "Crawl up to N profiles in parallel"
+    )]
+    parallel: usize,
+
+    /// Verbose logging
+    #[arg(short, long, help = "Enable verbose logging")]
+    verbose: bool,
 }
 
 #[derive(ValueEnum, Clone)]
@@ -109,6 +669,78 @@ enum OutputFormatArg {
     Hierarchical,
     /// Compact tree structure with all endpoint info in one block
     Tree,
+    /// CSV with one row per endpoint
+    Csv,
+    /// Markdown report with summary table and per-parent sections
+    Markdown,
+    /// JUnit XML report with one testcase per visited URL
+    Junit,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum ProbeMethodArg {
+    /// Probe with HEAD
+    Head,
+    /// Probe with OPTIONS
+    Options,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum BuiltinRuleArg {
+    /// Email addresses
+    Email,
+    /// Payment card numbers passing the Luhn check
+    CreditCard,
+    /// Phone numbers
+    Phone,
+    /// JSON Web Tokens
+    Jwt,
+    /// AWS access key IDs
+    AwsAccessKey,
+    /// AWS-style secret keys
+    AwsSecretKey,
+    /// PEM private keys
+    PrivateKey,
+}
+
+impl From<BuiltinRuleArg> for BuiltinRule {
+    fn from(arg: BuiltinRuleArg) -> Self {
+        match arg {
+            BuiltinRuleArg::Email => BuiltinRule::Email,
+            BuiltinRuleArg::CreditCard => BuiltinRule::CreditCard,
+            BuiltinRuleArg::Phone => BuiltinRule::Phone,
+            BuiltinRuleArg::Jwt => BuiltinRule::Jwt,
+            BuiltinRuleArg::AwsAccessKey => BuiltinRule::AwsAccessKey,
+            BuiltinRuleArg::AwsSecretKey => BuiltinRule::AwsSecretKey,
+            BuiltinRuleArg::PrivateKey => BuiltinRule::PrivateKey,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum SampleStrategyArg {
+    /// The first instances discovered
+    First,
+    /// Instances chosen at random
+    Random,
+}
+
+impl From<SampleStrategyArg> for SampleStrategy {
+    fn from(arg: SampleStrategyArg) -> Self {
+        match arg {
+            SampleStrategyArg::First => SampleStrategy::First,
+            SampleStrategyArg::Random => SampleStrategy::Random,
+        }
+    }
+}
+
+impl From<ProbeMethodArg> for ProbeMethod {
+    fn from(arg: ProbeMethodArg) -> Self {
+        match arg {
+            ProbeMethodArg::Head => ProbeMethod::Head,
+            ProbeMethodArg::Options => ProbeMethod::Options,
+        }
+    }
 }
 
 impl From<OutputFormatArg> for OutputFormat {
@@ -118,8 +750,416 @@ impl From<OutputFormatArg> for OutputFormat {
             OutputFormatArg::Compact => OutputFormat::CompactJson,
             OutputFormatArg::Hierarchical => OutputFormat::Hierarchical,
             OutputFormatArg::Tree => OutputFormat::Tree,
+            OutputFormatArg::Csv => OutputFormat::Csv,
+            OutputFormatArg::Markdown => OutputFormat::Markdown,
+            OutputFormatArg::Junit => OutputFormat::JUnit,
+        }
+    }
+}
+
+/// Build the authentication strategy from CLI flags, allowing at most one
+fn auth_from_args(args: &Args) -> Result<Option<Auth>> {
+    let mut strategies = Vec::new();
+
+    if let Some(basic) = &args.basic_auth {
+        let (username, password) = basic
+            .split_once(':')
+            .ok_or_else(|| CrawlerError::config("Expected --basic-auth in USER:PASSWORD format"))?;
+        strategies.push(Auth::Basic {
+            username: username.to_string(),
+            password: SecretSource::Value(password.to_string()),
+        });
+    }
+    if let Some(var) = &args.bearer_token_env {
+        strategies.push(Auth::Bearer(SecretSource::Env(var.clone())));
+    }
+    if let Some(path) = &args.bearer_token_file {
+        strategies.push(Auth::Bearer(SecretSource::File(path.clone())));
+    }
+    if let Some(var) = &args.api_key_env {
+        let (name, location) = match (&args.api_key_header, &args.api_key_query) {
+            (Some(name), None) => (name.clone(), ApiKeyLocation::Header),
+            (None, Some(name)) => (name.clone(), ApiKeyLocation::Query),
+            _ => {
+                return Err(CrawlerError::config(
+                    "--api-key-env requires exactly one of --api-key-header or --api-key-query",
+                ));
+            }
+        };
+        strategies.push(Auth::ApiKey {
+            name,
+            value: SecretSource::Env(var.clone()),
+            location,
+        });
+    }
+    if let (Some(token_url), Some(client_id), Some(secret_env)) = (
+        &args.oauth2_token_url,
+        &args.oauth2_client_id,
+        &args.oauth2_client_secret_env,
+    ) {
+        strategies.push(Auth::OAuth2(OAuth2ClientCredentials {
+            token_url: token_url.clone(),
+            client_id: client_id.clone(),
+            client_secret: SecretSource::Env(secret_env.clone()),
+            scopes: args.oauth2_scope.clone(),
+        }));
+    }
+
+    if strategies.len() > 1 {
+        return Err(CrawlerError::config(
+            "Only one authentication method can be used at a time",
+        ));
+    }
+    Ok(strategies.pop())
+}
+
+/// Build the pre-crawl login step from CLI flags
+fn login_from_args(args: &Args) -> Result<Option<LoginStep>> {
+    let Some(url) = &args.login_url else {
+        return Ok(None);
+    };
+
+    let mut login = LoginStep::new(url.clone()).method(args.login_method.clone());
+    let literal = args.login_field.iter().map(|f| (f, false));
+    let from_env = args.login_field_env.iter().map(|f| (f, true));
+    for (field, is_env) in literal.chain(from_env) {
+        let (name, value) = field.split_once('=').ok_or_else(|| {
+            CrawlerError::config(format!(
+                "Invalid login field '{}'. Expected 'name=value'",
+                field
+            ))
+        })?;
+        let value = if is_env {
+            SecretSource::Env(value.to_string())
+        } else {
+            SecretSource::Value(value.to_string())
+        };
+        login = login.form_field(name, value);
+    }
+    if let Some(path) = &args.login_json_file {
+        login = login.json_body(SecretSource::File(path.clone()));
+    }
+    if let Some(status) = args.login_status {
+        login = login.expect_status(status);
+    }
+    if let Some(name) = &args.login_session_cookie {
+        login = login.session_cookie(name.clone());
+    }
+
+    Ok(Some(login))
+}
+
+/// Build per-host credential scopes from CLI flags, one scope per distinct pattern
+fn credential_scopes_from_args(args: &Args) -> Result<Vec<CredentialScope>> {
+    let mut scopes: Vec<CredentialScope> = Vec::new();
+    let mut scope_for = |pattern: &str| -> Result<usize> {
+        let hosts = HostPattern::parse(pattern)?;
+        match scopes.iter().position(|scope| scope.hosts == hosts) {
+            Some(index) => Ok(index),
+            None => {
+                scopes.push(CredentialScope::new(pattern)?);
+                Ok(scopes.len() - 1)
+            }
+        }
+    };
+
+    let mut headers = Vec::new();
+    for scoped in &args.scoped_header {
+        let (pattern, header) = scoped
+            .split_once('=')
+            .and_then(|(pattern, header)| Some((pattern, header.split_once(':')?)))
+            .ok_or_else(|| {
+                CrawlerError::config(format!(
+                    "Invalid scoped header '{}'. Expected 'hosts=key:value'",
+                    scoped
+                ))
+            })?;
+        headers.push((scope_for(pattern)?, header));
+    }
+
+    let mut tokens = Vec::new();
+    for scoped in &args.scoped_bearer_env {
+        let (pattern, var) = scoped.split_once('=').ok_or_else(|| {
+            CrawlerError::config(format!(
+                "Invalid scoped bearer token '{}'. Expected 'hosts=VAR'",
+                scoped
+            ))
+        })?;
+        tokens.push((scope_for(pattern)?, pattern, var));
+    }
+
+    for (index, (key, value)) in headers {
+        scopes[index]
+            .headers
+            .insert(key.trim().to_string(), value.trim().to_string());
+    }
+    for (index, pattern, var) in tokens {
+        if scopes[index].auth.is_some() {
+            return Err(CrawlerError::config(format!(
+                "Only one bearer token can be scoped to {}",
+                pattern
+            )));
+        }
+        scopes[index].auth = Some(Auth::Bearer(SecretSource::Env(var.to_string())));
+    }
+
+    Ok(scopes)
+}
+
+/// Whether a flag was given explicitly rather than taken from its default
+fn from_cli(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+/// Default HAR path: next to the output file, or `api_crawl.har`
+fn default_har_path(args: &Args) -> PathBuf {
+    args.output
+        .as_ref()
+        .map(|output| output.with_extension("har"))
+        .unwrap_or_else(|| PathBuf::from("api_crawl.har"))
+}
+
+/// Layer command-line flags over a base configuration, usually loaded from a file
+fn crawler_config_from_args(
+    mut config: CrawlerConfig,
+    args: &Args,
+    matches: &ArgMatches,
+) -> Result<CrawlerConfig> {
+    if from_cli(matches, "max_depth") {
+        config.max_depth = args.max_depth;
+    }
+    if from_cli(matches, "concurrency") {
+        config.max_concurrent_requests = args.concurrency;
+    }
+    if from_cli(matches, "timeout") {
+        config.timeout_seconds = args.timeout;
+    }
+    if from_cli(matches, "max_urls") {
+        config.max_urls = args.max_urls;
+    }
+    if from_cli(matches, "max_pages") {
+        config = config.max_pages(args.max_pages);
+    }
+    if let Some(sample_size) = args.sample {
+        config = config.sample(sample_size, args.sample_strategy.into());
+    } else if from_cli(matches, "sample_strategy") {
+        config.sampling.strategy = args.sample_strategy.into();
+    }
+    if let Some(seed) = args.sample_seed {
+        config = config.sample_seed(seed);
+    }
+    if from_cli(matches, "delay") {
+        config.delay_ms = args.delay;
+    }
+    if from_cli(matches, "user_agent") {
+        config.user_agent = args.user_agent.clone();
+    }
+    if from_cli(matches, "har_body_limit") {
+        config.har_body_limit = args.har_body_limit;
+    }
+    if args.no_redirects {
+        config.follow_redirects = false;
+    }
+    if args.har.is_some() {
+        config.record_har = true;
+    }
+    if args.visits {
+        config.record_visits = true;
+    }
+
+    for header_str in &args.header {
+        let (key, value) = header_str.split_once(':').ok_or_else(|| {
+            CrawlerError::config(format!(
+                "Invalid header format '{}'. Expected 'key:value'",
+                header_str
+            ))
+        })?;
+        config
+            .headers
+            .insert(key.trim().to_string(), value.trim().to_string());
+    }
+
+    for domain in &args.allowed_domain {
+        config = config.allow_domain(domain.clone());
+    }
+    if args.any_domain {
+        config = config.any_domain();
+    }
+
+    let filters = &mut config.filters;
+    for pattern in &args.include {
+        filters.include.push(UrlPattern::parse(pattern)?);
+    }
+    for pattern in &args.exclude {
+        filters.exclude.push(UrlPattern::parse(pattern)?);
+    }
+    filters.allow_rels.extend(args.allow_rel.iter().cloned());
+    filters.deny_rels.extend(args.deny_rel.iter().cloned());
+    filters
+        .allow_methods
+        .extend(args.allow_method.iter().cloned());
+    filters
+        .deny_methods
+        .extend(args.deny_method.iter().cloned());
+
+    if args.read_only {
+        config = config.read_only();
+    }
+    if let Some(probe) = args.probe_unsafe {
+        config = config.probe_unsafe(probe.into());
+    }
+    config
+        .safety
+        .destructive_rels
+        .extend(args.destructive_rel.iter().cloned());
+
+    if args.probe_methods {
+        config = config.probe_methods();
+    }
+    if let Some(origin) = &args.cors_origin {
+        config = config.cors_origin(origin.clone());
+    }
+
+    if let Some(accept) = &args.accept {
+        config = config.accept(accept.clone());
+    }
+    if let Some(media_types) = &args.probe_representations {
+        let media_types: Vec<&str> = if media_types.is_empty() {
+            PROBE_MEDIA_TYPES.to_vec()
+        } else {
+            media_types.iter().map(String::as_str).collect()
+        };
+        let media_types = media_types
+            .into_iter()
+            .map(|media_type| {
+                MediaType::parse(media_type).ok_or_else(|| {
+                    CrawlerError::config(format!("Invalid media type '{}'", media_type))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        config = config.probe_representations(media_types);
+    }
+    for selector in &args.xml_selector {
+        config = config.xml_selector(XmlSelector::parse(selector)?);
+    }
+    if args.graphql_introspect {
+        config = config.introspect_graphql();
+    }
+    if args.infer_schemas || args.schema_dir.is_some() {
+        config = config.infer_schemas();
+    }
+    if args.scan_sensitive || !args.sensitive_rule.is_empty() {
+        config = config.scan_sensitive_data();
+    }
+    for rule in &args.sensitive_rule {
+        config = config.sensitive_rule(SensitiveRule::parse(rule)?);
+    }
+    for rule in &args.disable_sensitive_rule {
+        config = config.disable_sensitive_rule((*rule).into());
+    }
+
+    if let Some(auth) = auth_from_args(args)? {
+        config = config.auth(auth);
+    }
+
+    match (&args.client_cert, &args.client_key, &args.client_p12) {
+        (Some(cert), Some(key), _) => {
+            config.tls.client_identity = Some(ClientIdentity::Pem {
+                cert: cert.clone(),
+                key: key.clone(),
+            });
         }
+        (_, _, Some(path)) => {
+            config.tls.client_identity = Some(ClientIdentity::Pkcs12 {
+                path: path.clone(),
+                password: SecretSource::Env(
+                    args.client_p12_password_env.clone().unwrap_or_default(),
+                ),
+            });
+        }
+        _ => {}
+    }
+    config
+        .tls
+        .ca_certificates
+        .extend(args.ca_cert.iter().cloned());
+    config.tls.insecure |= args.insecure;
+
+    if let Some(url) = &args.proxy {
+        config.proxy = ProxyConfig {
+            username: args.proxy_user.clone(),
+            password: args.proxy_password_env.clone().map(SecretSource::Env),
+            no_proxy: args.no_proxy.clone(),
+            ..ProxyConfig::new(url.clone())
+        };
+    }
+    if args.no_env_proxy {
+        config.proxy.use_env = false;
     }
+
+    if args.cookies {
+        config = config.enable_cookies();
+    }
+    if let Some(path) = &args.cookie_file {
+        config = config.cookie_file(path.clone());
+    }
+    if let Some(login) = login_from_args(args)? {
+        config = config.login(login);
+    }
+
+    for scope in credential_scopes_from_args(args)? {
+        config = config.scope_credentials(scope);
+    }
+
+    Ok(config)
+}
+
+/// Layer output flags over a base output configuration
+fn output_config_from_args(
+    mut output: OutputConfig,
+    args: &Args,
+    matches: &ArgMatches,
+) -> OutputConfig {
+    if from_cli(matches, "format") {
+        output.format = args.format.clone().into();
+    }
+    output.hierarchical |= args.hierarchical;
+    output
+}
+
+/// Crawl the selected profiles, print a combined summary and exit
+async fn run_profiles_command(run: &RunArgs) -> ! {
+    let profiles = match ProfileSet::load(&run.file) {
+        Ok(profiles) => profiles,
+        Err(e) => {
+            error!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    let selected = if run.all {
+        profiles.iter().cloned().collect()
+    } else {
+        match profiles.select(&run.profile) {
+            Ok(selected) => selected,
+            Err(e) => {
+                error!("{}", e);
+                process::exit(1);
+            }
+        }
+    };
+
+    let outcomes = run_profiles(selected, run.parallel).await;
+    print_profiles_summary(&outcomes);
+
+    let exit_code = if outcomes.iter().any(|o| o.result.is_err()) {
+        1
+    } else if outcomes.iter().all(|o| o.is_success()) {
+        0
+    } else {
+        2
+    };
+    process::exit(exit_code);
 }
 
 #[tokio::main]
@@ -160,57 +1200,95 @@ async fn main() {
         eprintln!("  • This error message");
     }));
 
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
     // Initialize logging
-    let log_level = if args.verbose {
-        Level::DEBUG
-    } else {
-        Level::INFO
+    let verbose = match &args.command {
+        Some(Command::Run(run)) => run.verbose,
+        None => args.verbose,
     };
+    let log_level = if verbose { Level::DEBUG } else { Level::INFO };
     tracing_subscriber::fmt()
         .with_max_level(log_level)
         .with_target(false)
         .init();
 
+    if let Some(Command::Run(run)) = &args.command {
+        run_profiles_command(run).await;
+    }
+
+    // Load the configuration file, if any; command-line flags take precedence
+    let file = match &args.config {
+        Some(path) => match ConfigFile::load(path) {
+            Ok(file) => file,
+            Err(e) => {
+                error!("{}", e);
+                process::exit(1);
+            }
+        },
+        None => ConfigFile::default(),
+    };
+
+    let Some(start_url) = args.url.clone().or(file.url) else {
+        error!("No starting URL given on the command line or in the configuration file");
+        process::exit(1);
+    };
+
     // Validate URL
-    if let Err(e) = url::Url::parse(&args.url) {
-        error!("Invalid URL '{}': {}", args.url, e);
+    if let Err(e) = url::Url::parse(&start_url) {
+        error!("Invalid URL '{}': {}", start_url, e);
         process::exit(1);
     }
 
-    // Parse custom headers
-    let mut headers = std::collections::HashMap::new();
-    for header_str in &args.header {
-        if let Some((key, value)) = header_str.split_once(':') {
-            headers.insert(key.trim().to_string(), value.trim().to_string());
-        } else {
-            error!(
-                "Invalid header format '{}'. Expected 'key:value'",
-                header_str
-            );
+    let mut config = match crawler_config_from_args(file.crawler, &args, &matches) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
             process::exit(1);
         }
-    }
+    };
+    let output_config = output_config_from_args(file.output, &args, &matches);
 
-    // Build crawler configuration
-    let mut config = CrawlerConfig::new()
-        .max_depth(args.max_depth)
-        .max_concurrent_requests(args.concurrency)
-        .timeout_seconds(args.timeout);
+    // JUnit reports are built from the visited URLs
+    if matches!(output_config.format, OutputFormat::JUnit) {
+        config.record_visits = true;
+    }
 
-    config.max_urls = args.max_urls;
-    config.delay_ms = args.delay;
-    config.user_agent = args.user_agent;
-    config.headers = headers;
-    config.follow_redirects = !args.no_redirects;
+    // Resolve the HAR path next to the normal output unless one was given
+    let har_path = match &args.har {
+        Some(Some(path)) => Some(path.clone()),
+        Some(None) => Some(default_har_path(&args)),
+        None => config.record_har.then(|| default_har_path(&args)),
+    };
 
-    for domain in args.allowed_domain {
-        config = config.allow_domain(domain);
-    }
+    // Pick a transport: recorded responses for offline crawls, otherwise the network
+    let transport: Option<Box<dyn Transport>> = if let Some(har) = &args.replay_har {
+        match HarTransport::from_file(har) {
+            Ok(transport) => Some(Box::new(transport)),
+            Err(e) => {
+                error!("Failed to load HAR {}: {}", har.display(), e);
+                process::exit(1);
+            }
+        }
+    } else if let Some(dir) = &args.replay_dir {
+        match FixtureTransport::new(dir) {
+            Ok(transport) => Some(Box::new(transport)),
+            Err(e) => {
+                error!("Failed to open fixture directory: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
 
     // Create crawler
-    let mut crawler = match ApiCrawler::new(config) {
+    let crawler = match transport {
+        Some(transport) => ApiCrawler::with_transport(config, transport),
+        None => ApiCrawler::new(config),
+    };
+    let mut crawler = match crawler {
         Ok(crawler) => crawler,
         Err(e) => {
             error!("Failed to create crawler: {}", e);
@@ -218,7 +1296,7 @@ async fn main() {
         }
     };
 
-    info!("Starting API crawl from: {}", args.url);
+    info!("Starting API crawl from: {}", start_url);
 
     // Apply debug mode settings
     if args.debug {
@@ -230,7 +1308,7 @@ async fn main() {
     }
 
     // Start crawling with better error handling
-    let result = match crawler.crawl(&args.url).await {
+    let result = match crawler.crawl(&start_url).await {
         Ok(result) => result,
         Err(e) => {
             error!("Crawling failed: {}", e);
@@ -266,14 +1344,17 @@ async fn main() {
         }
     };
 
+    // Write recorded traffic first so it is kept even if saving other output fails
+    if let Some(har_path) = &har_path
+        && let Err(e) = crawler.har().save(har_path)
+    {
+        error!("Failed to save HAR to {}: {}", har_path.display(), e);
+        process::exit(1);
+    }
+
     // Output results with better error handling
     if let Some(output_path) = args.output {
-        let mut output_config = OutputConfig {
-            format: args.format.into(),
-            include_stats: true,
-            include_config: true,
-            hierarchical: args.hierarchical,
-        };
+        let mut output_config = output_config.clone();
 
         // In debug mode, fall back to standard format if tree format fails
         if args.debug && matches!(output_config.format, OutputFormat::Tree) {
@@ -327,6 +1408,40 @@ async fn main() {
         info!("Results saved to: {}", output_path.display());
     }
 
+    // Export request collections for API clients
+    if let Some(postman_path) = &args.postman
+        && let Err(e) = save_postman_collection(&result, crawler.config(), postman_path)
+    {
+        error!(
+            "Failed to save Postman collection to {}: {}",
+            postman_path.display(),
+            e
+        );
+        process::exit(1);
+    }
+
+    if let Some(http_path) = &args.http_file
+        && let Err(e) = save_http_requests(&result, &crawler.config().headers, http_path)
+    {
+        error!(
+            "Failed to save HTTP request file to {}: {}",
+            http_path.display(),
+            e
+        );
+        process::exit(1);
+    }
+
+    if let Some(schema_dir) = &args.schema_dir
+        && let Err(e) = save_schemas(&result.schemas, schema_dir)
+    {
+        error!(
+            "Failed to save JSON schemas to {}: {}",
+            schema_dir.display(),
+            e
+        );
+        process::exit(1);
+    }
+
     // Always print summary to stdout
     print_summary(&result);
 
@@ -336,7 +1451,7 @@ async fn main() {
     }
 
     // Print hierarchical structure if using hierarchical format
-    if args.hierarchical {
+    if output_config.hierarchical {
         print_hierarchical_summary(&result);
     }
 
@@ -359,11 +1474,17 @@ mod tests {
         let compact = OutputFormatArg::Compact;
         let hierarchical = OutputFormatArg::Hierarchical;
         let tree = OutputFormatArg::Tree;
+        let csv = OutputFormatArg::Csv;
+        let markdown = OutputFormatArg::Markdown;
+        let junit = OutputFormatArg::Junit;
 
         matches!(OutputFormat::from(pretty), OutputFormat::PrettyJson);
         matches!(OutputFormat::from(compact), OutputFormat::CompactJson);
         matches!(OutputFormat::from(hierarchical), OutputFormat::Hierarchical);
         matches!(OutputFormat::from(tree), OutputFormat::Tree);
+        matches!(OutputFormat::from(csv), OutputFormat::Csv);
+        matches!(OutputFormat::from(markdown), OutputFormat::Markdown);
+        matches!(OutputFormat::from(junit), OutputFormat::JUnit);
     }
 
     #[test]
@@ -376,4 +1497,74 @@ mod tests {
             panic!("Failed to parse header");
         }
     }
+
+    #[test]
+    fn test_credential_scopes_from_args() {
+        let args = Args::parse_from([
+            "api_crawler",
+            "http://api.internal.example.com/",
+            "--scoped-header",
+            "*.internal.example.com=X-Tenant: acme",
+            "--scoped-bearer-env",
+            "*.internal.example.com=INTERNAL_TOKEN",
+            "--scoped-bearer-env",
+            "partner.example.org=PARTNER_TOKEN",
+        ]);
+
+        let scopes = credential_scopes_from_args(&args).unwrap();
+        assert_eq!(scopes.len(), 2);
+        assert_eq!(scopes[0].hosts.to_string(), "*.internal.example.com");
+        assert_eq!(scopes[0].headers["X-Tenant"], "acme");
+        assert_eq!(
+            scopes[1].auth,
+            Some(Auth::Bearer(SecretSource::Env("PARTNER_TOKEN".to_string())))
+        );
+
+        let invalid = Args::parse_from([
+            "api_crawler",
+            "http://example.com/",
+            "--scoped-header",
+            "X-Tenant:acme",
+        ]);
+        assert!(credential_scopes_from_args(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_flags_override_config_file() {
+        let file = ConfigFile::parse(
+            "[crawler]\nmax_depth = 3\ntimeout_seconds = 5\nheaders = { Accept = \"application/hal+json\" }\n\n[output]\nformat = \"csv\"\n",
+            api_crawler::config::ConfigFormat::Toml,
+        )
+        .unwrap();
+
+        let argv = ["api_crawler", "--max-depth", "7", "--header", "X-Trace: 1"];
+        let matches = Args::command().get_matches_from(argv);
+        let args = Args::from_arg_matches(&matches).unwrap();
+        assert!(args.url.is_none());
+
+        let config = crawler_config_from_args(file.crawler, &args, &matches).unwrap();
+        assert_eq!(config.max_depth, 7);
+        // Flags left at their defaults don't clobber file values
+        assert_eq!(config.timeout_seconds, 5);
+        assert_eq!(config.headers.len(), 2);
+
+        let output = output_config_from_args(file.output, &args, &matches);
+        assert!(matches!(output.format, OutputFormat::Csv));
+    }
+
+    #[test]
+    fn test_run_subcommand() {
+        let args = Args::parse_from(["api_crawler", "run", "--all", "--parallel", "4"]);
+        let Some(Command::Run(run)) = args.command else {
+            panic!("expected the run subcommand");
+        };
+        assert!(run.all);
+        assert_eq!(run.parallel, 4);
+        assert_eq!(run.file, PathBuf::from("crawl-profiles.toml"));
+
+        assert!(Args::try_parse_from(["api_crawler", "run"]).is_err());
+        assert!(
+            Args::try_parse_from(["api_crawler", "run", "--all", "--profile", "billing"]).is_err()
+        );
+    }
 }
@@ -0,0 +1,284 @@
+//! Media types and content negotiation
+//!
+//! Servers label JSON in many ways: `application/json`, HAL, JSON:API,
+//! `application/problem+json` and vendor types with a `+json` suffix, often with
+//! oddly cased or quoted parameters. [`MediaType`] parses a `Content-Type` value
+//! into its parts so the crawler can recognize all of them.
+
+use encoding_rs::{Encoding, UTF_8};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+
+/// `Accept` header sent by default, preferring hypermedia JSON
+pub const DEFAULT_ACCEPT: &str =
+    "application/hal+json, application/json, application/*+json;q=0.9, */*;q=0.8";
+
+/// Representations probed when none are configured: HAL, JSON:API, XML and CSV
+pub const PROBE_MEDIA_TYPES: &[&str] = &[
+    "application/hal+json",
+    "application/vnd.api+json",
+    "application/xml",
+    "text/csv",
+];
+
+/// A parsed media type such as `application/vnd.api+json; charset=utf-8`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaType {
+    /// Top-level type, lowercased (`application`)
+    pub r#type: String,
+
+    /// Subtype including any structured suffix, lowercased (`vnd.api+json`)
+    pub subtype: String,
+
+    /// Parameters with lowercased names and unquoted values
+    pub params: Vec<(String, String)>,
+}
+
+impl MediaType {
+    /// Parse a media type, tolerating case, whitespace, quoting and stray parameters
+    ///
+    /// Only the first of several comma-separated values is used, as some servers
+    /// send a duplicated `Content-Type` header.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = split_unquoted(value, ',').next()?;
+        let mut parts = split_unquoted(value, ';');
+        let (r#type, subtype) = parts.next()?.trim().split_once('/')?;
+        let (r#type, subtype) = (r#type.trim(), subtype.trim());
+        if !is_token(r#type) || !is_token(subtype) {
+            return None;
+        }
+
+        let params = parts
+            .filter_map(|param| {
+                let (name, value) = param.split_once('=')?;
+                let name = name.trim().to_ascii_lowercase();
+                let value = unquote(value.trim());
+                (!name.is_empty()).then_some((name, value))
+            })
+            .collect();
+
+        Some(Self {
+            r#type: r#type.to_ascii_lowercase(),
+            subtype: subtype.to_ascii_lowercase(),
+            params,
+        })
+    }
+
+    /// `type/subtype` without parameters
+    pub fn essence(&self) -> String {
+        format!("{}/{}", self.r#type, self.subtype)
+    }
+
+    /// Structured syntax suffix, e.g. `json` for `application/problem+json`
+    pub fn suffix(&self) -> Option<&str> {
+        self.subtype.rsplit_once('+').map(|(_, suffix)| suffix)
+    }
+
+    /// A parameter's value, looked up case-insensitively
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Whether the body is JSON: `application/json`, `text/json` or any `+json` type
+    pub fn is_json(&self) -> bool {
+        matches!(
+            (self.r#type.as_str(), self.subtype.as_str()),
+            ("application" | "text", "json")
+        ) || self.suffix() == Some("json")
+    }
+
+    /// Whether the body is XML: `application/xml`, `text/xml` or any `+xml` type
+    pub fn is_xml(&self) -> bool {
+        matches!(
+            (self.r#type.as_str(), self.subtype.as_str()),
+            ("application" | "text", "xml")
+        ) || self.suffix() == Some("xml")
+    }
+
+    /// Whether the body is HTML: `text/html` or `application/xhtml+xml`
+    pub fn is_html(&self) -> bool {
+        matches!(
+            (self.r#type.as_str(), self.subtype.as_str()),
+            ("text", "html") | ("application", "xhtml+xml")
+        )
+    }
+
+    /// Whether a response of this type satisfies a request for `wanted`
+    ///
+    /// `*` matches any type or subtype, and `application/xml` and `text/xml` are
+    /// interchangeable.
+    pub fn satisfies(&self, wanted: &MediaType) -> bool {
+        let type_matches = wanted.r#type == "*" || wanted.r#type == self.r#type;
+        let subtype_matches = wanted.subtype == "*" || wanted.subtype == self.subtype;
+        (type_matches && subtype_matches)
+            || (wanted.subtype == "xml" && self.subtype == "xml" && wanted.is_xml())
+    }
+
+    /// Body bytes as UTF-8, dropping a byte order mark and transcoding any other
+    /// charset (`iso-8859-1`, `windows-1252`, `shift_jis`, ...)
+    ///
+    /// Unknown charsets are passed through unchanged.
+    pub fn utf8_body<'a>(&self, body: &'a [u8]) -> Cow<'a, [u8]> {
+        let body = body.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(body);
+        match self
+            .param("charset")
+            .and_then(|charset| Encoding::for_label(charset.trim().as_bytes()))
+        {
+            Some(encoding) if encoding != UTF_8 && !body.is_ascii() => {
+                match encoding.decode_without_bom_handling(body).0 {
+                    Cow::Owned(decoded) => Cow::Owned(decoded.into_bytes()),
+                    Cow::Borrowed(_) => Cow::Borrowed(body),
+                }
+            }
+            _ => Cow::Borrowed(body),
+        }
+    }
+}
+
+impl fmt::Display for MediaType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.r#type, self.subtype)?;
+        for (name, value) in &self.params {
+            if is_token(value) {
+                write!(f, "; {}={}", name, value)?;
+            } else {
+                let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, "; {}=\"{}\"", name, escaped)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for MediaType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for MediaType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Self::parse(&value)
+            .ok_or_else(|| serde::de::Error::custom(format!("Invalid media type '{}'", value)))
+    }
+}
+
+/// Split on a delimiter outside double-quoted strings
+fn split_unquoted(value: &str, delimiter: char) -> impl Iterator<Item = &str> {
+    let mut quoted = false;
+    let mut escaped = false;
+    value.split(move |c: char| {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            _ if c == delimiter => return !quoted,
+            _ => {}
+        }
+        false
+    })
+}
+
+/// Remove the quotes and backslash escapes of a quoted parameter value
+fn unquote(value: &str) -> String {
+    let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+        return value.to_string();
+    };
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        unquoted.push(if c == '\\' {
+            chars.next().unwrap_or(c)
+        } else {
+            c
+        });
+    }
+    unquoted
+}
+
+/// Whether a string is a non-empty RFC 9110 token (`*` included)
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_media_types() {
+        let problem = MediaType::parse("Application/Problem+JSON; Charset=\"UTF-8\"").unwrap();
+        assert_eq!(problem.essence(), "application/problem+json");
+        assert_eq!(problem.suffix(), Some("json"));
+        assert_eq!(problem.param("charset"), Some("UTF-8"));
+        assert!(problem.is_json());
+
+        for json in [
+            "application/json",
+            "application/json;charset=utf8",
+            "application/hal+json ; profile=\"https://example.com/p\"",
+            "application/vnd.api+json",
+            "text/json",
+            "application/json, application/json",
+        ] {
+            assert!(MediaType::parse(json).unwrap().is_json(), "{}", json);
+        }
+        for other in ["text/html", "application/xml", "application/jsonl"] {
+            assert!(!MediaType::parse(other).unwrap().is_json(), "{}", other);
+        }
+        assert!(MediaType::parse("application/atom+xml").unwrap().is_xml());
+        assert!(
+            MediaType::parse("Text/HTML; charset=utf-8")
+                .unwrap()
+                .is_html()
+        );
+
+        // Commas and semicolons inside quoted parameters don't split the value
+        let profiled =
+            MediaType::parse("application/json; profile=\"a,b; c\"; charset=utf-8").unwrap();
+        assert_eq!(profiled.param("profile"), Some("a,b; c"));
+        assert_eq!(profiled.param("charset"), Some("utf-8"));
+        let escaped = MediaType::parse(r#"text/plain; title="say \"hi\", then go""#).unwrap();
+        assert_eq!(escaped.param("title"), Some(r#"say "hi", then go"#));
+        assert_eq!(MediaType::parse(&escaped.to_string()), Some(escaped));
+
+        assert_eq!(MediaType::parse(""), None);
+        assert_eq!(MediaType::parse("json"), None);
+        assert_eq!(MediaType::parse("application/"), None);
+        assert_eq!(MediaType::parse("text/html charset"), None);
+    }
+
+    #[test]
+    fn test_satisfies_and_decoding() {
+        let parse = |s| MediaType::parse(s).unwrap();
+        assert!(parse("text/csv; header=present").satisfies(&parse("text/csv")));
+        assert!(parse("text/xml").satisfies(&parse("application/xml")));
+        assert!(parse("application/json").satisfies(&parse("application/*")));
+        assert!(!parse("application/json").satisfies(&parse("application/hal+json")));
+        assert!(!parse("application/atom+xml").satisfies(&parse("application/xml")));
+
+        let bom = parse("application/json").utf8_body(b"\xEF\xBB\xBF{}");
+        assert_eq!(&*bom, b"{}");
+        let latin1 = parse("application/json; charset=ISO-8859-1").utf8_body(b"\"caf\xE9\"");
+        assert_eq!(
+            serde_json::from_slice::<String>(&latin1).unwrap(),
+            "caf\u{e9}"
+        );
+
+        // windows-1252 differs from Latin-1 in 0x80-0x9F
+        let cp1252 = parse("application/json; charset=windows-1252").utf8_body(b"\"\x80\x96\"");
+        assert_eq!(
+            serde_json::from_slice::<String>(&cp1252).unwrap(),
+            "\u{20ac}\u{2013}"
+        );
+        let unknown = parse("application/json; charset=x-unknown").utf8_body(b"\"\xE9\"");
+        assert_eq!(&*unknown, b"\"\xE9\"");
+    }
+}
@@ -0,0 +1,172 @@
+//! Discovering the methods an endpoint supports
+//!
+//! Links rarely say everything a resource can do. When method probing is
+//! enabled the crawler sends `OPTIONS` to every discovered endpoint after the
+//! crawl and reads the answer from the response headers:
+//!
+//! - `Allow` lists the supported methods (also sent with `405` responses)
+//! - `Accept-Patch` and `Accept-Post` imply `PATCH` and `POST`, and list the
+//!   media types those accept
+//! - `Access-Control-Allow-Methods` answers a CORS preflight, which is only sent
+//!   when a [`MethodProbeConfig::cors_origin`] is configured
+
+use reqwest::header::{HeaderMap, HeaderName};
+use serde::{Deserialize, Serialize};
+
+/// Method probing settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MethodProbeConfig {
+    /// Send OPTIONS to every discovered endpoint after the crawl
+    pub enabled: bool,
+
+    /// Origin to send as a CORS preflight, e.g. `https://app.example.com`
+    pub cors_origin: Option<String>,
+}
+
+/// Methods and request media types an endpoint advertises in response to OPTIONS
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SupportedMethods {
+    /// Every supported method from all headers, in conventional order
+    pub methods: Vec<String>,
+
+    /// Media types accepted by PATCH (`Accept-Patch`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accept_patch: Vec<String>,
+
+    /// Media types accepted by POST (`Accept-Post`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accept_post: Vec<String>,
+
+    /// Methods allowed for cross-origin requests (`Access-Control-Allow-Methods`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cors_methods: Vec<String>,
+}
+
+/// Conventional ordering of methods; anything else sorts after these alphabetically
+const METHOD_ORDER: &[&str] = &["GET", "HEAD", "OPTIONS", "POST", "PUT", "PATCH", "DELETE"];
+
+impl SupportedMethods {
+    /// Read supported methods from OPTIONS response headers, if any were advertised
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let allow = header_list(headers, &reqwest::header::ALLOW, true);
+        let accept_patch = header_list(headers, &HeaderName::from_static("accept-patch"), false);
+        let accept_post = header_list(headers, &HeaderName::from_static("accept-post"), false);
+        let cors_methods = header_list(
+            headers,
+            &reqwest::header::ACCESS_CONTROL_ALLOW_METHODS,
+            true,
+        );
+
+        let mut methods = allow;
+        if !accept_patch.is_empty() {
+            methods.push("PATCH".to_string());
+        }
+        if !accept_post.is_empty() {
+            methods.push("POST".to_string());
+        }
+        // A wildcard only means "any method" for requests without credentials
+        methods.extend(cors_methods.iter().filter(|m| *m != "*").cloned());
+        methods.sort_by(|a, b| method_rank(a).cmp(&method_rank(b)));
+        methods.dedup();
+
+        let supported = Self {
+            methods,
+            accept_patch,
+            accept_post,
+            cors_methods,
+        };
+        (!supported.is_empty()).then_some(supported)
+    }
+
+    /// Whether nothing was advertised
+    pub fn is_empty(&self) -> bool {
+        self.methods.is_empty() && self.cors_methods.is_empty()
+    }
+
+    /// Whether the endpoint supports a method
+    pub fn supports(&self, method: &str) -> bool {
+        self.methods.iter().any(|m| m.eq_ignore_ascii_case(method))
+    }
+}
+
+/// Sort key placing well-known methods first
+fn method_rank(method: &str) -> (usize, &str) {
+    let position = METHOD_ORDER.iter().position(|m| *m == method);
+    (position.unwrap_or(METHOD_ORDER.len()), method)
+}
+
+/// Values of a comma-separated header across all its occurrences
+fn header_list(headers: &HeaderMap, name: &HeaderName, uppercase: bool) -> Vec<String> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            if uppercase {
+                item.to_ascii_uppercase()
+            } else {
+                item.to_string()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(
+                HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                value.parse().unwrap(),
+            );
+        }
+        headers
+    }
+
+    #[test]
+    fn test_supported_methods_from_headers() {
+        assert_eq!(SupportedMethods::from_headers(&headers(&[])), None);
+        assert_eq!(
+            SupportedMethods::from_headers(&headers(&[("content-type", "text/plain")])),
+            None
+        );
+
+        let supported = SupportedMethods::from_headers(&headers(&[
+            ("allow", "delete, get"),
+            ("allow", "HEAD,OPTIONS"),
+            (
+                "accept-patch",
+                "application/merge-patch+json, application/json-patch+json",
+            ),
+            ("access-control-allow-methods", "GET, PURGE, *"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            supported.methods,
+            ["GET", "HEAD", "OPTIONS", "PATCH", "DELETE", "PURGE"]
+        );
+        assert_eq!(
+            supported.accept_patch,
+            [
+                "application/merge-patch+json",
+                "application/json-patch+json"
+            ]
+        );
+        assert!(supported.accept_post.is_empty());
+        assert_eq!(supported.cors_methods, ["GET", "PURGE", "*"]);
+        assert!(supported.supports("patch"));
+        assert!(!supported.supports("PUT"));
+
+        let post_only =
+            SupportedMethods::from_headers(&headers(&[("accept-post", "text/turtle")])).unwrap();
+        assert_eq!(post_only.methods, ["POST"]);
+        assert_eq!(post_only.accept_post, ["text/turtle"]);
+    }
+}
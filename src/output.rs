@@ -1,27 +1,41 @@
 //! Output handling for API crawler results
 
 use crate::error::{CrawlerError, Result};
+use crate::profile::ProfileOutcome;
 use crate::types::CrawlResult;
+use serde::Deserialize;
 use serde_json;
 use std::fs;
 use std::path::Path;
 use tracing::info;
 
 /// Output format options
-#[derive(Debug, Clone)]
+///
+/// Configuration files use the same names as `--format`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     /// Pretty-printed JSON
+    #[serde(rename = "pretty")]
     PrettyJson,
     /// Compact JSON
+    #[serde(rename = "compact")]
     CompactJson,
     /// Hierarchical structure with endpoints nested under parent URLs
     Hierarchical,
     /// Compact tree structure with all endpoint info in one block
     Tree,
+    /// CSV with one row per endpoint and flattened metadata columns
+    Csv,
+    /// Markdown report with a summary table and per-parent sections
+    Markdown,
+    /// JUnit XML report with one testcase per visited URL, grouped by parent URL
+    JUnit,
 }
 
 /// Output configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct OutputConfig {
     /// Format for the output
     pub format: OutputFormat,
@@ -47,7 +61,7 @@ impl Default for OutputConfig {
     }
 }
 
-/// Save crawl results to a JSON file
+/// Save crawl results to a file in the configured format
 pub fn save_results_to_file<P: AsRef<Path>>(
     result: &CrawlResult,
     file_path: P,
@@ -59,10 +73,10 @@ pub fn save_results_to_file<P: AsRef<Path>>(
     info!("Saving results to: {}", path.display());
 
     // Create parent directory if it doesn't exist
-    if let Some(parent) = path.parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent)?;
-        }
+    if let Some(parent) = path.parent()
+        && !parent.exists()
+    {
+        fs::create_dir_all(parent)?;
     }
 
     let json_string = serialize_result(result, &config)?;
@@ -72,9 +86,12 @@ pub fn save_results_to_file<P: AsRef<Path>>(
     Ok(())
 }
 
-/// Serialize crawl results to JSON string
+/// Serialize crawl results to a string in the configured format
 pub fn serialize_result(result: &CrawlResult, config: &OutputConfig) -> Result<String> {
     match config.format {
+        OutputFormat::Csv => serialize_csv_result(result),
+        OutputFormat::Markdown => serialize_markdown_result(result, config),
+        OutputFormat::JUnit => serialize_junit_result(result),
         OutputFormat::Tree => serialize_tree_result(result, config),
         OutputFormat::Hierarchical => serialize_hierarchical_result(result, config),
         _ if config.hierarchical => serialize_hierarchical_result(result, config),
@@ -84,6 +101,7 @@ pub fn serialize_result(result: &CrawlResult, config: &OutputConfig) -> Result<S
             // Filter out unwanted fields based on config
             if !config.include_stats {
                 result_copy.stats = Default::default();
+                result_copy.visits = Vec::new();
             }
 
             if !config.include_config {
@@ -91,12 +109,10 @@ pub fn serialize_result(result: &CrawlResult, config: &OutputConfig) -> Result<S
             }
 
             match config.format {
-                OutputFormat::PrettyJson | OutputFormat::Hierarchical | OutputFormat::Tree => {
-                    serde_json::to_string_pretty(&result_copy).map_err(CrawlerError::from)
-                }
                 OutputFormat::CompactJson => {
                     serde_json::to_string(&result_copy).map_err(CrawlerError::from)
                 }
+                _ => serde_json::to_string_pretty(&result_copy).map_err(CrawlerError::from),
             }
         }
     }
@@ -124,6 +140,15 @@ fn serialize_hierarchical_result(result: &CrawlResult, config: &OutputConfig) ->
         if let Some(ref method) = endpoint.method {
             endpoint_obj.insert("method".to_string(), Value::String(method.clone()));
         }
+        if let Some(ref supported) = endpoint.supported_methods {
+            endpoint_obj.insert("supported_methods".to_string(), json!(supported));
+        }
+        if !endpoint.representations.is_empty() {
+            endpoint_obj.insert(
+                "representations".to_string(),
+                json!(endpoint.representations),
+            );
+        }
         if let Some(ref content_type) = endpoint.r#type {
             endpoint_obj.insert("type".to_string(), Value::String(content_type.clone()));
         }
@@ -132,6 +157,9 @@ fn serialize_hierarchical_result(result: &CrawlResult, config: &OutputConfig) ->
         }
 
         endpoint_obj.insert("depth".to_string(), Value::Number(endpoint.depth.into()));
+        if let Some(ref template) = endpoint.template {
+            endpoint_obj.insert("template".to_string(), Value::String(template.clone()));
+        }
 
         // Only include metadata if it's not empty
         if !endpoint.metadata.is_empty() {
@@ -178,6 +206,32 @@ fn serialize_hierarchical_result(result: &CrawlResult, config: &OutputConfig) ->
         Value::Object(summary.into_iter().collect()),
     );
 
+    if !result.collections.is_empty() {
+        output.insert("collections".to_string(), json!(result.collections));
+    }
+    if !result.sampled_patterns.is_empty() {
+        output.insert(
+            "sampled_patterns".to_string(),
+            json!(result.sampled_patterns),
+        );
+    }
+
+    if !result.graphql.is_empty() {
+        output.insert("graphql".to_string(), json!(result.graphql));
+    }
+
+    if !result.routes.is_empty() {
+        output.insert("routes".to_string(), json!(result.routes));
+    }
+
+    if !result.schemas.is_empty() {
+        output.insert("schemas".to_string(), json!(result.schemas));
+    }
+
+    if !result.findings.is_empty() {
+        output.insert("findings".to_string(), json!(result.findings));
+    }
+
     if config.include_stats {
         output.insert("stats".to_string(), json!(result.stats));
     }
@@ -202,14 +256,13 @@ fn serialize_hierarchical_result(result: &CrawlResult, config: &OutputConfig) ->
     let final_json = Value::Object(output.into_iter().collect());
     match config.format {
         OutputFormat::CompactJson => serde_json::to_string(&final_json).map_err(CrawlerError::from),
-        OutputFormat::PrettyJson | OutputFormat::Hierarchical | OutputFormat::Tree => {
-            serde_json::to_string_pretty(&final_json).map_err(CrawlerError::from)
-        }
+        _ => serde_json::to_string_pretty(&final_json).map_err(CrawlerError::from),
     }
 }
 
 /// Serialize crawl results in compact tree format
 fn serialize_tree_result(result: &CrawlResult, config: &OutputConfig) -> Result<String> {
+    use crate::pagination::PaginatedCollection;
     use crate::types::ApiEndpoint;
     use indexmap::IndexMap;
     use serde_json::{Value, json};
@@ -233,6 +286,29 @@ fn serialize_tree_result(result: &CrawlResult, config: &OutputConfig) -> Result<
             Value::Object(summary.into_iter().collect()),
         );
 
+        if !result.sampled_patterns.is_empty() {
+            output.insert(
+                "sampled_patterns".to_string(),
+                json!(result.sampled_patterns),
+            );
+        }
+
+        if !result.graphql.is_empty() {
+            output.insert("graphql".to_string(), json!(result.graphql));
+        }
+
+        if !result.routes.is_empty() {
+            output.insert("routes".to_string(), json!(result.routes));
+        }
+
+        if !result.schemas.is_empty() {
+            output.insert("schemas".to_string(), json!(result.schemas));
+        }
+
+        if !result.findings.is_empty() {
+            output.insert("findings".to_string(), json!(result.findings));
+        }
+
         if config.include_stats {
             output.insert("stats".to_string(), json!(result.stats));
         }
@@ -277,12 +353,44 @@ fn serialize_tree_result(result: &CrawlResult, config: &OutputConfig) -> Result<
         }
     }
 
+    // Pages hang under their collection instead of chaining from page to page
+    let page_collections: HashMap<&str, &str> = result
+        .collections
+        .iter()
+        .flat_map(|c| c.pages.iter().map(|page| (page.as_str(), c.url.as_str())))
+        .collect();
+    for endpoint in unique_endpoints.values_mut() {
+        if let Some(collection) = endpoint
+            .parent_url
+            .as_deref()
+            .and_then(|parent| page_collections.get(parent))
+        {
+            endpoint.parent_url = Some(collection.to_string());
+        }
+    }
+    unique_endpoints.retain(|href, _| !page_collections.contains_key(href.as_str()));
+
     let endpoints: Vec<&ApiEndpoint> = unique_endpoints.values().collect();
 
+    /// Page URLs listed on a collection's node
+    fn insert_pages<M: Extend<(String, Value)>>(
+        info: &mut M,
+        href: &str,
+        collections: &[PaginatedCollection],
+    ) {
+        if let Some(collection) = collections.iter().find(|c| c.url == href) {
+            info.extend([("pages".to_string(), json!(collection.pages))]);
+            if collection.truncated {
+                info.extend([("truncated".to_string(), Value::Bool(true))]);
+            }
+        }
+    }
+
     // Build a clean tree node structure where parent info appears before children
     fn build_tree_node(
         endpoint: &ApiEndpoint,
         all_endpoints: &[&ApiEndpoint],
+        collections: &[PaginatedCollection],
         processed: &mut HashSet<String>,
     ) -> IndexMap<String, Value> {
         let mut node = IndexMap::new();
@@ -291,7 +399,7 @@ fn serialize_tree_result(result: &CrawlResult, config: &OutputConfig) -> Result<
         let name = endpoint
             .href
             .split('/')
-            .last()
+            .next_back()
             .unwrap_or(&endpoint.href)
             .to_string();
 
@@ -314,12 +422,25 @@ fn serialize_tree_result(result: &CrawlResult, config: &OutputConfig) -> Result<
         if let Some(ref method) = endpoint.method {
             endpoint_info.insert("method".to_string(), Value::String(method.clone()));
         }
+        if let Some(ref supported) = endpoint.supported_methods {
+            endpoint_info.insert("supported_methods".to_string(), json!(supported));
+        }
+        if !endpoint.representations.is_empty() {
+            endpoint_info.insert(
+                "representations".to_string(),
+                json!(endpoint.representations),
+            );
+        }
         if let Some(ref content_type) = endpoint.r#type {
             endpoint_info.insert("type".to_string(), Value::String(content_type.clone()));
         }
         if let Some(ref title) = endpoint.title {
             endpoint_info.insert("title".to_string(), Value::String(title.clone()));
         }
+        if let Some(ref template) = endpoint.template {
+            endpoint_info.insert("template".to_string(), Value::String(template.clone()));
+        }
+        insert_pages(&mut endpoint_info, &endpoint.href, collections);
 
         // Put endpoint info first
         node.insert(
@@ -341,8 +462,8 @@ fn serialize_tree_result(result: &CrawlResult, config: &OutputConfig) -> Result<
         // Sort children by depth first, then alphabetically by name
         children.sort_by(|a, b| {
             a.depth.cmp(&b.depth).then_with(|| {
-                let name_a = a.href.split('/').last().unwrap_or("");
-                let name_b = b.href.split('/').last().unwrap_or("");
+                let name_a = a.href.split('/').next_back().unwrap_or("");
+                let name_b = b.href.split('/').next_back().unwrap_or("");
                 name_a.cmp(name_b)
             })
         });
@@ -353,7 +474,7 @@ fn serialize_tree_result(result: &CrawlResult, config: &OutputConfig) -> Result<
             for child in children {
                 if !processed.contains(&child.href) {
                     processed.insert(child.href.clone());
-                    let child_node = build_tree_node(child, all_endpoints, processed);
+                    let child_node = build_tree_node(child, all_endpoints, collections, processed);
                     child_nodes.push(Value::Object(child_node.into_iter().collect()));
                 }
             }
@@ -369,22 +490,12 @@ fn serialize_tree_result(result: &CrawlResult, config: &OutputConfig) -> Result<
     let root_endpoint = endpoints
         .iter()
         .find(|e| {
-            let matches = e.href == result.start_url
+            e.href == result.start_url
                 && e.parent_url.as_ref() == Some(&result.start_url)
-                && e.rel.as_deref() == Some("self");
-
-            matches
-        })
-        .or_else(|| {
-            let found = endpoints.iter().find(|e| e.href == result.start_url);
-
-            found
-        })
-        .or_else(|| {
-            let found = endpoints.iter().find(|e| e.depth == 0);
-
-            found
+                && e.rel.as_deref() == Some("self")
         })
+        .or_else(|| endpoints.iter().find(|e| e.href == result.start_url))
+        .or_else(|| endpoints.iter().find(|e| e.depth == 0))
         .or_else(|| endpoints.first())
         .map(|e| (*e).clone());
 
@@ -395,7 +506,7 @@ fn serialize_tree_result(result: &CrawlResult, config: &OutputConfig) -> Result<
         let name = root
             .href
             .split('/')
-            .last()
+            .next_back()
             .unwrap_or(&root.href)
             .to_string();
         let rel = root
@@ -422,8 +533,8 @@ fn serialize_tree_result(result: &CrawlResult, config: &OutputConfig) -> Result<
         // Sort children by depth first, then alphabetically by name
         children.sort_by(|a, b| {
             a.depth.cmp(&b.depth).then_with(|| {
-                let name_a = a.href.split('/').last().unwrap_or("");
-                let name_b = b.href.split('/').last().unwrap_or("");
+                let name_a = a.href.split('/').next_back().unwrap_or("");
+                let name_b = b.href.split('/').next_back().unwrap_or("");
                 name_a.cmp(name_b)
             })
         });
@@ -432,7 +543,8 @@ fn serialize_tree_result(result: &CrawlResult, config: &OutputConfig) -> Result<
         for child in children {
             if !processed.contains(&child.href) {
                 processed.insert(child.href.clone());
-                let child_node = build_tree_node(child, &endpoints, &mut processed);
+                let child_node =
+                    build_tree_node(child, &endpoints, &result.collections, &mut processed);
                 child_nodes.push(Value::Object(child_node.into_iter().collect()));
             }
         }
@@ -451,12 +563,22 @@ fn serialize_tree_result(result: &CrawlResult, config: &OutputConfig) -> Result<
         if let Some(ref method) = root.method {
             endpoint_info.insert("method".to_string(), Value::String(method.clone()));
         }
+        if let Some(ref supported) = root.supported_methods {
+            endpoint_info.insert("supported_methods".to_string(), json!(supported));
+        }
+        if !root.representations.is_empty() {
+            endpoint_info.insert("representations".to_string(), json!(root.representations));
+        }
         if let Some(ref content_type) = root.r#type {
             endpoint_info.insert("type".to_string(), Value::String(content_type.clone()));
         }
         if let Some(ref title) = root.title {
             endpoint_info.insert("title".to_string(), Value::String(title.clone()));
         }
+        if let Some(ref template) = root.template {
+            endpoint_info.insert("template".to_string(), Value::String(template.clone()));
+        }
+        insert_pages(&mut endpoint_info, &root.href, &result.collections);
 
         root_object.insert("api".to_string(), Value::Object(endpoint_info));
 
@@ -497,6 +619,29 @@ fn serialize_tree_result(result: &CrawlResult, config: &OutputConfig) -> Result<
         Value::Object(summary.into_iter().collect()),
     );
 
+    if !result.sampled_patterns.is_empty() {
+        output.insert(
+            "sampled_patterns".to_string(),
+            json!(result.sampled_patterns),
+        );
+    }
+
+    if !result.graphql.is_empty() {
+        output.insert("graphql".to_string(), json!(result.graphql));
+    }
+
+    if !result.routes.is_empty() {
+        output.insert("routes".to_string(), json!(result.routes));
+    }
+
+    if !result.schemas.is_empty() {
+        output.insert("schemas".to_string(), json!(result.schemas));
+    }
+
+    if !result.findings.is_empty() {
+        output.insert("findings".to_string(), json!(result.findings));
+    }
+
     if config.include_stats {
         output.insert("stats".to_string(), json!(result.stats));
     }
@@ -535,6 +680,439 @@ fn serialize_tree_result(result: &CrawlResult, config: &OutputConfig) -> Result<
     }
 }
 
+/// Escape a single CSV field according to RFC 4180
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Render a JSON value as plain text (strings unquoted, everything else as JSON)
+fn value_to_plain_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Serialize crawl results as CSV, one row per endpoint
+///
+/// Metadata is flattened into one `metadata.<key>` column per key seen on any endpoint;
+/// space-separated `supported_methods` and `representations` columns follow `method`
+/// and `type` when those were probed, and a `template` column holds the inferred route.
+fn serialize_csv_result(result: &CrawlResult) -> Result<String> {
+    use std::collections::BTreeSet;
+
+    let metadata_keys: BTreeSet<&String> = result
+        .endpoints
+        .iter()
+        .flat_map(|e| e.metadata.keys())
+        .collect();
+
+    let mut header: Vec<String> = [
+        "href",
+        "rel",
+        "method",
+        "type",
+        "title",
+        "depth",
+        "parent_url",
+    ]
+    .iter()
+    .map(|h| h.to_string())
+    .collect();
+    // Only crawls with method probing get a supported_methods column
+    let probed = result
+        .endpoints
+        .iter()
+        .any(|e| e.supported_methods.is_some());
+    if probed {
+        header.insert(3, "supported_methods".to_string());
+    }
+    let negotiated = result
+        .endpoints
+        .iter()
+        .any(|e| !e.representations.is_empty());
+    if negotiated {
+        header.insert(if probed { 5 } else { 4 }, "representations".to_string());
+    }
+    let templated = result.endpoints.iter().any(|e| e.template.is_some());
+    if templated {
+        header.push("template".to_string());
+    }
+    header.extend(metadata_keys.iter().map(|k| format!("metadata.{}", k)));
+
+    let mut csv = String::new();
+    csv.push_str(
+        &header
+            .iter()
+            .map(|h| csv_escape(h))
+            .collect::<Vec<_>>()
+            .join(","),
+    );
+    csv.push_str("\r\n");
+
+    for endpoint in &result.endpoints {
+        let mut row = vec![
+            endpoint.href.clone(),
+            endpoint.rel.clone().unwrap_or_default(),
+            endpoint.method.clone().unwrap_or_default(),
+            endpoint.r#type.clone().unwrap_or_default(),
+            endpoint.title.clone().unwrap_or_default(),
+            endpoint.depth.to_string(),
+            endpoint.parent_url.clone().unwrap_or_default(),
+        ];
+        if probed {
+            let supported = endpoint
+                .supported_methods
+                .as_ref()
+                .map(|s| s.methods.join(" "))
+                .unwrap_or_default();
+            row.insert(3, supported);
+        }
+        if negotiated {
+            let column = if probed { 5 } else { 4 };
+            row.insert(column, endpoint.representations.join(" "));
+        }
+        if templated {
+            row.push(endpoint.template.clone().unwrap_or_default());
+        }
+        row.extend(metadata_keys.iter().map(|k| {
+            endpoint
+                .metadata
+                .get(*k)
+                .map(value_to_plain_string)
+                .unwrap_or_default()
+        }));
+
+        csv.push_str(
+            &row.iter()
+                .map(|f| csv_escape(f))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        csv.push_str("\r\n");
+    }
+
+    Ok(csv)
+}
+
+/// Escape a value for use inside a Markdown table cell
+fn markdown_cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\n', '\r'], " ")
+}
+
+/// Serialize crawl results as a Markdown report
+///
+/// The layout mirrors `print_hierarchical_summary`: a summary table followed by one
+/// section per parent URL listing the endpoints discovered there.
+fn serialize_markdown_result(result: &CrawlResult, config: &OutputConfig) -> Result<String> {
+    let mut md = String::new();
+
+    md.push_str("# API Crawl Report\n\n");
+    md.push_str(&format!("- **Start URL:** {}\n", result.start_url));
+    md.push_str(&format!(
+        "- **Started at:** {}\n",
+        result.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+    ));
+    md.push_str(&format!(
+        "- **Completed at:** {}\n\n",
+        result.completed_at.format("%Y-%m-%d %H:%M:%S UTC")
+    ));
+
+    md.push_str("## Summary\n\n");
+    md.push_str("| Metric | Value |\n");
+    md.push_str("| --- | --- |\n");
+    md.push_str(&format!(
+        "| Total endpoints | {} |\n",
+        result.endpoints.len()
+    ));
+    md.push_str(&format!(
+        "| Unique domains | {} |\n",
+        result.discovered_domains().len()
+    ));
+    md.push_str(&format!(
+        "| Parent URLs | {} |\n",
+        result.url_mappings.len()
+    ));
+    if config.include_stats {
+        md.push_str(&format!(
+            "| URLs processed | {} |\n",
+            result.stats.urls_processed
+        ));
+        md.push_str(&format!(
+            "| Successful requests | {} |\n",
+            result.stats.successful_requests
+        ));
+        md.push_str(&format!(
+            "| Failed requests | {} |\n",
+            result.stats.failed_requests
+        ));
+        md.push_str(&format!(
+            "| URLs skipped | {} |\n",
+            result.stats.urls_skipped
+        ));
+        md.push_str(&format!(
+            "| Max depth reached | {} |\n",
+            result.stats.max_depth_reached
+        ));
+        md.push_str(&format!(
+            "| Total time | {}ms |\n",
+            result.stats.total_time_ms
+        ));
+    }
+    md.push('\n');
+
+    md.push_str("## Endpoints by Parent\n\n");
+    if result.url_mappings.is_empty() {
+        md.push_str("No parent-child relationships discovered.\n\n");
+    } else {
+        let mut parents: Vec<_> = result.url_mappings.keys().collect();
+        parents.sort();
+
+        for parent in parents {
+            let children = &result.url_mappings[parent];
+            md.push_str(&format!("### {}\n\n", markdown_cell(parent)));
+            md.push_str("| Href | Rel | Method | Type | Title | Depth |\n");
+            md.push_str("| --- | --- | --- | --- | --- | --- |\n");
+            for child in children {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} | {} | {} |\n",
+                    markdown_cell(&child.href),
+                    markdown_cell(child.rel.as_deref().unwrap_or("")),
+                    markdown_cell(child.method.as_deref().unwrap_or("")),
+                    markdown_cell(child.r#type.as_deref().unwrap_or("")),
+                    markdown_cell(child.title.as_deref().unwrap_or("")),
+                    child.depth
+                ));
+            }
+            md.push('\n');
+        }
+    }
+
+    if !result.sampled_patterns.is_empty() {
+        md.push_str("## Sampled Patterns\n\n");
+        md.push_str("| Pattern | Estimated count | Sampled | Skipped |\n");
+        md.push_str("| --- | --- | --- | --- |\n");
+        for pattern in &result.sampled_patterns {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                markdown_cell(&pattern.pattern),
+                pattern.estimated_count,
+                pattern.sampled.len(),
+                pattern.skipped
+            ));
+        }
+        md.push('\n');
+    }
+
+    if !result.routes.is_empty() {
+        md.push_str("## Routes\n\n");
+        md.push_str("| Template | URLs |\n");
+        md.push_str("| --- | --- |\n");
+        for route in &result.routes {
+            md.push_str(&format!(
+                "| {} | {} |\n",
+                markdown_cell(&route.template),
+                route.count
+            ));
+        }
+        md.push('\n');
+    }
+
+    if !result.graphql.is_empty() {
+        md.push_str("## GraphQL\n\n");
+        for endpoint in &result.graphql {
+            md.push_str(&format!("### {}\n\n", markdown_cell(&endpoint.url)));
+            md.push_str(&format!("Detected by: {}\n\n", endpoint.detected_by));
+            if let Some(error) = &endpoint.error {
+                md.push_str(&format!("Introspection failed: {}\n\n", error));
+            }
+            let Some(schema) = &endpoint.schema else {
+                continue;
+            };
+            md.push_str(&format!("Types: {}\n\n", schema.types.len()));
+            md.push_str("| Operation | Signature | Description |\n");
+            md.push_str("| --- | --- | --- |\n");
+            let operations = [
+                ("query", &schema.queries),
+                ("mutation", &schema.mutations),
+                ("subscription", &schema.subscriptions),
+            ];
+            for (operation, fields) in operations {
+                for field in fields {
+                    let deprecated = if field.deprecated {
+                        " (deprecated)"
+                    } else {
+                        ""
+                    };
+                    md.push_str(&format!(
+                        "| {} | `{}`{} | {} |\n",
+                        operation,
+                        markdown_cell(&field.signature()),
+                        deprecated,
+                        markdown_cell(field.description.as_deref().unwrap_or(""))
+                    ));
+                }
+            }
+            md.push('\n');
+        }
+    }
+
+    if !result.findings.is_empty() {
+        md.push_str("## Sensitive Data\n\n");
+        md.push_str("| URL | Path | Rule | Occurrences | Sample |\n");
+        md.push_str("| --- | --- | --- | --- | --- |\n");
+        for finding in &result.findings {
+            md.push_str(&format!(
+                "| {} | `{}` | {} | {} | `{}` |\n",
+                markdown_cell(&finding.url),
+                markdown_cell(&finding.path),
+                markdown_cell(&finding.rule),
+                finding.occurrences,
+                markdown_cell(&finding.sample)
+            ));
+        }
+        md.push('\n');
+    }
+
+    if config.include_stats && !result.stats.errors.is_empty() {
+        md.push_str(&format!("## Errors ({})\n\n", result.stats.errors.len()));
+        for error in &result.stats.errors {
+            md.push_str(&format!("- {}\n", error));
+        }
+        md.push('\n');
+    }
+
+    if config.include_config && !result.config_snapshot.is_empty() {
+        md.push_str("## Configuration\n\n");
+        md.push_str("```text\n");
+        md.push_str(&result.config_snapshot);
+        md.push_str("\n```\n");
+    }
+
+    Ok(md)
+}
+
+/// Escape text for use in XML attribute values and character data
+pub fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab/newline/CR are not allowed in XML 1.0
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Format a millisecond duration as JUnit seconds
+fn junit_seconds(ms: u128) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+/// Serialize crawl results as a JUnit XML report
+///
+/// Each visited URL becomes a testcase, grouped into one testsuite per parent URL
+/// (the start URL forms its own suite). Failed requests, including HTTP error
+/// statuses, are reported as `<failure>` elements carrying the error message.
+/// Inferred route templates are listed as properties of a final `routes` suite,
+/// each valued with the number of URLs matching it.
+fn serialize_junit_result(result: &CrawlResult) -> Result<String> {
+    use crate::types::UrlVisit;
+    use indexmap::IndexMap;
+
+    let mut suites: IndexMap<&str, Vec<&UrlVisit>> = IndexMap::new();
+    for visit in &result.visits {
+        let parent = visit.parent_url.as_deref().unwrap_or(&result.start_url);
+        suites.entry(parent).or_default().push(visit);
+    }
+
+    let total_failures = result.visits.iter().filter(|v| !v.is_success()).count();
+    let total_ms: u128 = result.visits.iter().map(|v| v.duration_ms).sum();
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"api_crawler\" tests=\"{}\" failures=\"{}\" errors=\"0\" time=\"{}\" timestamp=\"{}\">\n",
+        result.visits.len(),
+        total_failures,
+        junit_seconds(total_ms),
+        result.started_at.format("%Y-%m-%dT%H:%M:%S")
+    ));
+
+    for (parent, visits) in suites {
+        let failures = visits.iter().filter(|v| !v.is_success()).count();
+        let suite_ms: u128 = visits.iter().map(|v| v.duration_ms).sum();
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"0\" time=\"{}\">\n",
+            xml_escape(parent),
+            visits.len(),
+            failures,
+            junit_seconds(suite_ms)
+        ));
+
+        for visit in visits {
+            xml.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\"",
+                xml_escape(&visit.url),
+                xml_escape(parent),
+                junit_seconds(visit.duration_ms)
+            ));
+            match &visit.error {
+                Some(error) => {
+                    let failure_type = match visit.status {
+                        Some(status) => format!("HTTP {}", status),
+                        None => "RequestError".to_string(),
+                    };
+                    xml.push_str(">\n");
+                    xml.push_str(&format!(
+                        "      <failure message=\"{}\" type=\"{}\">{}</failure>\n",
+                        xml_escape(error),
+                        xml_escape(&failure_type),
+                        xml_escape(error)
+                    ));
+                    xml.push_str("    </testcase>\n");
+                }
+                None => xml.push_str("/>\n"),
+            }
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    if !result.routes.is_empty() {
+        xml.push_str(
+            "  <testsuite name=\"routes\" tests=\"0\" failures=\"0\" errors=\"0\" skipped=\"0\" time=\"0.000\">\n",
+        );
+        xml.push_str("    <properties>\n");
+        for route in &result.routes {
+            xml.push_str(&format!(
+                "      <property name=\"{}\" value=\"{}\"/>\n",
+                xml_escape(&route.template),
+                route.count
+            ));
+        }
+        xml.push_str("    </properties>\n");
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    Ok(xml)
+}
+
 /// Print a summary of the crawl results to stdout
 pub fn print_summary(result: &CrawlResult) {
     println!("\n🕷️  API Crawl Summary");
@@ -559,6 +1137,32 @@ pub fn print_summary(result: &CrawlResult) {
     );
     println!("  • Failed requests: {}", result.stats.failed_requests);
     println!("  • URLs skipped: {}", result.stats.urls_skipped);
+    let skips = &result.stats.skipped_by_rule;
+    if !skips.is_empty() {
+        let rules = [
+            ("domain", skips.domain),
+            ("not included", skips.not_included),
+            ("excluded", skips.excluded),
+            ("rel", skips.rel),
+            ("method", skips.method),
+            ("unsafe method", skips.unsafe_method),
+            ("destructive rel", skips.destructive_rel),
+            ("page limit", skips.page_limit),
+            ("sampled", skips.sampled),
+        ];
+        let breakdown: Vec<String> = rules
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(rule, count)| format!("{} {}", count, rule))
+            .collect();
+        println!("    ↳ by rule: {}", breakdown.join(", "));
+    }
+    if result.stats.methods_probed > 0 {
+        println!(
+            "  • Endpoints with probed methods: {}",
+            result.stats.methods_probed
+        );
+    }
     println!("  • Max depth reached: {}", result.stats.max_depth_reached);
     println!("  • Total time: {}ms", result.stats.total_time_ms);
     println!();
@@ -622,6 +1226,74 @@ pub fn print_summary(result: &CrawlResult) {
         }
     }
 
+    // Sampled patterns
+    if !result.sampled_patterns.is_empty() {
+        println!();
+        println!("🎲 Sampled Patterns:");
+        for pattern in &result.sampled_patterns {
+            println!(
+                "  • {}: ~{} instances, {} followed",
+                pattern.pattern,
+                pattern.estimated_count,
+                pattern.sampled.len()
+            );
+        }
+    }
+
+    // Sensitive data
+    if !result.findings.is_empty() {
+        println!();
+        println!("🔐 Sensitive Data ({} findings):", result.findings.len());
+        for finding in result.findings.iter().take(10) {
+            println!(
+                "  • {} {} [{}]: {}",
+                finding.url, finding.path, finding.rule, finding.sample
+            );
+        }
+        if result.findings.len() > 10 {
+            println!("  ... and {} more findings", result.findings.len() - 10);
+        }
+    }
+
+    // Routes, most common first
+    if !result.routes.is_empty() {
+        println!();
+        println!("🧭 Routes ({} templates):", result.routes.len());
+        let mut routes: Vec<_> = result.routes.iter().collect();
+        routes.sort_by_key(|route| std::cmp::Reverse(route.count));
+        for route in routes.iter().take(10) {
+            println!("  • {}: {} URLs", route.template, route.count);
+        }
+        if routes.len() > 10 {
+            println!("  ... and {} more templates", routes.len() - 10);
+        }
+    }
+
+    // GraphQL
+    if !result.graphql.is_empty() {
+        println!();
+        println!("🔮 GraphQL Endpoints:");
+        for endpoint in &result.graphql {
+            println!(
+                "  • {} (detected by {})",
+                endpoint.url, endpoint.detected_by
+            );
+            if let Some(schema) = &endpoint.schema {
+                println!(
+                    "     {} types, {} queries, {} mutations",
+                    schema.types.len(),
+                    schema.queries.len(),
+                    schema.mutations.len()
+                );
+                for field in schema.queries.iter().chain(&schema.mutations).take(5) {
+                    println!("     └─ {}", field.signature());
+                }
+            } else if let Some(error) = &endpoint.error {
+                println!("     └─ {}", error);
+            }
+        }
+    }
+
     // Errors
     if !result.stats.errors.is_empty() {
         println!();
@@ -637,6 +1309,52 @@ pub fn print_summary(result: &CrawlResult) {
     println!();
 }
 
+/// Print one line per profile plus totals after a multi-target run
+pub fn print_profiles_summary(outcomes: &[ProfileOutcome]) {
+    println!("\n🕷️  Profile Crawl Summary");
+    println!("════════════════════════");
+
+    let width = outcomes.iter().map(|o| o.name.len()).max().unwrap_or(0);
+    let (mut urls, mut endpoints, mut failed) = (0, 0, 0);
+    for outcome in outcomes {
+        match &outcome.result {
+            Ok(result) => {
+                let icon = if outcome.is_success() {
+                    "✅"
+                } else {
+                    "⚠️ "
+                };
+                println!(
+                    "{} {:<width$}  {} URLs, {} endpoints, {} failed, {}ms",
+                    icon,
+                    outcome.name,
+                    result.stats.urls_processed,
+                    result.endpoints.len(),
+                    result.stats.failed_requests,
+                    result.stats.total_time_ms,
+                    width = width
+                );
+                urls += result.stats.urls_processed;
+                endpoints += result.endpoints.len();
+                failed += result.stats.failed_requests;
+            }
+            Err(e) => println!("❌ {:<width$}  {}", outcome.name, e, width = width),
+        }
+    }
+
+    let errored = outcomes.iter().filter(|o| o.result.is_err()).count();
+    println!();
+    println!(
+        "Total: {} profiles ({} errored), {} URLs, {} endpoints, {} failed requests",
+        outcomes.len(),
+        errored,
+        urls,
+        endpoints,
+        failed
+    );
+    println!();
+}
+
 /// Print hierarchical structure of endpoints
 pub fn print_hierarchical_summary(result: &CrawlResult) {
     println!("\n🌳 Hierarchical API Structure");
@@ -682,6 +1400,17 @@ pub fn print_endpoints_detailed(result: &CrawlResult, max_endpoints: Option<usiz
             println!("   Method: {}", method);
         }
 
+        if let Some(ref supported) = endpoint.supported_methods {
+            println!("   Supports: {}", supported.methods.join(", "));
+        }
+
+        if !endpoint.representations.is_empty() {
+            println!(
+                "   Representations: {}",
+                endpoint.representations.join(", ")
+            );
+        }
+
         if let Some(ref content_type) = endpoint.r#type {
             println!("   Type: {}", content_type);
         }
@@ -717,8 +1446,8 @@ pub fn print_endpoints_detailed(result: &CrawlResult, max_endpoints: Option<usiz
 pub fn generate_text_report(result: &CrawlResult) -> String {
     let mut report = String::new();
 
-    report.push_str(&format!("API Crawl Report\n"));
-    report.push_str(&format!("================\n\n"));
+    report.push_str("API Crawl Report\n");
+    report.push_str("================\n\n");
 
     report.push_str(&format!("Start URL: {}\n", result.start_url));
     report.push_str(&format!("Duration: {}ms\n", result.stats.total_time_ms));
@@ -971,4 +1700,347 @@ mod tests {
         assert!(json.contains("\"url\":"));
         assert!(json.contains("\"rel\":"));
     }
+
+    #[test]
+    fn test_csv_serialization() {
+        let mut result =
+            CrawlResult::new("http://example.com".to_string(), &CrawlerConfig::default());
+
+        let endpoint = ApiEndpoint::new("http://example.com/users".to_string(), 1)
+            .with_rel(Some("users".to_string()))
+            .with_parent(Some("http://example.com".to_string()))
+            .with_metadata("templated".to_string(), serde_json::json!(true))
+            .with_metadata(
+                "name".to_string(),
+                serde_json::json!("Users, \"all\" of them"),
+            );
+        let plain = ApiEndpoint::new("http://example.com/posts".to_string(), 1)
+            .with_parent(Some("http://example.com".to_string()));
+
+        result.add_endpoint(endpoint);
+        result.add_endpoint(plain);
+
+        let config = OutputConfig {
+            format: OutputFormat::Csv,
+            ..OutputConfig::default()
+        };
+
+        let csv = serialize_result(&result, &config).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "href,rel,method,type,title,depth,parent_url,metadata.name,metadata.templated"
+        );
+        assert_eq!(
+            lines[1],
+            "http://example.com/users,users,,,,1,http://example.com,\"Users, \"\"all\"\" of them\",true"
+        );
+        assert_eq!(
+            lines[2],
+            "http://example.com/posts,,,,,1,http://example.com,,"
+        );
+    }
+
+    #[test]
+    fn test_csv_supported_methods_column() {
+        use crate::methods::SupportedMethods;
+
+        let mut result =
+            CrawlResult::new("http://example.com".to_string(), &CrawlerConfig::default());
+        let mut endpoint = ApiEndpoint::new("http://example.com/users".to_string(), 1);
+        endpoint.supported_methods = Some(SupportedMethods {
+            methods: vec!["GET".to_string(), "POST".to_string()],
+            ..SupportedMethods::default()
+        });
+        result.add_endpoint(endpoint);
+        result.add_endpoint(ApiEndpoint::new("http://example.com/posts".to_string(), 1));
+
+        let config = OutputConfig {
+            format: OutputFormat::Csv,
+            ..OutputConfig::default()
+        };
+        let csv = serialize_result(&result, &config).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "href,rel,method,supported_methods,type,title,depth,parent_url",
+                "http://example.com/users,,,GET POST,,,1,",
+                "http://example.com/posts,,,,,,1,",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markdown_serialization() {
+        let mut result =
+            CrawlResult::new("http://example.com".to_string(), &CrawlerConfig::default());
+
+        let endpoint = ApiEndpoint::new("http://example.com/users".to_string(), 1)
+            .with_rel(Some("users".to_string()))
+            .with_parent(Some("http://example.com".to_string()));
+        let mut piped = ApiEndpoint::new("http://example.com/a|b".to_string(), 1)
+            .with_parent(Some("http://example.com".to_string()));
+        piped.title = Some("Pipes | everywhere".to_string());
+
+        result.add_endpoint(endpoint);
+        result.add_endpoint(piped);
+        result
+            .stats
+            .errors
+            .push("URL http://example.com/x: boom".to_string());
+        result
+            .sampled_patterns
+            .push(crate::sampling::SampledPattern {
+                pattern: "http://example.com/users/{id}".to_string(),
+                estimated_count: 500,
+                sampled: vec!["http://example.com/users/1".to_string()],
+                skipped: 9,
+            });
+        result.findings.push(crate::sensitive::SensitiveFinding {
+            url: "http://example.com/users".to_string(),
+            path: "$.items[*].email".to_string(),
+            rule: "email".to_string(),
+            occurrences: 3,
+            sample: "j***@example.com".to_string(),
+        });
+
+        let config = OutputConfig {
+            format: OutputFormat::Markdown,
+            include_stats: true,
+            include_config: false,
+            hierarchical: false,
+        };
+
+        let md = serialize_result(&result, &config).unwrap();
+
+        assert!(md.starts_with("# API Crawl Report"));
+        assert!(md.contains("| Total endpoints | 2 |"));
+        assert!(md.contains("### http://example.com\n"));
+        assert!(md.contains("| http://example.com/users | users |  |  |  | 1 |"));
+        assert!(md.contains("http://example.com/a\\|b"));
+        assert!(md.contains("Pipes \\| everywhere"));
+        assert!(md.contains("| http://example.com/users/{id} | 500 | 1 | 9 |"));
+        assert!(md.contains(
+            "| http://example.com/users | `$.items[*].email` | email | 3 | `j***@example.com` |"
+        ));
+        assert!(md.contains("## Errors (1)"));
+        assert!(!md.contains("## Configuration"));
+    }
+
+    #[test]
+    fn test_graphql_sections() {
+        use crate::graphql::{Detection, GraphqlEndpoint, GraphqlField, GraphqlSchema};
+
+        let mut result =
+            CrawlResult::new("http://example.com".to_string(), &CrawlerConfig::default());
+        let field = |name: &str, args: &[&str], r#type: &str| GraphqlField {
+            name: name.to_string(),
+            r#type: r#type.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            description: None,
+            deprecated: false,
+        };
+        result.graphql.push(GraphqlEndpoint {
+            url: "http://example.com/graphql".to_string(),
+            detected_by: Detection::Path,
+            schema: Some(GraphqlSchema {
+                types: Vec::new(),
+                queries: vec![field("user", &["id: ID!"], "User")],
+                mutations: vec![field("deleteUser", &["id: ID!"], "Boolean!")],
+                subscriptions: Vec::new(),
+            }),
+            error: None,
+        });
+
+        let mut config = OutputConfig {
+            format: OutputFormat::Markdown,
+            include_stats: false,
+            include_config: false,
+            hierarchical: false,
+        };
+        let md = serialize_result(&result, &config).unwrap();
+        assert!(md.contains("## GraphQL\n\n### http://example.com/graphql\n\nDetected by: path\n"));
+        assert!(md.contains("| query | `user(id: ID!): User` |  |"));
+        assert!(md.contains("| mutation | `deleteUser(id: ID!): Boolean!` |  |"));
+
+        for format in [OutputFormat::Hierarchical, OutputFormat::Tree] {
+            config.format = format;
+            let json: serde_json::Value =
+                serde_json::from_str(&serialize_result(&result, &config).unwrap()).unwrap();
+            assert_eq!(
+                json["graphql"][0]["schema"]["queries"][0]["type"], "User",
+                "{:?}",
+                config.format
+            );
+        }
+    }
+
+    #[test]
+    fn test_routes_sections() {
+        let mut result =
+            CrawlResult::new("http://example.com".to_string(), &CrawlerConfig::default());
+        for id in [1, 2] {
+            result.add_endpoint(
+                ApiEndpoint::new(format!("http://example.com/users/{}", id), 1)
+                    .with_parent(Some("http://example.com".to_string())),
+            );
+        }
+        crate::routes::infer_routes(&mut result);
+
+        let mut config = OutputConfig {
+            format: OutputFormat::Markdown,
+            include_stats: false,
+            include_config: false,
+            hierarchical: false,
+        };
+        let md = serialize_result(&result, &config).unwrap();
+        assert!(md.contains("## Routes\n\n| Template | URLs |\n| --- | --- |\n| http://example.com/users/{id} | 2 |\n"));
+
+        config.format = OutputFormat::JUnit;
+        let xml = serialize_result(&result, &config).unwrap();
+        assert!(xml.contains("<property name=\"http://example.com/users/{id}\" value=\"2\"/>"));
+
+        config.format = OutputFormat::Csv;
+        let csv = serialize_result(&result, &config).unwrap();
+        assert!(csv.starts_with("href,rel,method,type,title,depth,parent_url,template\r\n"));
+        assert!(csv.contains(",http://example.com,http://example.com/users/{id}\r\n"));
+
+        for format in [OutputFormat::Hierarchical, OutputFormat::Tree] {
+            config.format = format;
+            let json: serde_json::Value =
+                serde_json::from_str(&serialize_result(&result, &config).unwrap()).unwrap();
+            assert_eq!(json["routes"][0]["count"], 2, "{:?}", config.format);
+        }
+    }
+
+    #[test]
+    fn test_tree_groups_pages_under_collection() {
+        use crate::pagination::PaginatedCollection;
+
+        let mut result =
+            CrawlResult::new("http://example.com".to_string(), &CrawlerConfig::default());
+        let link = |href: &str, rel: Option<&str>, parent: &str, depth: usize| {
+            ApiEndpoint::new(href.to_string(), depth)
+                .with_rel(rel.map(str::to_string))
+                .with_parent(Some(parent.to_string()))
+        };
+        let orders = "http://example.com/orders";
+        let page2 = "http://example.com/orders?page=2";
+        let page3 = "http://example.com/orders?page=3";
+        result.add_endpoint(link(
+            "http://example.com",
+            Some("self"),
+            "http://example.com",
+            0,
+        ));
+        result.add_endpoint(link(orders, Some("orders"), "http://example.com", 1));
+        result.add_endpoint(link("http://example.com/orders/1", None, orders, 2));
+        result.add_endpoint(link(page2, Some("next"), orders, 1));
+        result.add_endpoint(link("http://example.com/orders/2", None, page2, 2));
+        result.add_endpoint(link(page3, Some("next"), page2, 1));
+        result.collections.push(PaginatedCollection {
+            url: orders.to_string(),
+            pages: vec![page2.to_string(), page3.to_string()],
+            truncated: true,
+        });
+
+        let config = OutputConfig {
+            format: OutputFormat::Tree,
+            include_stats: false,
+            include_config: false,
+            hierarchical: false,
+        };
+        let json: serde_json::Value =
+            serde_json::from_str(&serialize_result(&result, &config).unwrap()).unwrap();
+        let collection = &json["api_tree"]["children"][0];
+        assert_eq!(collection["api"]["url"], orders);
+        assert_eq!(
+            collection["api"]["pages"],
+            serde_json::json!([page2, page3])
+        );
+        assert_eq!(collection["api"]["truncated"], true);
+
+        // Items of every page are children of the collection; pages are not
+        let children: Vec<&str> = collection["children"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["api"]["url"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            children,
+            ["http://example.com/orders/1", "http://example.com/orders/2"]
+        );
+        assert_eq!(json["summary"]["total_endpoints"], 4);
+    }
+
+    #[test]
+    fn test_junit_serialization() {
+        use crate::types::UrlVisit;
+
+        let mut result =
+            CrawlResult::new("http://example.com".to_string(), &CrawlerConfig::default());
+
+        result.visits.push(UrlVisit {
+            url: "http://example.com".to_string(),
+            depth: 0,
+            parent_url: None,
+            method: None,
+            status: Some(200),
+            duration_ms: 120,
+            endpoints_found: 2,
+            error: None,
+        });
+        result.visits.push(UrlVisit {
+            url: "http://example.com/users?a=1&b=2".to_string(),
+            depth: 1,
+            parent_url: Some("http://example.com".to_string()),
+            method: None,
+            status: Some(200),
+            duration_ms: 30,
+            endpoints_found: 0,
+            error: None,
+        });
+        result.visits.push(UrlVisit {
+            url: "http://example.com/missing".to_string(),
+            depth: 1,
+            parent_url: Some("http://example.com".to_string()),
+            method: None,
+            status: Some(404),
+            duration_ms: 5,
+            endpoints_found: 0,
+            error: Some("HTTP status client error (404 Not Found)".to_string()),
+        });
+
+        let config = OutputConfig {
+            format: OutputFormat::JUnit,
+            ..OutputConfig::default()
+        };
+
+        let xml = serialize_result(&result, &config).unwrap();
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        assert!(xml.contains("<testsuites name=\"api_crawler\" tests=\"3\" failures=\"1\""));
+        // Start URL and its children share the start URL suite
+        assert_eq!(xml.matches("<testsuite ").count(), 1);
+        assert!(
+            xml.contains("tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"0\" time=\"0.155\"")
+        );
+        assert!(xml.contains("name=\"http://example.com/users?a=1&amp;b=2\""));
+        assert!(xml.contains(
+            "<failure message=\"HTTP status client error (404 Not Found)\" type=\"HTTP 404\">"
+        ));
+    }
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(
+            xml_escape("<a href=\"x\">'&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&apos;&amp;&apos;&lt;/a&gt;"
+        );
+        assert_eq!(xml_escape("bell\u{7}tab\t"), "belltab\t");
+    }
 }
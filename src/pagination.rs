@@ -0,0 +1,334 @@
+//! Pagination of collections
+//!
+//! Page links are recognised by their rel (`next`, `prev`, `first`, `last`),
+//! whether they come from HAL `_links`, JSON:API `links`, Atom or HTML, and are
+//! also read from `Link` headers, DRF-style `next`/`previous` fields and cursor
+//! fields such as `next_cursor` or `nextPageToken`.
+//!
+//! The crawler follows pages at the depth of their collection rather than one
+//! level deeper, stops after `max_pages` pages per collection and groups the
+//! pages under their collection in [`crate::types::CrawlResult::collections`].
+
+use crate::types::{ApiEndpoint, QueueItem};
+use reqwest::header::{HeaderMap, LINK};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use url::Url;
+
+/// Rels marking a link to another page of the same collection
+pub const PAGE_RELS: &[&str] = &["next", "prev", "previous", "first", "last"];
+
+/// Objects searched for pagination fields besides the top level
+const PAGINATION_CONTAINERS: &[&str] = &[
+    "meta",
+    "pagination",
+    "paging",
+    "page_info",
+    "pageInfo",
+    "response_metadata",
+];
+
+/// Fields holding a page URL, with the rel they stand for
+const PAGE_URL_FIELDS: &[(&str, &str)] = &[
+    ("next", "next"),
+    ("next_page_url", "next"),
+    ("nextPageUrl", "next"),
+    ("previous", "prev"),
+    ("prev", "prev"),
+    ("prev_page_url", "prev"),
+    ("prevPageUrl", "prev"),
+];
+
+/// Cursor fields, with the query parameter the cursor is sent back in
+const CURSOR_FIELDS: &[(&str, &str)] = &[
+    ("next_cursor", "cursor"),
+    ("nextCursor", "cursor"),
+    ("next_page_token", "page_token"),
+    ("nextPageToken", "pageToken"),
+];
+
+/// Fields that, when `false`, mean there is no next page
+const HAS_MORE_FIELDS: &[&str] = &["has_more", "hasMore", "has_next", "hasNextPage"];
+
+/// Pagination settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PaginationConfig {
+    /// Maximum pages fetched per collection, the first included (0, the default, means unlimited)
+    pub max_pages: usize,
+}
+
+impl PaginationConfig {
+    /// Whether another page may be fetched for a collection with `pages` pages beyond the first
+    pub fn allows(&self, pages: usize) -> bool {
+        self.max_pages == 0 || pages + 1 < self.max_pages
+    }
+}
+
+/// A paginated collection and the pages followed from it
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PaginatedCollection {
+    /// URL of the first page fetched
+    pub url: String,
+
+    /// Further pages, in the order they were queued
+    pub pages: Vec<String>,
+
+    /// Whether more pages were linked than `max_pages` allowed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+/// Whether a link points at another page of the same collection
+pub fn is_page_link(endpoint: &ApiEndpoint) -> bool {
+    endpoint.rel.as_deref().is_some_and(|rel| {
+        rel.split_whitespace()
+            .any(|rel| PAGE_RELS.iter().any(|page| rel.eq_ignore_ascii_case(page)))
+    })
+}
+
+/// Page links from `Link` headers (RFC 8288), resolved against the fetched URL
+///
+/// Links with other rels are left to the body's own link extraction.
+pub fn link_header_endpoints(headers: &HeaderMap, item: &QueueItem) -> Vec<ApiEndpoint> {
+    let Ok(base) = Url::parse(&item.url) else {
+        return Vec::new();
+    };
+
+    let mut endpoints = Vec::new();
+    for value in headers.get_all(LINK).iter().filter_map(|v| v.to_str().ok()) {
+        for (target, rel) in parse_link_header(value) {
+            let Ok(href) = base.join(&target) else {
+                continue;
+            };
+            let endpoint = page_endpoint(href.to_string(), &rel, item).with_metadata(
+                "source".to_string(),
+                Value::String("link_header".to_string()),
+            );
+            if is_page_link(&endpoint) {
+                endpoints.push(endpoint);
+            }
+        }
+    }
+    endpoints
+}
+
+/// `(target, rel)` pairs of a `Link` header value
+fn parse_link_header(value: &str) -> Vec<(String, String)> {
+    let mut links = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let target = rest[start + 1..start + end].trim().to_string();
+        rest = &rest[start + end + 1..];
+
+        // Parameters run up to the next link; quoted values may contain commas
+        let mut params_end = rest.len();
+        let mut quoted = false;
+        for (i, c) in rest.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                ',' if !quoted => {
+                    params_end = i;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let rel = rest[..params_end].split(';').find_map(|param| {
+            let (name, value) = param.split_once('=')?;
+            name.trim()
+                .eq_ignore_ascii_case("rel")
+                .then(|| value.trim().trim_matches('"').to_string())
+        });
+        if let Some(rel) = rel {
+            links.push((target, rel));
+        }
+        rest = &rest[params_end..];
+    }
+    links
+}
+
+/// Page links from pagination fields in a JSON body
+///
+/// Looks at the top level and common pagination objects for page URLs
+/// (`next`, `previous`, `next_page_url`, ...) and cursors (`next_cursor`,
+/// `nextPageToken`, ...). A cursor becomes the fetched URL with the cursor in
+/// its query, unless a `has_more`-style field says there are no more pages.
+pub fn body_endpoints(json: &Value, item: &QueueItem) -> Vec<ApiEndpoint> {
+    let Some(root) = json.as_object() else {
+        return Vec::new();
+    };
+    let Ok(base) = Url::parse(&item.url) else {
+        return Vec::new();
+    };
+
+    let containers = std::iter::once(root).chain(
+        PAGINATION_CONTAINERS
+            .iter()
+            .filter_map(|key| root.get(*key)?.as_object()),
+    );
+
+    let mut endpoints = Vec::new();
+    for container in containers {
+        for (field, rel) in PAGE_URL_FIELDS {
+            if let Some(href) = container.get(*field).and_then(Value::as_str)
+                && let Some(href) = resolve_page_url(&base, href)
+            {
+                endpoints.push(field_endpoint(href, rel, field, item));
+            }
+        }
+
+        if has_no_more(container) {
+            continue;
+        }
+        for (field, param) in CURSOR_FIELDS {
+            let Some(cursor) = container.get(*field).and_then(Value::as_str) else {
+                continue;
+            };
+            let href = match resolve_page_url(&base, cursor) {
+                Some(href) => href,
+                None if !cursor.is_empty() => with_query_param(&base, param, cursor),
+                None => continue,
+            };
+            endpoints.push(field_endpoint(href, "next", field, item));
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    endpoints.retain(|e| seen.insert((e.href.clone(), e.rel.clone())));
+    endpoints
+}
+
+/// Whether a pagination object says there are no further pages
+fn has_no_more(container: &Map<String, Value>) -> bool {
+    HAS_MORE_FIELDS
+        .iter()
+        .any(|field| container.get(*field) == Some(&Value::Bool(false)))
+}
+
+/// A page URL field's value resolved against the fetched URL, if it is a URL
+fn resolve_page_url(base: &Url, value: &str) -> Option<String> {
+    if !(value.starts_with("http://") || value.starts_with("https://") || value.starts_with('/')) {
+        return None;
+    }
+    base.join(value).ok().map(|url| url.to_string())
+}
+
+/// The fetched URL with one query parameter replaced
+fn with_query_param(base: &Url, param: &str, value: &str) -> String {
+    let mut url = base.clone();
+    let pairs: Vec<(String, String)> = base
+        .query_pairs()
+        .filter(|(name, _)| name != param)
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair(param, value);
+    url.to_string()
+}
+
+/// A page link found in a body field
+fn field_endpoint(href: String, rel: &str, field: &str, item: &QueueItem) -> ApiEndpoint {
+    page_endpoint(href, rel, item)
+        .with_metadata("source_field".to_string(), Value::String(field.to_string()))
+}
+
+/// A page link found while processing `item`
+fn page_endpoint(href: String, rel: &str, item: &QueueItem) -> ApiEndpoint {
+    ApiEndpoint::new(href, item.depth + 1)
+        .with_rel(Some(rel.to_string()))
+        .with_parent(Some(item.url.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn item(url: &str) -> QueueItem {
+        QueueItem::new(url.to_string(), 1, None)
+    }
+
+    #[test]
+    fn test_link_header_pages() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            LINK,
+            r#"</orders?page=3>; rel="next", <https://example.com/orders?page=1>; rel="prev first"; title="a, b""#
+                .parse()
+                .unwrap(),
+        );
+        headers.append(
+            LINK,
+            r#"<https://example.com/schema>; rel="describedby""#
+                .parse()
+                .unwrap(),
+        );
+
+        let endpoints = link_header_endpoints(&headers, &item("https://example.com/orders?page=2"));
+        let links: Vec<(&str, &str)> = endpoints
+            .iter()
+            .map(|e| (e.href.as_str(), e.rel.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                ("https://example.com/orders?page=3", "next"),
+                ("https://example.com/orders?page=1", "prev first"),
+            ]
+        );
+        assert_eq!(endpoints[0].metadata["source"], "link_header");
+        assert!(is_page_link(&endpoints[1]));
+    }
+
+    #[test]
+    fn test_body_pagination_fields() {
+        let drf = json!({
+            "count": 40,
+            "next": "https://example.com/users/?page=3",
+            "previous": "/users/?page=1",
+            "results": []
+        });
+        let endpoints = body_endpoints(&drf, &item("https://example.com/users/?page=2"));
+        let links: Vec<(&str, &str)> = endpoints
+            .iter()
+            .map(|e| (e.href.as_str(), e.rel.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                ("https://example.com/users/?page=3", "next"),
+                ("https://example.com/users/?page=1", "prev"),
+            ]
+        );
+
+        let cursor = json!({"data": [], "meta": {"next_cursor": "abc=", "has_more": true}});
+        let endpoints = body_endpoints(
+            &cursor,
+            &item("https://example.com/events?limit=5&cursor=x"),
+        );
+        assert_eq!(
+            endpoints[0].href,
+            "https://example.com/events?limit=5&cursor=abc%3D"
+        );
+        assert_eq!(endpoints[0].metadata["source_field"], "next_cursor");
+
+        let last_page = json!({"items": [], "nextPageToken": "t", "hasMore": false});
+        assert!(body_endpoints(&last_page, &item("https://example.com/items")).is_empty());
+        let empty_cursor = json!({"response_metadata": {"next_cursor": ""}});
+        assert!(body_endpoints(&empty_cursor, &item("https://example.com/items")).is_empty());
+        let not_a_url = json!({"next": "page two"});
+        assert!(body_endpoints(&not_a_url, &item("https://example.com/items")).is_empty());
+
+        let limit = PaginationConfig { max_pages: 3 };
+        assert!(limit.allows(1));
+        assert!(!limit.allows(2));
+        assert!(PaginationConfig::default().allows(1000));
+        assert!(!PaginationConfig { max_pages: 1 }.allows(0));
+    }
+}
@@ -0,0 +1,403 @@
+//! Named crawl targets ("profiles") for crawling many services in one run
+//!
+//! A profiles file holds optional `defaults` and one table per target under
+//! `profiles`. Each target has the same shape as a [`ConfigFile`] plus an
+//! `output_file` for its result; `defaults` are merged underneath every target,
+//! with the target's own values winning:
+//!
+//! ```toml
+//! [defaults.crawler]
+//! timeout_seconds = 10
+//!
+//! [profiles.billing]
+//! url = "https://billing.example.com/api"
+//! output_file = "results/billing.json"
+//! crawler = { max_depth = 4, auth = { bearer = { env = "BILLING_TOKEN" } } }
+//!
+//! [profiles.search]
+//! url = "https://search.example.com/"
+//! output = { format = "tree" }
+//! ```
+
+use crate::config::{ConfigFile, ConfigFormat, check_problems, in_file, read_document};
+use crate::crawler::ApiCrawler;
+use crate::error::{CrawlerError, Result};
+use crate::output::{OutputConfig, OutputFormat, save_results_to_file};
+use crate::types::{CrawlResult, CrawlerConfig};
+use indexmap::IndexMap;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tracing::{error, info};
+
+/// A single named crawl target
+#[derive(Debug, Clone)]
+pub struct Profile {
+    /// Profile name
+    pub name: String,
+
+    /// Starting URL for the crawl
+    pub url: String,
+
+    /// Where to write the crawl result, if anywhere
+    pub output_file: Option<PathBuf>,
+
+    /// Crawler settings
+    pub crawler: CrawlerConfig,
+
+    /// Output settings for `output_file`
+    pub output: OutputConfig,
+}
+
+/// Profiles loaded from a file, in file order
+#[derive(Debug, Clone, Default)]
+pub struct ProfileSet {
+    profiles: IndexMap<String, Profile>,
+}
+
+impl ProfileSet {
+    /// Load and validate a profiles file, picking the format from its extension
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let profiles = Self::from_value(read_document(path)?).map_err(|e| in_file(path, e))?;
+        info!(
+            "Loaded {} profiles from: {}",
+            profiles.len(),
+            path.display()
+        );
+        Ok(profiles)
+    }
+
+    /// Parse and validate profiles text
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<Self> {
+        let document = format.parse(content).map_err(CrawlerError::config)?;
+        Self::from_value(document)
+    }
+
+    fn from_value(document: Value) -> Result<Self> {
+        let Value::Object(mut root) = document else {
+            return Err(CrawlerError::config(
+                "Profiles file must be a table of settings",
+            ));
+        };
+
+        let mut problems = Vec::new();
+        let defaults = match root.remove("defaults") {
+            None => Map::new(),
+            Some(Value::Object(defaults)) => defaults,
+            Some(_) => {
+                problems.push("defaults: expected a table".to_string());
+                Map::new()
+            }
+        };
+        let targets = match root.remove("profiles") {
+            Some(Value::Object(targets)) if !targets.is_empty() => targets,
+            Some(Value::Object(_)) | None => {
+                problems.push("profiles: no profiles defined".to_string());
+                Map::new()
+            }
+            Some(_) => {
+                problems.push("profiles: expected a table of named profiles".to_string());
+                Map::new()
+            }
+        };
+        let mut unknown: Vec<&String> = root.keys().collect();
+        unknown.sort();
+        for key in unknown {
+            problems.push(format!(
+                "{}: unknown section, expected `defaults` or `profiles`",
+                key
+            ));
+        }
+
+        let mut profiles = IndexMap::new();
+        for (name, target) in targets {
+            let prefix = format!("profiles.{}", name);
+            let mut document = Value::Object(defaults.clone());
+            merge(&mut document, target);
+
+            let output_file = match &mut document {
+                Value::Object(table) => match table.remove("output_file") {
+                    None => None,
+                    Some(Value::String(path)) => Some(PathBuf::from(path)),
+                    Some(_) => {
+                        problems.push(format!("{}.output_file: expected a path", prefix));
+                        None
+                    }
+                },
+                _ => None,
+            };
+
+            let mut profile_problems = Vec::new();
+            let config = ConfigFile::from_value(document, &mut profile_problems);
+            if config.url.is_none() && !profile_problems.iter().any(|p| p.starts_with("url:")) {
+                profile_problems.push("url: missing starting URL".to_string());
+            }
+            problems.extend(
+                profile_problems
+                    .into_iter()
+                    .map(|problem| format!("{}.{}", prefix, problem)),
+            );
+
+            profiles.insert(
+                name.clone(),
+                Profile {
+                    name,
+                    url: config.url.unwrap_or_default(),
+                    output_file,
+                    crawler: config.crawler,
+                    output: config.output,
+                },
+            );
+        }
+
+        check_problems(problems)?;
+        Ok(Self { profiles })
+    }
+
+    /// Look up a profile by name
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.get(name)
+    }
+
+    /// Profile names in file order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// All profiles in file order
+    pub fn iter(&self) -> impl Iterator<Item = &Profile> {
+        self.profiles.values()
+    }
+
+    /// Select profiles by name, failing on any unknown name
+    pub fn select<S: AsRef<str>>(&self, names: &[S]) -> Result<Vec<Profile>> {
+        names
+            .iter()
+            .map(|name| {
+                let name = name.as_ref();
+                self.get(name).cloned().ok_or_else(|| {
+                    CrawlerError::config(format!(
+                        "Unknown profile '{}' (available: {})",
+                        name,
+                        self.names().collect::<Vec<_>>().join(", ")
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// Number of profiles
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    /// Whether no profiles are defined
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+}
+
+/// Recursively merge `overlay` into `base`; tables merge, everything else is replaced
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Outcome of crawling one profile
+#[derive(Debug)]
+pub struct ProfileOutcome {
+    /// Profile name
+    pub name: String,
+
+    /// Crawl result, or the error that stopped the crawl or saving its output
+    pub result: Result<CrawlResult>,
+}
+
+impl ProfileOutcome {
+    /// Whether the crawl completed without failed requests
+    pub fn is_success(&self) -> bool {
+        matches!(&self.result, Ok(result) if result.stats.failed_requests == 0)
+    }
+}
+
+impl Profile {
+    /// Crawl this profile and save its result to `output_file`
+    pub async fn run(&self) -> Result<CrawlResult> {
+        let mut config = self.crawler.clone();
+        // JUnit reports are built from the visited URLs
+        config.record_visits |= matches!(self.output.format, OutputFormat::JUnit);
+        let mut crawler = ApiCrawler::new(config)?;
+        let result = crawler.crawl(&self.url).await?;
+
+        if let Some(path) = &self.output_file {
+            save_results_to_file(&result, path, Some(self.output.clone()))?;
+            info!("[{}] Results saved to: {}", self.name, path.display());
+        }
+        Ok(result)
+    }
+}
+
+/// Crawl profiles, at most `parallelism` at a time, returning outcomes in input order
+pub async fn run_profiles(profiles: Vec<Profile>, parallelism: usize) -> Vec<ProfileOutcome> {
+    let semaphore = Arc::new(Semaphore::new(parallelism.max(1)));
+    let handles: Vec<_> = profiles
+        .into_iter()
+        .map(|profile| {
+            let semaphore = semaphore.clone();
+            tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                info!("[{}] Crawling {}", profile.name, profile.url);
+                let result = profile.run().await;
+                if let Err(e) = &result {
+                    error!("[{}] {}", profile.name, e);
+                }
+                ProfileOutcome {
+                    name: profile.name,
+                    result,
+                }
+            })
+        })
+        .collect();
+
+    let mut outcomes = Vec::with_capacity(handles.len());
+    for handle in handles {
+        match handle.await {
+            Ok(outcome) => outcomes.push(outcome),
+            Err(e) => outcomes.push(ProfileOutcome {
+                name: "<unknown>".to_string(),
+                result: Err(CrawlerError::invalid_response(format!(
+                    "Profile task failed: {}",
+                    e
+                ))),
+            }),
+        }
+    }
+    outcomes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const PROFILES: &str = r#"
+        [defaults.crawler]
+        timeout_seconds = 7
+        headers = { Accept = "application/hal+json" }
+
+        [profiles.billing]
+        url = "https://billing.example.com/api"
+        output_file = "results/billing.json"
+        crawler = { max_depth = 4, headers = { X-Team = "billing" } }
+
+        [profiles.search]
+        url = "https://search.example.com/"
+        crawler = { timeout_seconds = 3 }
+        output = { format = "tree" }
+    "#;
+
+    #[test]
+    fn test_profiles_merge_defaults() {
+        let profiles = ProfileSet::parse(PROFILES, ConfigFormat::Toml).unwrap();
+        assert_eq!(profiles.names().collect::<Vec<_>>(), ["billing", "search"]);
+
+        let billing = profiles.get("billing").unwrap();
+        assert_eq!(billing.crawler.timeout_seconds, 7);
+        assert_eq!(billing.crawler.max_depth, 4);
+        assert_eq!(billing.crawler.headers.len(), 2);
+        assert_eq!(
+            billing.output_file.as_deref(),
+            Some(Path::new("results/billing.json"))
+        );
+
+        let search = profiles.get("search").unwrap();
+        assert_eq!(search.crawler.timeout_seconds, 3);
+        assert_eq!(search.crawler.headers.len(), 1);
+        assert!(search.output_file.is_none());
+
+        let error = profiles.select(&["billing", "payroll"]).unwrap_err();
+        assert!(error.to_string().contains("Unknown profile 'payroll'"));
+    }
+
+    #[test]
+    fn test_invalid_profiles_reported_together() {
+        let toml = r#"
+            [profiles.billing]
+            crawler = { max_concurrent_requests = 0 }
+
+            [profiles.search]
+            url = "https://search.example.com/"
+            output_file = 3
+            output = { format = "yaml" }
+        "#;
+        let message = ProfileSet::parse(toml, ConfigFormat::Toml)
+            .unwrap_err()
+            .to_string();
+        for problem in [
+            "profiles.billing.url: missing starting URL",
+            "profiles.billing.crawler.max_concurrent_requests: must be at least 1",
+            "profiles.search.output_file: expected a path",
+            "profiles.search.output.format:",
+        ] {
+            assert!(
+                message.contains(problem),
+                "missing {} in {}",
+                problem,
+                message
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_profiles_in_parallel() {
+        // Tiny HTTP server answering every request with an empty JSON document
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    let _ = socket.read(&mut buf).await;
+                    let _ = socket
+                        .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}")
+                        .await;
+                });
+            }
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("a.json");
+        let json = serde_json::json!({
+            "defaults": {"crawler": {"delay_ms": 0, "proxy": {"use_env": false}}},
+            "profiles": {
+                "a": {"url": format!("{}/a", base), "output_file": output},
+                "b": {"url": format!("{}/b", base)},
+                "broken": {"url": "http://127.0.0.1:1/", "crawler": {"timeout_seconds": 1}}
+            }
+        });
+        let profiles = ProfileSet::parse(&json.to_string(), ConfigFormat::Json).unwrap();
+
+        let outcomes = run_profiles(profiles.iter().cloned().collect(), 2).await;
+        let names: Vec<&str> = outcomes.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "broken"]);
+        assert!(outcomes[0].is_success());
+        assert!(outcomes[1].is_success());
+        assert!(!outcomes[2].is_success());
+        assert!(output.exists());
+    }
+}
@@ -0,0 +1,266 @@
+//! Route template inference
+//!
+//! After a crawl, discovered hrefs are clustered into route templates such as
+//! `http://example.com/orders/{id}/items/{itemId}`. Numeric, UUID and hash
+//! segments are always parameters; slugs (`my-first-post`, `2024-05-01`) are
+//! parameters only when sibling URLs differ in that position, so fixed names
+//! like `line-items` stay literal. The first parameter is named `{id}` and
+//! later ones after the collection segment before them.
+//!
+//! Queries and fragments are not part of a template: `/orders?page=2` matches
+//! `/orders`.
+
+use crate::sampling::is_id_segment;
+use crate::types::CrawlResult;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use url::Url;
+
+/// A route template and how many concrete URLs matched it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RouteTemplate {
+    /// Origin and templated path, e.g. `http://example.com/users/{id}`
+    pub template: String,
+
+    /// Distinct concrete URLs (without query) matching the template
+    pub count: usize,
+}
+
+/// What a path segment holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment {
+    Literal,
+    Id,
+    Slug,
+}
+
+/// Whether a segment looks like a slug: lowercase hyphenated words, at least
+/// three of them or one containing a digit
+fn is_slug(segment: &str) -> bool {
+    let words: Vec<&str> = segment.split('-').collect();
+    words.len() >= 2
+        && words.iter().all(|w| {
+            !w.is_empty()
+                && w.bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+        })
+        && (words.len() >= 3 || words.iter().any(|w| w.bytes().any(|b| b.is_ascii_digit())))
+}
+
+/// A URL split into origin and path segments, without query or fragment
+fn split(href: &str) -> Option<(String, Vec<String>)> {
+    let url = Url::parse(href).ok()?;
+    let segments = url.path_segments()?.map(str::to_string).collect();
+    Some((url.origin().ascii_serialization(), segments))
+}
+
+/// Infer route templates for every discovered endpoint
+///
+/// Sets `template` on each endpoint (in `endpoints` and `url_mappings`) and
+/// replaces `routes` with one entry per template, sorted by template.
+pub fn infer_routes(result: &mut CrawlResult) {
+    let urls: HashSet<(String, Vec<String>)> = result
+        .endpoints
+        .iter()
+        .filter_map(|e| split(&e.href))
+        .collect();
+
+    // Slug positions become parameters only where siblings differ
+    let classify = |segments: &[String]| -> Vec<Segment> {
+        segments
+            .iter()
+            .map(|s| {
+                if is_id_segment(s) {
+                    Segment::Id
+                } else if is_slug(s) {
+                    Segment::Slug
+                } else {
+                    Segment::Literal
+                }
+            })
+            .collect()
+    };
+    let mut slug_values: HashMap<(String, usize, String), HashSet<&str>> = HashMap::new();
+    for (origin, segments) in &urls {
+        let kinds = classify(segments);
+        for (i, kind) in kinds.iter().enumerate() {
+            if *kind == Segment::Slug {
+                let key = (origin.clone(), i, sibling_key(segments, &kinds, i));
+                slug_values.entry(key).or_default().insert(&segments[i]);
+            }
+        }
+    }
+
+    let mut templates: HashMap<String, String> = HashMap::new();
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for (origin, segments) in &urls {
+        let mut kinds = classify(segments);
+        for i in 0..kinds.len() {
+            if kinds[i] == Segment::Slug {
+                let key = (origin.clone(), i, sibling_key(segments, &kinds, i));
+                if slug_values.get(&key).is_none_or(|values| values.len() < 2) {
+                    kinds[i] = Segment::Literal;
+                }
+            }
+        }
+
+        let template = format!("{}/{}", origin, template_path(segments, &kinds));
+        *counts.entry(template.clone()).or_default() += 1;
+        templates.insert(format!("{}/{}", origin, segments.join("/")), template);
+    }
+
+    let lookup = |href: &str| {
+        split(href).and_then(|(origin, segments)| {
+            templates
+                .get(&format!("{}/{}", origin, segments.join("/")))
+                .cloned()
+        })
+    };
+    let mappings = result.url_mappings.values_mut().flatten();
+    for endpoint in result.endpoints.iter_mut().chain(mappings) {
+        endpoint.template = lookup(&endpoint.href);
+    }
+    result.routes = counts
+        .into_iter()
+        .map(|(template, count)| RouteTemplate { template, count })
+        .collect();
+}
+
+/// Key grouping URLs that differ only in the slug at position `index`
+fn sibling_key(segments: &[String], kinds: &[Segment], index: usize) -> String {
+    segments
+        .iter()
+        .zip(kinds)
+        .enumerate()
+        .map(|(i, (segment, kind))| match kind {
+            _ if i == index => "*",
+            Segment::Id => "{}",
+            _ => segment.as_str(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Path with parameters named: `{id}` first, then after the preceding segment
+fn template_path(segments: &[String], kinds: &[Segment]) -> String {
+    let mut names: Vec<String> = Vec::new();
+    let mut parts = Vec::with_capacity(segments.len());
+    for (i, (segment, kind)) in segments.iter().zip(kinds).enumerate() {
+        if *kind == Segment::Literal {
+            parts.push(segment.clone());
+            continue;
+        }
+
+        let suffix = if *kind == Segment::Slug { "Slug" } else { "Id" };
+        let previous = i
+            .checked_sub(1)
+            .filter(|&p| kinds[p] == Segment::Literal)
+            .map(|p| segments[p].as_str());
+        let mut name = match previous {
+            _ if names.is_empty() => suffix.to_ascii_lowercase(),
+            Some(collection) if !collection.is_empty() => {
+                format!("{}{}", camel_case(&singular(collection)), suffix)
+            }
+            _ => format!("{}{}", suffix.to_ascii_lowercase(), names.len() + 1),
+        };
+        if names.contains(&name) {
+            name = format!("{}{}", name, names.len() + 1);
+        }
+        parts.push(format!("{{{}}}", name));
+        names.push(name);
+    }
+    parts.join("/")
+}
+
+/// Rough English singular of a collection name
+fn singular(word: &str) -> String {
+    if let Some(stem) = word.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if ["sses", "xes", "ches", "shes"]
+        .iter()
+        .any(|suffix| word.ends_with(suffix))
+    {
+        word[..word.len() - 2].to_string()
+    } else if word.ends_with('s') && !word.ends_with("ss") {
+        word[..word.len() - 1].to_string()
+    } else {
+        word.to_string()
+    }
+}
+
+/// `line-items` or `line_items` as `lineItems`
+fn camel_case(word: &str) -> String {
+    let mut parts = word.split(['-', '_', '.']).filter(|p| !p.is_empty());
+    let mut camel = parts.next().unwrap_or("").to_ascii_lowercase();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            camel.push(first.to_ascii_uppercase());
+            camel.push_str(&chars.as_str().to_ascii_lowercase());
+        }
+    }
+    camel
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ApiEndpoint, CrawlerConfig};
+
+    #[test]
+    fn test_infer_route_templates() {
+        let mut result =
+            CrawlResult::new("http://example.com".to_string(), &CrawlerConfig::default());
+        for href in [
+            "http://example.com/orders?page=2",
+            "http://example.com/orders/17",
+            "http://example.com/orders/18?expand=items",
+            "http://example.com/orders/17/items/3",
+            "http://example.com/orders/18/line-items/123e4567-e89b-12d3-a456-426614174000",
+            "http://example.com/categories/5/subcategories/9",
+            "http://example.com/posts/my-first-post",
+            "http://example.com/posts/hello-again-world",
+            "http://example.com/docs/getting-started-guide",
+            "http://example.com/commits/3f2a9c10ab",
+        ] {
+            result.add_endpoint(
+                ApiEndpoint::new(href.to_string(), 1)
+                    .with_parent(Some("http://example.com".to_string())),
+            );
+        }
+
+        infer_routes(&mut result);
+
+        let routes: Vec<(&str, usize)> = result
+            .routes
+            .iter()
+            .map(|r| (r.template.as_str(), r.count))
+            .collect();
+        assert_eq!(
+            routes,
+            [
+                (
+                    "http://example.com/categories/{id}/subcategories/{subcategoryId}",
+                    1
+                ),
+                ("http://example.com/commits/{id}", 1),
+                ("http://example.com/docs/getting-started-guide", 1),
+                ("http://example.com/orders", 1),
+                ("http://example.com/orders/{id}", 2),
+                ("http://example.com/orders/{id}/items/{itemId}", 1),
+                ("http://example.com/orders/{id}/line-items/{lineItemId}", 1),
+                ("http://example.com/posts/{slug}", 2),
+            ]
+        );
+        assert_eq!(
+            result.endpoints[2].template.as_deref(),
+            Some("http://example.com/orders/{id}")
+        );
+        assert_eq!(
+            result.url_mappings["http://example.com"][3]
+                .template
+                .as_deref(),
+            Some("http://example.com/orders/{id}/items/{itemId}")
+        );
+    }
+}
@@ -0,0 +1,340 @@
+//! Guarantees that crawling doesn't change server state
+//!
+//! The crawler only ever issues `GET` for discovered links, so links that
+//! advertise an unsafe method (`POST`, `DELETE`, ...) or carry a well-known
+//! destructive rel (`delete`, `logout`, ...) are never followed. Links without a
+//! rel, such as plain HTML anchors, are judged by their last path segment and
+//! title instead (`/api-auth/logout/`, "Delete account"). Links with an unsafe
+//! method can optionally be probed with `HEAD` or `OPTIONS` instead.
+//!
+//! [`SafetyConfig::read_only`] adds a hard guarantee below the crawler: a
+//! [`ReadOnlyTransport`] refuses every request that isn't `GET`, `HEAD` or
+//! `OPTIONS`, whichever code path it comes from.
+
+use crate::error::CrawlerError;
+use crate::transport::{Transport, TransportFuture, TransportRequest};
+use crate::types::ApiEndpoint;
+use reqwest::Method;
+use serde::Deserialize;
+use std::collections::HashSet;
+use tracing::warn;
+use url::Url;
+
+/// Rels that name state-changing actions; links carrying them are never followed
+pub const DESTRUCTIVE_RELS: &[&str] = &[
+    "delete",
+    "remove",
+    "destroy",
+    "purge",
+    "cancel",
+    "revoke",
+    "reset",
+    "deactivate",
+    "disable",
+    "unsubscribe",
+    "logout",
+    "log-out",
+    "signout",
+    "sign-out",
+];
+
+/// Method used to probe links that advertise an unsafe method
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProbeMethod {
+    Head,
+    Options,
+}
+
+impl ProbeMethod {
+    /// The HTTP method sent
+    pub fn method(self) -> Method {
+        match self {
+            Self::Head => Method::HEAD,
+            Self::Options => Method::OPTIONS,
+        }
+    }
+}
+
+/// Safety settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SafetyConfig {
+    /// Refuse every request that isn't GET, HEAD or OPTIONS at the transport layer
+    pub read_only: bool,
+
+    /// Probe links advertising unsafe methods with HEAD or OPTIONS instead of skipping them
+    pub probe_unsafe: Option<ProbeMethod>,
+
+    /// Additional rels to treat as destructive
+    pub destructive_rels: Vec<String>,
+}
+
+/// What to do with a discovered link
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkSafety {
+    /// Safe to fetch with GET
+    Safe,
+    /// Advertises an unsafe method; probe it with this method
+    Probe(ProbeMethod),
+    /// Advertises an unsafe method and probing is off
+    UnsafeMethod,
+    /// Carries a destructive rel
+    DestructiveRel,
+}
+
+/// Whether the crawler may send a method: GET, HEAD or OPTIONS
+///
+/// TRACE is safe in the RFC 9110 sense but echoes request headers back, so it's
+/// excluded along with every method that changes state.
+pub fn is_safe_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+impl SafetyConfig {
+    /// Classify a discovered link
+    pub fn check(&self, endpoint: &ApiEndpoint) -> LinkSafety {
+        if let Some(rels) = &endpoint.rel
+            && rels
+                .split_whitespace()
+                .any(|rel| self.is_destructive_rel(rel))
+        {
+            return LinkSafety::DestructiveRel;
+        }
+        if endpoint.rel.is_none() && self.names_destructive_action(endpoint) {
+            return LinkSafety::DestructiveRel;
+        }
+
+        let method = endpoint
+            .method
+            .as_deref()
+            .map(|m| Method::from_bytes(m.trim().to_ascii_uppercase().as_bytes()));
+        match method {
+            None => LinkSafety::Safe,
+            Some(Ok(method)) if is_safe_method(&method) => LinkSafety::Safe,
+            // Unknown or malformed methods are treated as unsafe
+            Some(_) => match self.probe_unsafe {
+                Some(probe) => LinkSafety::Probe(probe),
+                None => LinkSafety::UnsafeMethod,
+            },
+        }
+    }
+
+    /// Whether a rel-less link's last path segment or title names a destructive action
+    fn names_destructive_action(&self, endpoint: &ApiEndpoint) -> bool {
+        let segment = Url::parse(&endpoint.href).ok().and_then(|url| {
+            url.path_segments()?
+                .rfind(|segment| !segment.is_empty())
+                .map(|segment| segment.replace('_', "-"))
+        });
+        let words: Vec<String> = endpoint
+            .title
+            .iter()
+            .flat_map(|title| title.split(|c: char| c.is_whitespace() || c == '_'))
+            .filter(|word| !word.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        segment.is_some_and(|segment| self.is_destructive_rel(&segment))
+            || words.iter().any(|word| self.is_destructive_rel(word))
+            || (words.len() > 1 && self.is_destructive_rel(&words.join("-")))
+    }
+
+    /// Whether a single rel is destructive; extension rels match on their last segment
+    fn is_destructive_rel(&self, rel: &str) -> bool {
+        let rel = rel.to_ascii_lowercase();
+        let name = rel.rsplit(['/', ':', '#']).next().unwrap_or(&rel);
+        DESTRUCTIVE_RELS.contains(&name)
+            || self
+                .destructive_rels
+                .iter()
+                .any(|extra| extra.eq_ignore_ascii_case(name) || extra.eq_ignore_ascii_case(&rel))
+    }
+}
+
+/// Transport wrapper that refuses any request with a method other than GET, HEAD or OPTIONS
+///
+/// Explicitly allowed URLs (such as a configured OAuth2 token endpoint) may be
+/// POSTed to; nothing else can be.
+pub struct ReadOnlyTransport {
+    inner: Box<dyn Transport>,
+    allowed_posts: HashSet<String>,
+}
+
+impl ReadOnlyTransport {
+    /// Wrap a transport
+    pub fn new(inner: Box<dyn Transport>) -> Self {
+        Self {
+            inner,
+            allowed_posts: HashSet::new(),
+        }
+    }
+
+    /// Allow POST requests to exactly this URL
+    pub fn allow_post(mut self, url: impl Into<String>) -> Self {
+        self.allowed_posts.insert(url.into());
+        self
+    }
+
+    /// Check a request against the read-only policy
+    pub fn permits(&self, request: &TransportRequest) -> bool {
+        is_safe_method(&request.method)
+            || (request.method == Method::POST && self.allowed_posts.contains(&request.url))
+    }
+}
+
+impl Transport for ReadOnlyTransport {
+    fn fetch<'a>(&'a self, request: &'a TransportRequest) -> TransportFuture<'a> {
+        if !self.permits(request) {
+            warn!("Read-only mode blocked {} {}", request.method, request.url);
+            return Box::pin(async move {
+                Err(CrawlerError::UnsafeRequest {
+                    method: request.method.to_string(),
+                    url: request.url.clone(),
+                })
+            });
+        }
+        self.inner.fetch(request)
+    }
+}
+
+/// Reject configurations whose explicit requests would be blocked in read-only mode
+pub(crate) fn check_read_only(login_method: Option<&str>, problems: &mut Vec<String>) {
+    if let Some(method) = login_method
+        && !Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+            .is_ok_and(|m| is_safe_method(&m))
+    {
+        problems.push(format!(
+            "safety.read_only: login uses {} which read-only mode forbids",
+            method.to_ascii_uppercase()
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::stub::StubTransport;
+
+    fn link(rel: Option<&str>, method: Option<&str>) -> ApiEndpoint {
+        let mut endpoint =
+            ApiEndpoint::new("http://example.com/x".to_string(), 1).with_rel(rel.map(String::from));
+        endpoint.method = method.map(String::from);
+        endpoint
+    }
+
+    #[test]
+    fn test_link_classification() {
+        let safety = SafetyConfig::default();
+        assert_eq!(safety.check(&link(Some("next"), None)), LinkSafety::Safe);
+        assert_eq!(safety.check(&link(None, Some("get"))), LinkSafety::Safe);
+        assert_eq!(
+            safety.check(&link(None, Some("DELETE"))),
+            LinkSafety::UnsafeMethod
+        );
+        assert_eq!(
+            safety.check(&link(None, Some("TRACE"))),
+            LinkSafety::UnsafeMethod
+        );
+        assert_eq!(
+            safety.check(&link(None, Some("not a method"))),
+            LinkSafety::UnsafeMethod
+        );
+        assert_eq!(
+            safety.check(&link(Some("ea:logout"), None)),
+            LinkSafety::DestructiveRel
+        );
+        assert_eq!(
+            safety.check(&link(Some("https://example.com/rels/Delete"), None)),
+            LinkSafety::DestructiveRel
+        );
+
+        // Rel-less links are judged by their last path segment and title
+        let anchor = |href: &str, title: Option<&str>| {
+            let mut endpoint = ApiEndpoint::new(href.to_string(), 1);
+            endpoint.title = title.map(String::from);
+            endpoint
+        };
+        for destructive in [
+            anchor(
+                "http://example.com/api-auth/logout/?next=/api/",
+                Some("Log in"),
+            ),
+            anchor("http://example.com/users/1/delete", None),
+            anchor("http://example.com/session", Some("Log out")),
+            anchor("http://example.com/account", Some("Delete account")),
+        ] {
+            assert_eq!(
+                safety.check(&destructive),
+                LinkSafety::DestructiveRel,
+                "{}",
+                destructive.href
+            );
+        }
+        assert_eq!(
+            safety.check(&anchor("http://example.com/deleted-items", Some("Trash"))),
+            LinkSafety::Safe
+        );
+
+        let probing = SafetyConfig {
+            probe_unsafe: Some(ProbeMethod::Options),
+            destructive_rels: vec!["archive".to_string()],
+            ..SafetyConfig::default()
+        };
+        assert_eq!(
+            probing.check(&link(None, Some("POST"))),
+            LinkSafety::Probe(ProbeMethod::Options)
+        );
+        assert_eq!(
+            probing.check(&link(Some("archive"), Some("GET"))),
+            LinkSafety::DestructiveRel
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_only_transport_blocks_unsafe_methods() {
+        let inner = StubTransport::new();
+        let seen = inner.log();
+        let transport =
+            ReadOnlyTransport::new(Box::new(inner)).allow_post("https://auth.example.com/token");
+
+        for method in [Method::GET, Method::HEAD, Method::OPTIONS] {
+            let mut request = TransportRequest::get("http://example.com/items");
+            request.method = method;
+            assert!(transport.fetch(&request).await.is_ok());
+        }
+
+        for method in [
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::TRACE,
+            Method::from_bytes(b"PURGE").unwrap(),
+        ] {
+            let mut request = TransportRequest::get("http://example.com/items");
+            request.method = method.clone();
+            let error = transport.fetch(&request).await.unwrap_err();
+            assert!(
+                matches!(&error, CrawlerError::UnsafeRequest { method: m, .. } if *m == method.as_str()),
+                "{} was not blocked",
+                method
+            );
+        }
+
+        let mut token = TransportRequest::get("https://auth.example.com/token");
+        token.method = Method::POST;
+        assert!(transport.fetch(&token).await.is_ok());
+
+        // Only the permitted requests reached the inner transport
+        assert_eq!(
+            seen.lines(),
+            [
+                "GET http://example.com/items",
+                "HEAD http://example.com/items",
+                "OPTIONS http://example.com/items",
+                "POST https://auth.example.com/token",
+            ]
+        );
+    }
+}
@@ -0,0 +1,389 @@
+//! Sampling of large collections
+//!
+//! Links that differ only in an ID-like path segment (`/users/1`, `/users/2`,
+//! `/orders/3f2a…`) are instances of one pattern (`/users/{id}`). With a sample
+//! size set, only that many instances of each pattern are followed, chosen in
+//! discovery order or at random. The rest are left out of the results, which
+//! report each pattern with an estimated instance count instead.
+
+use crate::types::ApiEndpoint;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use url::Url;
+
+/// Placeholder replacing ID-like segments in a pattern
+pub const ID_PLACEHOLDER: &str = "{id}";
+
+/// Objects searched for a reported total besides the top level
+const TOTAL_CONTAINERS: &[&str] = &["meta", "pagination", "paging", "page_info", "pageInfo"];
+
+/// Fields holding the total number of items in a collection
+const TOTAL_FIELDS: &[&str] = &[
+    "total",
+    "total_count",
+    "totalCount",
+    "total_items",
+    "totalItems",
+    "total_results",
+    "totalResults",
+    "count",
+];
+
+/// How instances of a pattern are chosen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SampleStrategy {
+    /// The first instances discovered
+    #[default]
+    First,
+    /// Instances chosen at random from each response
+    Random,
+}
+
+/// Collection sampling settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SamplingConfig {
+    /// Instances followed per pattern (0 means every instance)
+    pub sample_size: usize,
+
+    /// How the instances are chosen
+    pub strategy: SampleStrategy,
+
+    /// Seed for random sampling, for reproducible crawls
+    pub seed: Option<u64>,
+}
+
+/// A pattern whose instances were sampled
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SampledPattern {
+    /// URL with ID-like segments replaced by `{id}`
+    pub pattern: String,
+
+    /// Instances discovered, or the collection's reported total if larger
+    pub estimated_count: usize,
+
+    /// Instances followed
+    pub sampled: Vec<String>,
+
+    /// Instances discovered but not followed
+    pub skipped: usize,
+}
+
+/// Whether a path segment looks like an identifier rather than a name
+///
+/// Numbers, UUIDs, hex strings of 8 or more characters and long tokens mixing
+/// letters and digits count as IDs.
+pub fn is_id_segment(segment: &str) -> bool {
+    let bytes = segment.as_bytes();
+    let has_digit = bytes.iter().any(u8::is_ascii_digit);
+    let has_alpha = bytes.iter().any(u8::is_ascii_alphabetic);
+    !segment.is_empty()
+        && (bytes.iter().all(u8::is_ascii_digit)
+            || is_uuid(segment)
+            || (bytes.len() >= 8 && has_digit && bytes.iter().all(u8::is_ascii_hexdigit))
+            || (bytes.len() >= 16
+                && has_digit
+                && has_alpha
+                && bytes
+                    .iter()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_'))))
+}
+
+/// Whether a string is a hyphenated UUID
+pub fn is_uuid(s: &str) -> bool {
+    let groups: Vec<&str> = s.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// The pattern a URL is an instance of, if any path segment is ID-like
+///
+/// The query and fragment are dropped: `http://example.com/users/42?expand=1`
+/// is an instance of `http://example.com/users/{id}`.
+pub fn pattern_of(url: &str) -> Option<String> {
+    let mut url = Url::parse(url).ok()?;
+    let segments: Vec<&str> = url.path_segments()?.collect();
+    if !segments.iter().any(|s| is_id_segment(s)) {
+        return None;
+    }
+    let path = segments
+        .iter()
+        .map(|s| if is_id_segment(s) { ID_PLACEHOLDER } else { s })
+        .collect::<Vec<_>>()
+        .join("/");
+    url.set_query(None);
+    url.set_fragment(None);
+    url.set_path("");
+    Some(format!("{}/{}", url.as_str().trim_end_matches('/'), path))
+}
+
+/// The pattern of a collection's own items, whose count the collection reports
+///
+/// `http://example.com/users?page=2` holds `http://example.com/users/{id}`.
+pub fn item_pattern(collection: &str) -> Option<String> {
+    let mut url = Url::parse(collection).ok()?;
+    let path = url
+        .path_segments()?
+        .filter(|s| !s.is_empty())
+        .map(|s| if is_id_segment(s) { ID_PLACEHOLDER } else { s })
+        .chain([ID_PLACEHOLDER])
+        .collect::<Vec<_>>()
+        .join("/");
+    url.set_query(None);
+    url.set_fragment(None);
+    url.set_path("");
+    Some(format!("{}/{}", url.as_str().trim_end_matches('/'), path))
+}
+
+/// The total item count a collection response reports, if any
+pub fn reported_total(json: &Value) -> Option<usize> {
+    let root = json.as_object()?;
+    std::iter::once(root)
+        .chain(
+            TOTAL_CONTAINERS
+                .iter()
+                .filter_map(|key| root.get(*key)?.as_object()),
+        )
+        .find_map(|container| {
+            TOTAL_FIELDS
+                .iter()
+                .find_map(|field| container.get(*field)?.as_u64())
+        })
+        .map(|total| total as usize)
+}
+
+/// Chooses which instances of each pattern to follow over a crawl
+#[derive(Debug)]
+pub struct Sampler {
+    config: SamplingConfig,
+    rng: fastrand::Rng,
+    patterns: IndexMap<String, PatternState>,
+}
+
+/// Instances of one pattern seen so far
+#[derive(Debug, Default)]
+struct PatternState {
+    seen: HashSet<String>,
+    sampled: Vec<String>,
+    reported_total: usize,
+}
+
+impl Sampler {
+    /// Create a sampler; random sampling is seeded from `seed` when set
+    pub fn new(config: SamplingConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => fastrand::Rng::with_seed(seed),
+            None => fastrand::Rng::new(),
+        };
+        Self {
+            config,
+            rng,
+            patterns: IndexMap::new(),
+        }
+    }
+
+    /// Choose the links of one response to follow, returning the hrefs left out
+    ///
+    /// `candidates` are the links that would otherwise be followed; `total` is
+    /// the collection URL and item count the response reports, used to estimate
+    /// the size of the collection's own item pattern only.
+    pub fn select(
+        &mut self,
+        candidates: &[&ApiEndpoint],
+        total: Option<(&str, usize)>,
+    ) -> HashSet<String> {
+        let mut left_out = HashSet::new();
+        if self.config.sample_size == 0 {
+            return left_out;
+        }
+        let total = total.and_then(|(collection, total)| Some((item_pattern(collection)?, total)));
+
+        let mut new_instances: IndexMap<String, Vec<String>> = IndexMap::new();
+        for endpoint in candidates {
+            let Some(pattern) = pattern_of(&endpoint.href) else {
+                continue;
+            };
+            let state = self.patterns.entry(pattern.clone()).or_default();
+            if let Some((items, total)) = &total
+                && pattern.trim_end_matches('/') == items
+            {
+                state.reported_total = state.reported_total.max(*total);
+            }
+            if state.seen.insert(endpoint.href.clone()) {
+                new_instances
+                    .entry(pattern)
+                    .or_default()
+                    .push(endpoint.href.clone());
+            } else if !state.sampled.contains(&endpoint.href) {
+                left_out.insert(endpoint.href.clone());
+            }
+        }
+
+        for (pattern, mut hrefs) in new_instances {
+            let state = &mut self.patterns[&pattern];
+            let budget = self.config.sample_size.saturating_sub(state.sampled.len());
+            if self.config.strategy == SampleStrategy::Random && hrefs.len() > budget {
+                self.rng.shuffle(&mut hrefs);
+            }
+            let rest = hrefs.split_off(budget.min(hrefs.len()));
+            state.sampled.extend(hrefs);
+            left_out.extend(rest);
+        }
+        left_out
+    }
+
+    /// Whether a URL is an instance of a pattern whose sample is full and isn't in it
+    pub fn is_sampled_out(&self, href: &str) -> bool {
+        self.config.sample_size > 0
+            && pattern_of(href)
+                .and_then(|pattern| self.patterns.get(&pattern))
+                .is_some_and(|state| {
+                    state.sampled.len() >= self.config.sample_size
+                        && !state.sampled.iter().any(|sampled| sampled == href)
+                })
+    }
+
+    /// Patterns whose instances were cut down to the sample, in discovery order
+    pub fn sampled_patterns(&self) -> Vec<SampledPattern> {
+        self.patterns
+            .iter()
+            .filter(|(_, state)| state.seen.len() > state.sampled.len())
+            .map(|(pattern, state)| SampledPattern {
+                pattern: pattern.clone(),
+                estimated_count: state.seen.len().max(state.reported_total),
+                sampled: state.sampled.clone(),
+                skipped: state.seen.len() - state.sampled.len(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_id_segments_and_patterns() {
+        for id in [
+            "42",
+            "3f2a9c10",
+            "123e4567-e89b-12d3-a456-426614174000",
+            "507f1f77bcf86cd799439011",
+            "cus_9s6XKzkNRiz8i3b2",
+        ] {
+            assert!(is_id_segment(id), "{}", id);
+        }
+        for name in ["users", "v2", "deadbeef", "me", "", "line-items"] {
+            assert!(!is_id_segment(name), "{}", name);
+        }
+
+        assert_eq!(
+            pattern_of("http://example.com/users/42/posts/7?expand=1").as_deref(),
+            Some("http://example.com/users/{id}/posts/{id}")
+        );
+        assert_eq!(
+            pattern_of("https://example.com:8443/v2/orders/3f2a9c10/").as_deref(),
+            Some("https://example.com:8443/v2/orders/{id}/")
+        );
+        assert_eq!(pattern_of("http://example.com/users/me"), None);
+
+        assert_eq!(
+            reported_total(&json!({"count": 50000, "results": []})),
+            Some(50000)
+        );
+        assert_eq!(
+            reported_total(&json!({"data": [], "meta": {"totalCount": 7}})),
+            Some(7)
+        );
+        assert_eq!(reported_total(&json!({"items": []})), None);
+
+        assert_eq!(
+            item_pattern("http://example.com/teams/7/users/?page=2").as_deref(),
+            Some("http://example.com/teams/{id}/users/{id}")
+        );
+    }
+
+    #[test]
+    fn test_sampler_budget_per_pattern() {
+        let users: Vec<ApiEndpoint> = (1..=6)
+            .map(|i| ApiEndpoint::new(format!("http://example.com/users/{}", i), 1))
+            .chain([ApiEndpoint::new(
+                "http://example.com/users/me".to_string(),
+                1,
+            )])
+            .collect();
+        let page1: Vec<&ApiEndpoint> = users[..4].iter().chain(&users[6..]).collect();
+        let page2: Vec<&ApiEndpoint> = users[2..6].iter().collect();
+
+        let mut sampler = Sampler::new(SamplingConfig {
+            sample_size: 2,
+            ..SamplingConfig::default()
+        });
+        let left_out = sampler.select(&page1, Some(("http://example.com/users?page=1", 500)));
+        let mut left_out: Vec<String> = left_out.into_iter().collect();
+        left_out.sort();
+        assert_eq!(
+            left_out,
+            ["http://example.com/users/3", "http://example.com/users/4"]
+        );
+        // Instances left out before stay left out and aren't counted twice
+        assert_eq!(sampler.select(&page2, None).len(), 4);
+
+        let patterns = sampler.sampled_patterns();
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(patterns[0].pattern, "http://example.com/users/{id}");
+        assert_eq!(
+            patterns[0].sampled,
+            ["http://example.com/users/1", "http://example.com/users/2"]
+        );
+        assert_eq!((patterns[0].skipped, patterns[0].estimated_count), (4, 500));
+
+        // A reported total only applies to the collection's own items
+        let mut sampler = Sampler::new(SamplingConfig {
+            sample_size: 1,
+            ..SamplingConfig::default()
+        });
+        let links: Vec<ApiEndpoint> = ["users/1", "users/2", "teams/1", "teams/2"]
+            .iter()
+            .map(|path| ApiEndpoint::new(format!("http://example.com/{}", path), 1))
+            .collect();
+        let links: Vec<&ApiEndpoint> = links.iter().collect();
+        sampler.select(&links, Some(("http://example.com/users", 500)));
+        let estimates: Vec<(String, usize)> = sampler
+            .sampled_patterns()
+            .into_iter()
+            .map(|p| (p.pattern, p.estimated_count))
+            .collect();
+        assert_eq!(
+            estimates,
+            [
+                ("http://example.com/users/{id}".to_string(), 500),
+                ("http://example.com/teams/{id}".to_string(), 2)
+            ]
+        );
+
+        // Seeded random samples are reproducible
+        let config = SamplingConfig {
+            sample_size: 2,
+            strategy: SampleStrategy::Random,
+            seed: Some(7),
+        };
+        let candidates: Vec<&ApiEndpoint> = users.iter().collect();
+        let mut first = Sampler::new(config.clone());
+        let mut second = Sampler::new(config);
+        first.select(&candidates, None);
+        second.select(&candidates, None);
+        assert_eq!(
+            first.sampled_patterns()[0].sampled,
+            second.sampled_patterns()[0].sampled
+        );
+        assert_eq!(first.sampled_patterns()[0].sampled.len(), 2);
+    }
+}
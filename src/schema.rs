@@ -0,0 +1,560 @@
+//! JSON Schema inference from response bodies
+//!
+//! With inference enabled, every JSON body fetched during the crawl is merged
+//! into a [`Shape`] for its URL (query dropped). After the crawl, shapes of URLs
+//! sharing a route template are merged again and each route gets a JSON Schema
+//! draft 2020-12 document: types (several when values disagree), properties
+//! required when present in every object seen, `enum` for strings repeating a
+//! few values and `format` for strings that are all date-times, UUIDs or URIs.
+
+use crate::error::Result;
+use crate::sampling::is_uuid;
+use crate::types::CrawlResult;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::info;
+use url::Url;
+
+/// JSON Schema dialect of the inferred documents
+pub const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Strings longer than this are never enum values
+const MAX_ENUM_VALUE_LEN: usize = 64;
+
+/// Schema inference settings
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchemaConfig {
+    /// Infer a JSON Schema for each route from the JSON bodies it returned
+    pub infer: bool,
+
+    /// Most distinct values a string may take to be listed as an `enum`
+    pub max_enum_values: usize,
+}
+
+impl Default for SchemaConfig {
+    fn default() -> Self {
+        Self {
+            infer: false,
+            max_enum_values: 10,
+        }
+    }
+}
+
+/// The inferred schema of one route's responses
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EndpointSchema {
+    /// Route template, or the URL for URLs matching no template
+    pub route: String,
+
+    /// Response bodies merged into the schema
+    pub samples: usize,
+
+    /// JSON Schema draft 2020-12 document
+    pub schema: Value,
+}
+
+/// Everything seen so far at one position of a JSON document
+#[derive(Debug, Clone, Default)]
+pub struct Shape {
+    samples: usize,
+    null: bool,
+    boolean: bool,
+    integer: bool,
+    number: bool,
+    string: Option<StringShape>,
+    array: Option<Box<Shape>>,
+    object: Option<ObjectShape>,
+}
+
+/// Strings seen at one position
+#[derive(Debug, Clone)]
+struct StringShape {
+    count: usize,
+    /// Distinct values, dropped once there are too many for an enum
+    values: Option<BTreeSet<String>>,
+    date_time: bool,
+    uuid: bool,
+    uri: bool,
+}
+
+/// Objects seen at one position
+#[derive(Debug, Clone, Default)]
+struct ObjectShape {
+    count: usize,
+    properties: IndexMap<String, Shape>,
+}
+
+impl Shape {
+    /// Merge one value into the shape
+    pub fn merge(&mut self, value: &Value, max_enum_values: usize) {
+        self.samples += 1;
+        match value {
+            Value::Null => self.null = true,
+            Value::Bool(_) => self.boolean = true,
+            Value::Number(n) if n.is_f64() => self.number = true,
+            Value::Number(_) => self.integer = true,
+            Value::String(s) => self
+                .string
+                .get_or_insert_with(StringShape::new)
+                .merge(s, max_enum_values),
+            Value::Array(items) => {
+                let shape = self.array.get_or_insert_default();
+                for item in items {
+                    shape.merge(item, max_enum_values);
+                }
+            }
+            Value::Object(map) => {
+                let object = self.object.get_or_insert_default();
+                object.count += 1;
+                for (key, value) in map {
+                    object
+                        .properties
+                        .entry(key.clone())
+                        .or_default()
+                        .merge(value, max_enum_values);
+                }
+            }
+        }
+    }
+
+    /// Merge another shape, as if its values had been merged into this one
+    pub fn absorb(&mut self, other: Shape, max_enum_values: usize) {
+        self.samples += other.samples;
+        self.null |= other.null;
+        self.boolean |= other.boolean;
+        self.integer |= other.integer;
+        self.number |= other.number;
+        if let Some(string) = other.string {
+            match &mut self.string {
+                Some(existing) => existing.absorb(string, max_enum_values),
+                None => self.string = Some(string),
+            }
+        }
+        if let Some(items) = other.array {
+            match &mut self.array {
+                Some(existing) => existing.absorb(*items, max_enum_values),
+                None => self.array = Some(items),
+            }
+        }
+        if let Some(object) = other.object {
+            let existing = self.object.get_or_insert_default();
+            existing.count += object.count;
+            for (key, shape) in object.properties {
+                existing
+                    .properties
+                    .entry(key)
+                    .or_default()
+                    .absorb(shape, max_enum_values);
+            }
+        }
+    }
+
+    /// The JSON Schema describing every value merged so far
+    pub fn to_schema(&self) -> Value {
+        let mut schema = Map::new();
+        let mut types = Vec::new();
+        if let Some(object) = &self.object {
+            types.push("object");
+            let properties: Map<String, Value> = object
+                .properties
+                .iter()
+                .map(|(key, shape)| (key.clone(), shape.to_schema()))
+                .collect();
+            schema.insert("properties".to_string(), Value::Object(properties));
+            let required: Vec<&String> = object
+                .properties
+                .iter()
+                .filter(|(_, shape)| shape.samples == object.count)
+                .map(|(key, _)| key)
+                .collect();
+            if !required.is_empty() {
+                schema.insert("required".to_string(), json!(required));
+            }
+        }
+        if let Some(items) = &self.array {
+            types.push("array");
+            if items.samples > 0 {
+                schema.insert("items".to_string(), items.to_schema());
+            }
+        }
+        if let Some(string) = &self.string {
+            types.push("string");
+            // An enum would reject every other type, so it's only used when strings
+            // (or null) are all a field holds
+            let only_strings = self.object.is_none()
+                && self.array.is_none()
+                && !self.number
+                && !self.integer
+                && !self.boolean;
+            if let Some(format) = string.format() {
+                schema.insert("format".to_string(), json!(format));
+            } else if let Some(values) = string.enum_values().filter(|_| only_strings) {
+                let mut values: Vec<Value> = values.iter().map(|v| json!(v)).collect();
+                if self.null {
+                    values.push(Value::Null);
+                }
+                schema.insert("enum".to_string(), Value::Array(values));
+            }
+        }
+        if self.number {
+            types.push("number");
+        } else if self.integer {
+            types.push("integer");
+        }
+        if self.boolean {
+            types.push("boolean");
+        }
+        if self.null {
+            types.push("null");
+        }
+        match types.as_slice() {
+            [] => {}
+            [single] => {
+                schema.insert("type".to_string(), json!(single));
+            }
+            _ => {
+                schema.insert("type".to_string(), json!(types));
+            }
+        }
+        Value::Object(schema)
+    }
+}
+
+impl StringShape {
+    fn new() -> Self {
+        Self {
+            count: 0,
+            values: Some(BTreeSet::new()),
+            date_time: true,
+            uuid: true,
+            uri: true,
+        }
+    }
+
+    fn merge(&mut self, value: &str, max_enum_values: usize) {
+        self.count += 1;
+        self.date_time &= chrono::DateTime::parse_from_rfc3339(value).is_ok();
+        self.uuid &= is_uuid(value);
+        self.uri &= Url::parse(value).is_ok_and(|url| url.has_host());
+        if let Some(values) = &mut self.values {
+            values.insert(value.to_string());
+            if value.len() > MAX_ENUM_VALUE_LEN || values.len() > max_enum_values {
+                self.values = None;
+            }
+        }
+    }
+
+    fn absorb(&mut self, other: StringShape, max_enum_values: usize) {
+        self.count += other.count;
+        self.date_time &= other.date_time;
+        self.uuid &= other.uuid;
+        self.uri &= other.uri;
+        self.values = match (self.values.take(), other.values) {
+            (Some(mut values), Some(other)) => {
+                values.extend(other);
+                (values.len() <= max_enum_values).then_some(values)
+            }
+            _ => None,
+        };
+    }
+
+    /// The format every string matched, if any
+    fn format(&self) -> Option<&'static str> {
+        if self.uuid {
+            Some("uuid")
+        } else if self.date_time {
+            Some("date-time")
+        } else if self.uri {
+            Some("uri")
+        } else {
+            None
+        }
+    }
+
+    /// The distinct values, when few enough and repeated enough to be an enum
+    fn enum_values(&self) -> Option<&BTreeSet<String>> {
+        self.values
+            .as_ref()
+            .filter(|values| self.count > values.len())
+    }
+}
+
+/// Key a response body's shape is recorded under: the URL without query or fragment
+pub fn shape_key(url: &str) -> Option<String> {
+    let mut url = Url::parse(url).ok()?;
+    url.set_query(None);
+    url.set_fragment(None);
+    Some(url.to_string())
+}
+
+/// A complete schema document for a route
+pub fn schema_document(route: &str, shape: &Shape) -> Value {
+    let mut schema = shape.to_schema();
+    if let Value::Object(map) = &mut schema {
+        map.insert("$schema".to_string(), json!(SCHEMA_DIALECT));
+        map.insert("title".to_string(), json!(route));
+    }
+    schema
+}
+
+/// Merge the shapes recorded per URL into one schema per route template
+///
+/// Templates come from the endpoints' `template`, so this runs after route
+/// inference; URLs matching no template keep a schema of their own.
+pub fn build_schemas(
+    shapes: HashMap<String, Shape>,
+    result: &CrawlResult,
+    config: &SchemaConfig,
+) -> Vec<EndpointSchema> {
+    let templates: HashMap<String, &str> = result
+        .endpoints
+        .iter()
+        .filter_map(|e| Some((shape_key(&e.href)?, e.template.as_deref()?)))
+        .collect();
+
+    let mut routes: BTreeMap<String, Shape> = BTreeMap::new();
+    for (url, shape) in shapes {
+        let route = templates.get(&url).map_or(url, |t| t.to_string());
+        match routes.entry(route) {
+            Entry::Vacant(entry) => {
+                entry.insert(shape);
+            }
+            Entry::Occupied(mut entry) => entry.get_mut().absorb(shape, config.max_enum_values),
+        }
+    }
+
+    routes
+        .into_iter()
+        .map(|(route, shape)| EndpointSchema {
+            schema: schema_document(&route, &shape),
+            samples: shape.samples,
+            route,
+        })
+        .collect()
+}
+
+/// File name stem for a route, e.g. `example.com_orders_id` for `http://example.com/orders/{id}`
+fn file_stem(route: &str) -> String {
+    let route = route.split_once("://").map_or(route, |(_, rest)| rest);
+    let mut stem = String::new();
+    for c in route.chars() {
+        if c.is_ascii_alphanumeric() || matches!(c, '-' | '.') {
+            stem.push(c);
+        } else if !matches!(c, '{' | '}') && !stem.ends_with('_') {
+            stem.push('_');
+        }
+    }
+    match stem.trim_matches('_') {
+        "" => "schema".to_string(),
+        stem => stem.to_string(),
+    }
+}
+
+/// Write each schema to `<dir>/<route>.schema.json`, returning the paths written
+pub fn save_schemas<P: AsRef<Path>>(schemas: &[EndpointSchema], dir: P) -> Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+
+    let mut names = HashSet::new();
+    let mut paths = Vec::new();
+    for schema in schemas {
+        let stem = file_stem(&schema.route);
+        let mut name = format!("{}.schema.json", stem);
+        let mut suffix = 2;
+        while !names.insert(name.clone()) {
+            name = format!("{}_{}.schema.json", stem, suffix);
+            suffix += 1;
+        }
+        let path = dir.join(name);
+        fs::write(&path, serde_json::to_string_pretty(&schema.schema)?)?;
+        paths.push(path);
+    }
+    info!("{} JSON schemas saved to: {}", paths.len(), dir.display());
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ApiEndpoint, CrawlerConfig};
+
+    #[test]
+    fn test_shape_to_schema() {
+        let mut shape = Shape::default();
+        shape.merge(
+            &json!({
+                "items": [
+                    {"id": "123e4567-e89b-12d3-a456-426614174000", "status": "open", "total": 5,
+                     "created_at": "2024-05-01T10:00:00Z", "href": "https://example.com/orders/1"},
+                    {"id": "223e4567-e89b-12d3-a456-426614174000", "status": "open", "total": 2.5,
+                     "created_at": "2024-05-02T10:00:00+02:00", "href": "https://example.com/orders/2",
+                     "note": null},
+                    {"id": "323e4567-e89b-12d3-a456-426614174000", "status": "closed", "total": 1,
+                     "created_at": "2024-05-03T10:00:00Z", "href": "https://example.com/orders/3",
+                     "note": "gift"}
+                ],
+                "count": 3
+            }),
+            10,
+        );
+
+        let schema = schema_document("http://example.com/orders", &shape);
+        assert_eq!(schema["$schema"], SCHEMA_DIALECT);
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], json!(["count", "items"]));
+        let item = &schema["properties"]["items"]["items"];
+        assert_eq!(
+            item["required"],
+            json!(["created_at", "href", "id", "status", "total"])
+        );
+        assert_eq!(item["properties"]["id"]["format"], "uuid");
+        assert_eq!(item["properties"]["created_at"]["format"], "date-time");
+        assert_eq!(item["properties"]["href"]["format"], "uri");
+        assert_eq!(
+            item["properties"]["status"]["enum"],
+            json!(["closed", "open"])
+        );
+        assert_eq!(item["properties"]["total"]["type"], "number");
+        assert_eq!(
+            item["properties"]["note"]["type"],
+            json!(["string", "null"])
+        );
+        assert!(item["properties"]["note"].get("enum").is_none());
+        assert_eq!(schema["properties"]["count"]["type"], "integer");
+
+        // Too many distinct values for an enum
+        let mut shape = Shape::default();
+        for name in ["a", "b", "c", "a"] {
+            shape.merge(&json!(name), 2);
+        }
+        assert_eq!(shape.to_schema(), json!({"type": "string"}));
+    }
+
+    /// Whether a value is valid against the subset of JSON Schema `to_schema` emits
+    fn is_valid(schema: &Value, value: &Value) -> bool {
+        let type_matches = |name: &Value| match name.as_str() {
+            Some("object") => value.is_object(),
+            Some("array") => value.is_array(),
+            Some("string") => value.is_string(),
+            Some("number") => value.is_number(),
+            Some("integer") => value.is_i64() || value.is_u64(),
+            Some("boolean") => value.is_boolean(),
+            Some("null") => value.is_null(),
+            _ => false,
+        };
+        let types_ok = match &schema["type"] {
+            Value::Null => true,
+            Value::Array(names) => names.iter().any(type_matches),
+            name => type_matches(name),
+        };
+        let enum_ok = schema["enum"]
+            .as_array()
+            .is_none_or(|values| values.contains(value));
+        let object_ok = value.as_object().is_none_or(|object| {
+            let required = schema["required"].as_array().into_iter().flatten();
+            required
+                .filter_map(Value::as_str)
+                .all(|key| object.contains_key(key))
+                && object.iter().all(|(key, field)| {
+                    schema["properties"]
+                        .get(key)
+                        .is_none_or(|field_schema| is_valid(field_schema, field))
+                })
+        });
+        let array_ok = value.as_array().is_none_or(|items| {
+            schema
+                .get("items")
+                .is_none_or(|item_schema| items.iter().all(|item| is_valid(item_schema, item)))
+        });
+        types_ok && enum_ok && object_ok && array_ok
+    }
+
+    #[test]
+    fn test_schema_accepts_its_own_samples() {
+        let samples = [
+            json!({"id": 1, "status": "open", "code": "A", "tags": ["x"], "owner": {"name": "a"}}),
+            json!({"id": 2, "status": "open", "code": 7, "tags": [], "owner": null}),
+            json!({"id": 3, "status": null, "code": "A", "tags": ["x", 1], "extra": true}),
+            json!({"id": 4.5, "status": "closed", "code": "A", "tags": ["y"], "owner": {"name": "b"}}),
+        ];
+        let mut shape = Shape::default();
+        for sample in &samples {
+            shape.merge(sample, 10);
+        }
+
+        let schema = shape.to_schema();
+        for sample in &samples {
+            assert!(is_valid(&schema, sample), "{} rejects {}", schema, sample);
+        }
+        // Strings that share a field with numbers aren't listed as an enum
+        assert_eq!(
+            schema["properties"]["code"],
+            json!({"type": ["string", "integer"]})
+        );
+        assert_eq!(
+            schema["properties"]["status"]["enum"],
+            json!(["closed", "open", null])
+        );
+        assert!(!is_valid(
+            &schema,
+            &json!({"id": 5, "status": "pending", "code": "A", "tags": []})
+        ));
+    }
+
+    #[test]
+    fn test_schemas_per_route_saved_to_files() {
+        let mut result =
+            CrawlResult::new("http://example.com".to_string(), &CrawlerConfig::default());
+        let mut shapes = HashMap::new();
+        for (id, body) in [(1, json!({"id": 1, "name": "a"})), (2, json!({"id": 2}))] {
+            let href = format!("http://example.com/users/{}", id);
+            let mut endpoint = ApiEndpoint::new(href.clone(), 1);
+            endpoint.template = Some("http://example.com/users/{id}".to_string());
+            result.add_endpoint(endpoint);
+            shapes
+                .entry(shape_key(&format!("{}?expand=1", href)).unwrap())
+                .or_insert_with(Shape::default)
+                .merge(&body, 10);
+        }
+        shapes
+            .entry(shape_key("http://example.com").unwrap())
+            .or_insert_with(Shape::default)
+            .merge(&json!({"_links": {}}), 10);
+
+        let schemas = build_schemas(shapes, &result, &SchemaConfig::default());
+        let routes: Vec<(&str, usize)> = schemas
+            .iter()
+            .map(|s| (s.route.as_str(), s.samples))
+            .collect();
+        assert_eq!(
+            routes,
+            [
+                ("http://example.com/", 1),
+                ("http://example.com/users/{id}", 2)
+            ]
+        );
+        assert_eq!(schemas[1].schema["required"], json!(["id"]));
+
+        let dir = tempfile::tempdir().unwrap();
+        let paths = save_schemas(&schemas, dir.path()).unwrap();
+        let names: Vec<_> = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "example.com.schema.json",
+                "example.com_users_id.schema.json"
+            ]
+        );
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&paths[1]).unwrap()).unwrap();
+        assert_eq!(saved["title"], "http://example.com/users/{id}");
+    }
+}
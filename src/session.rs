@@ -0,0 +1,345 @@
+//! Cookie sessions and the optional pre-crawl login step
+//!
+//! When cookies are enabled the crawler's HTTP client stores `Set-Cookie`
+//! responses in a [`CookieJar`], including those received on redirects, and
+//! sends them back on later requests. A [`LoginStep`] runs before the first
+//! URL is fetched so APIs that require a session cookie can be crawled.
+
+use crate::auth::SecretSource;
+use crate::error::{CrawlerError, Result};
+use crate::transport::TransportRequest;
+use cookie_store::RawCookie;
+use reqwest::header::{CONTENT_TYPE, HeaderValue};
+use serde::Deserialize;
+use std::fs;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::RwLock;
+use tracing::info;
+use url::Url;
+
+/// Thread-safe cookie store shared with the HTTP client
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    store: RwLock<cookie_store::CookieStore>,
+}
+
+impl CookieJar {
+    /// Create an empty cookie jar
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load cookies saved by [`CookieJar::save`], starting empty if the file doesn't exist
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+
+        let file = fs::File::open(path)?;
+        let store =
+            cookie_store::CookieStore::load_json_all(BufReader::new(file)).map_err(|e| {
+                CrawlerError::config(format!(
+                    "Failed to load cookies from {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        info!("Loaded cookies from: {}", path.display());
+
+        Ok(Self {
+            store: RwLock::new(store),
+        })
+    }
+
+    /// Write all cookies, including session cookies, to a file as JSON lines
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+            && !parent.exists()
+        {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut buffer = Vec::new();
+        self.read()
+            .save_incl_expired_and_nonpersistent_json(&mut buffer)
+            .map_err(|e| CrawlerError::config(format!("Failed to serialize cookies: {}", e)))?;
+        fs::write(path, buffer)?;
+        info!("Cookies saved to: {}", path.display());
+        Ok(())
+    }
+
+    /// Store a `Set-Cookie` header value received from `url`
+    pub fn add_cookie_str(&self, cookie: &str, url: &Url) {
+        let cookies = RawCookie::parse(cookie.to_string()).ok().into_iter();
+        self.write().store_response_cookies(cookies, url);
+    }
+
+    /// Whether any unexpired cookie would be sent to `url`
+    pub fn has_cookies_for(&self, url: &Url) -> bool {
+        !self.read().matches(url).is_empty()
+    }
+
+    /// Whether an unexpired cookie with this name would be sent to `url`
+    pub fn has_cookie(&self, url: &Url, name: &str) -> bool {
+        self.read().matches(url).iter().any(|c| c.name() == name)
+    }
+
+    /// Number of unexpired cookies in the jar
+    pub fn len(&self) -> usize {
+        self.read().iter_unexpired().count()
+    }
+
+    /// Whether the jar holds no unexpired cookies
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, cookie_store::CookieStore> {
+        self.store.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, cookie_store::CookieStore> {
+        self.store.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies = cookie_headers
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| RawCookie::parse(value.to_string()).ok());
+        self.write().store_response_cookies(cookies, url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let header = self
+            .read()
+            .get_request_values(url)
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        if header.is_empty() {
+            return None;
+        }
+
+        let mut value = HeaderValue::from_str(&header).ok()?;
+        value.set_sensitive(true);
+        Some(value)
+    }
+}
+
+/// Request body sent by the login step
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum LoginBody {
+    /// `application/x-www-form-urlencoded` fields
+    Form(Vec<(String, SecretSource)>),
+    /// A raw JSON document, sent as `application/json`
+    Json(SecretSource),
+}
+
+/// A request made before crawling to establish a session cookie
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoginStep {
+    /// Login endpoint URL
+    pub url: String,
+
+    /// HTTP method (POST by default)
+    #[serde(default = "default_login_method")]
+    pub method: String,
+
+    /// Optional request body
+    #[serde(default)]
+    pub body: Option<LoginBody>,
+
+    /// Status the login must return once redirects are followed (any 2xx when unset)
+    #[serde(default)]
+    pub expected_status: Option<u16>,
+
+    /// Cookie holding the session; saved cookies only skip the login when it is present
+    /// (any cookie for the login URL when unset)
+    #[serde(default)]
+    pub session_cookie: Option<String>,
+}
+
+fn default_login_method() -> String {
+    "POST".to_string()
+}
+
+impl LoginStep {
+    /// Create a bodiless POST login step
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            method: default_login_method(),
+            body: None,
+            expected_status: None,
+            session_cookie: None,
+        }
+    }
+
+    /// Set the HTTP method
+    pub fn method(mut self, method: impl Into<String>) -> Self {
+        self.method = method.into();
+        self
+    }
+
+    /// Add a form field, replacing any JSON body
+    pub fn form_field(mut self, name: impl Into<String>, value: SecretSource) -> Self {
+        let field = (name.into(), value);
+        match &mut self.body {
+            Some(LoginBody::Form(fields)) => fields.push(field),
+            _ => self.body = Some(LoginBody::Form(vec![field])),
+        }
+        self
+    }
+
+    /// Send a JSON document as the body
+    pub fn json_body(mut self, json: SecretSource) -> Self {
+        self.body = Some(LoginBody::Json(json));
+        self
+    }
+
+    /// Require the login to return this status
+    pub fn expect_status(mut self, status: u16) -> Self {
+        self.expected_status = Some(status);
+        self
+    }
+
+    /// Name the cookie that holds the session
+    pub fn session_cookie(mut self, name: impl Into<String>) -> Self {
+        self.session_cookie = Some(name.into());
+        self
+    }
+
+    /// Whether the jar already holds a session for the login URL
+    pub(crate) fn has_session(&self, jar: &CookieJar) -> Result<bool> {
+        let url = Url::parse(&self.url)?;
+        Ok(match &self.session_cookie {
+            Some(name) => jar.has_cookie(&url, name),
+            None => jar.has_cookies_for(&url),
+        })
+    }
+
+    /// Build the login request, resolving secrets in the body
+    pub(crate) fn request(&self) -> Result<TransportRequest> {
+        let mut request = TransportRequest::get(Url::parse(&self.url)?.to_string());
+        request.method =
+            self.method.to_ascii_uppercase().parse().map_err(|_| {
+                CrawlerError::config(format!("Invalid login method: {}", self.method))
+            })?;
+
+        match &self.body {
+            None => {}
+            Some(LoginBody::Form(fields)) => {
+                let mut form = url::form_urlencoded::Serializer::new(String::new());
+                for (name, value) in fields {
+                    form.append_pair(name, &value.resolve()?);
+                }
+                request.headers.insert(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static("application/x-www-form-urlencoded"),
+                );
+                request.body = Some(form.finish().into_bytes());
+            }
+            Some(LoginBody::Json(json)) => {
+                let json = json.resolve()?;
+                serde_json::from_str::<serde_json::Value>(&json).map_err(|e| {
+                    CrawlerError::config(format!("Login body is not valid JSON: {}", e))
+                })?;
+                request
+                    .headers
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                request.body = Some(json.into_bytes());
+            }
+        }
+
+        Ok(request)
+    }
+
+    /// Check the login response status
+    pub(crate) fn check_status(&self, status: u16) -> Result<()> {
+        let ok = match self.expected_status {
+            Some(expected) => status == expected,
+            None => (200..300).contains(&status),
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(CrawlerError::HttpStatus {
+                status,
+                url: self.url.clone(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::cookie::CookieStore;
+
+    #[test]
+    fn test_cookie_jar_round_trip() {
+        let url = Url::parse("http://legacy.example.com/login").unwrap();
+        let jar = CookieJar::new();
+        let set_cookie = HeaderValue::from_static("SESSIONID=abc123; Path=/; HttpOnly");
+        jar.set_cookies(&mut std::iter::once(&set_cookie), &url);
+
+        let api = Url::parse("http://legacy.example.com/api/items").unwrap();
+        assert_eq!(jar.cookies(&api).unwrap(), "SESSIONID=abc123");
+        assert!(!jar.has_cookies_for(&Url::parse("http://other.example.com/").unwrap()));
+
+        // Session cookies survive a save/load cycle so resumed crawls stay logged in
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state/cookies.json");
+        jar.save(&path).unwrap();
+
+        let restored = CookieJar::load(&path).unwrap();
+        assert_eq!(restored.len(), 1);
+        assert!(restored.has_cookies_for(&api));
+
+        assert!(
+            CookieJar::load(dir.path().join("missing.json"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_login_request() {
+        let step = LoginStep::new("http://legacy.example.com/login")
+            .form_field("user", SecretSource::Value("alice".to_string()))
+            .form_field("password", SecretSource::Value("p&ss word".to_string()))
+            .expect_status(302);
+
+        let request = step.request().unwrap();
+        assert_eq!(request.method, reqwest::Method::POST);
+        assert_eq!(
+            request.headers[CONTENT_TYPE],
+            "application/x-www-form-urlencoded"
+        );
+        assert_eq!(
+            request.body.as_deref(),
+            Some(&b"user=alice&password=p%26ss+word"[..])
+        );
+        assert!(!format!("{:?}", step).contains("p&ss word"));
+
+        assert!(step.check_status(302).is_ok());
+        assert!(matches!(
+            step.check_status(200),
+            Err(CrawlerError::HttpStatus { status: 200, .. })
+        ));
+
+        let json = LoginStep::new("http://legacy.example.com/login")
+            .method("put")
+            .json_body(SecretSource::Value("{not json".to_string()));
+        assert!(matches!(json.request(), Err(CrawlerError::Config { .. })));
+    }
+}
@@ -0,0 +1,180 @@
+//! TLS settings for the HTTP client: client certificates, extra trusted roots
+//! and the `--insecure` escape hatch for test environments
+
+use crate::auth::SecretSource;
+use crate::error::{CrawlerError, Result};
+use reqwest::{Certificate, ClientBuilder, Identity};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Client certificate presented for mutual TLS
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum ClientIdentity {
+    /// PEM certificate (chain) and PKCS#8 PEM private key files
+    Pem { cert: PathBuf, key: PathBuf },
+    /// PKCS#12 (`.p12`/`.pfx`) archive and its password
+    Pkcs12 {
+        path: PathBuf,
+        password: SecretSource,
+    },
+}
+
+/// TLS options for the HTTP client
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    /// Client certificate for mutual TLS
+    pub client_identity: Option<ClientIdentity>,
+
+    /// PEM files with additional trusted root certificates
+    pub ca_certificates: Vec<PathBuf>,
+
+    /// Skip certificate and hostname verification (test environments only)
+    pub insecure: bool,
+}
+
+/// Read a TLS file, naming it in the error
+fn read_file(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path)
+        .map_err(|e| CrawlerError::config(format!("Failed to read {}: {}", path.display(), e)))
+}
+
+impl ClientIdentity {
+    /// Load the identity from disk
+    fn load(&self) -> Result<Identity> {
+        match self {
+            Self::Pem { cert, key } => {
+                Identity::from_pkcs8_pem(&read_file(cert)?, &read_file(key)?).map_err(|e| {
+                    CrawlerError::config(format!(
+                        "Invalid client certificate {} / key {}: {}",
+                        cert.display(),
+                        key.display(),
+                        e
+                    ))
+                })
+            }
+            Self::Pkcs12 { path, password } => {
+                Identity::from_pkcs12_der(&read_file(path)?, &password.resolve()?).map_err(|e| {
+                    CrawlerError::config(format!(
+                        "Invalid PKCS#12 client certificate {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })
+            }
+        }
+    }
+}
+
+impl TlsConfig {
+    /// Configure a client builder with these settings
+    pub(crate) fn apply(&self, mut builder: ClientBuilder) -> Result<ClientBuilder> {
+        if let Some(identity) = &self.client_identity {
+            builder = builder.identity(identity.load()?);
+            info!("Using client certificate for mutual TLS");
+        }
+
+        for path in &self.ca_certificates {
+            let certificates = Certificate::from_pem_bundle(&read_file(path)?).map_err(|e| {
+                CrawlerError::config(format!(
+                    "Invalid CA certificate file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            if certificates.is_empty() {
+                return Err(CrawlerError::config(format!(
+                    "No certificates found in {}",
+                    path.display()
+                )));
+            }
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if self.insecure {
+            warn!(
+                "TLS certificate and hostname verification is DISABLED; \
+                 traffic can be intercepted. Only use --insecure against test environments."
+            );
+            builder = builder
+                .danger_accept_invalid_certs(true)
+                .danger_accept_invalid_hostnames(true);
+        }
+
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::Client;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("test/fixtures/tls")
+            .join(name)
+    }
+
+    fn build(tls: &TlsConfig) -> Result<Client> {
+        Ok(tls.apply(Client::builder())?.build()?)
+    }
+
+    #[test]
+    fn test_client_identity_and_ca() {
+        let pem = TlsConfig {
+            client_identity: Some(ClientIdentity::Pem {
+                cert: fixture("client.pem"),
+                key: fixture("client.key"),
+            }),
+            ca_certificates: vec![fixture("ca.pem")],
+            insecure: false,
+        };
+        assert!(build(&pem).is_ok());
+
+        let pkcs12 = TlsConfig {
+            client_identity: Some(ClientIdentity::Pkcs12 {
+                path: fixture("client.p12"),
+                password: SecretSource::Value("changeit".to_string()),
+            }),
+            ..TlsConfig::default()
+        };
+        assert!(build(&pkcs12).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_tls_files_are_config_errors() {
+        let missing = TlsConfig {
+            ca_certificates: vec![fixture("missing.pem")],
+            ..TlsConfig::default()
+        };
+        assert!(matches!(build(&missing), Err(CrawlerError::Config { .. })));
+
+        // A private key is not a CA certificate
+        let not_a_cert = TlsConfig {
+            ca_certificates: vec![fixture("client.key")],
+            ..TlsConfig::default()
+        };
+        assert!(matches!(
+            build(&not_a_cert),
+            Err(CrawlerError::Config { .. })
+        ));
+
+        let wrong_password = TlsConfig {
+            client_identity: Some(ClientIdentity::Pkcs12 {
+                path: fixture("client.p12"),
+                password: SecretSource::Value("wrong".to_string()),
+            }),
+            ..TlsConfig::default()
+        };
+        assert!(matches!(
+            build(&wrong_password),
+            Err(CrawlerError::Config { .. })
+        ));
+    }
+}
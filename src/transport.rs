@@ -0,0 +1,653 @@
+//! Transports that fetch responses for the crawler
+//!
+//! [`HttpTransport`] talks to the network. [`HarTransport`] and [`FixtureTransport`]
+//! serve previously recorded responses so crawls can be re-run offline and
+//! deterministically, e.g. in regression tests or after improving link extraction.
+
+use crate::error::{CrawlerError, Result};
+use crate::har::Har;
+use crate::media::MediaType;
+use reqwest::Client;
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use url::Url;
+
+/// Boxed future returned by [`Transport::fetch`]
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<TransportResponse>> + Send + 'a>>;
+
+/// A request issued by the crawler
+#[derive(Debug, Clone)]
+pub struct TransportRequest {
+    /// HTTP method
+    pub method: reqwest::Method,
+
+    /// Absolute URL to fetch
+    pub url: String,
+
+    /// Headers to send in addition to the transport's defaults
+    pub headers: HeaderMap,
+
+    /// Request body, if any
+    pub body: Option<Vec<u8>>,
+}
+
+impl TransportRequest {
+    /// Create a GET request for a URL
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            method: reqwest::Method::GET,
+            url: url.into(),
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+}
+
+/// A response returned by a transport
+#[derive(Debug, Clone)]
+pub struct TransportResponse {
+    /// HTTP status code
+    pub status: u16,
+
+    /// HTTP version as displayed in HAR files (e.g. "HTTP/1.1")
+    pub http_version: String,
+
+    /// Response headers
+    pub headers: HeaderMap,
+
+    /// Full response body
+    pub body: Vec<u8>,
+}
+
+impl TransportResponse {
+    /// Whether the status code is in the 2xx range
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// The Content-Type header value, or an empty string
+    pub fn content_type(&self) -> &str {
+        self.headers
+            .get(CONTENT_TYPE)
+            .and_then(|ct| ct.to_str().ok())
+            .unwrap_or("")
+    }
+
+    /// The parsed Content-Type, if present and well-formed
+    pub fn media_type(&self) -> Option<MediaType> {
+        MediaType::parse(self.content_type())
+    }
+}
+
+/// Something that can fetch a URL for the crawler
+pub trait Transport: Send + Sync {
+    /// Fetch a single request
+    fn fetch<'a>(&'a self, request: &'a TransportRequest) -> TransportFuture<'a>;
+}
+
+/// Transport that performs real HTTP requests
+pub struct HttpTransport {
+    client: Client,
+}
+
+impl HttpTransport {
+    /// Create a transport from a configured client
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for HttpTransport {
+    fn fetch<'a>(&'a self, request: &'a TransportRequest) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let mut builder = self
+                .client
+                .request(request.method.clone(), &request.url)
+                .headers(request.headers.clone());
+            if let Some(body) = &request.body {
+                builder = builder.body(body.clone());
+            }
+            // Errors would print the URL, which may carry an API key; callers name the URL
+            let response = builder.send().await.map_err(reqwest::Error::without_url)?;
+
+            let status = response.status().as_u16();
+            let http_version = format!("{:?}", response.version());
+            let headers = response.headers().clone();
+            let body = response
+                .bytes()
+                .await
+                .map_err(reqwest::Error::without_url)?
+                .to_vec();
+
+            Ok(TransportResponse {
+                status,
+                http_version,
+                headers,
+                body,
+            })
+        })
+    }
+}
+
+/// Normalize a URL so recorded and requested forms compare equal
+fn normalize_key(url: &str) -> String {
+    Url::parse(url)
+        .map(|u| u.to_string())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+/// Transport that replays responses from a HAR recording
+///
+/// The first recorded entry for each method and URL is served. Requests that were
+/// not recorded fail with [`CrawlerError::NotRecorded`], and entries whose body was
+/// truncated when recording fail rather than replay a partial body.
+pub struct HarTransport {
+    responses: HashMap<(String, String), std::result::Result<TransportResponse, String>>,
+}
+
+impl HarTransport {
+    /// Create a transport from an in-memory HAR document
+    pub fn new(har: Har) -> Self {
+        let mut responses = HashMap::new();
+
+        for entry in har.log.entries {
+            let key = (
+                entry.request.method.to_ascii_uppercase(),
+                normalize_key(&entry.request.url),
+            );
+            if responses.contains_key(&key) {
+                continue;
+            }
+
+            // Status 0 marks a request that failed without a response
+            let response = if entry.response.status == 0 {
+                Err(format!(
+                    "Recorded request failed: {}",
+                    entry.comment.as_deref().unwrap_or("no response")
+                ))
+            } else if entry.response.content.truncated {
+                Err(format!(
+                    "Recorded body of {} was truncated to {} of {} bytes; record with a body limit of 0 to replay it",
+                    entry.request.url,
+                    entry.response.content.body().map_or(0, |body| body.len()),
+                    entry.response.content.size
+                ))
+            } else if let Err(e) = entry.response.content.body() {
+                Err(format!(
+                    "Recorded body of {} is unreadable: {}",
+                    entry.request.url, e
+                ))
+            } else {
+                let mut headers = HeaderMap::new();
+                for header in &entry.response.headers {
+                    if let (Ok(name), Ok(value)) = (
+                        HeaderName::from_bytes(header.name.as_bytes()),
+                        HeaderValue::from_str(&header.value),
+                    ) {
+                        headers.append(name, value);
+                    }
+                }
+                if !headers.contains_key(CONTENT_TYPE)
+                    && let Ok(value) = HeaderValue::from_str(&entry.response.content.mime_type)
+                {
+                    headers.insert(CONTENT_TYPE, value);
+                }
+
+                Ok(TransportResponse {
+                    status: entry.response.status,
+                    http_version: entry.response.http_version,
+                    headers,
+                    body: entry.response.content.body().unwrap_or_default(),
+                })
+            };
+
+            responses.insert(key, response);
+        }
+
+        Self { responses }
+    }
+
+    /// Load a HAR recording from a file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(Har::from_file(path)?))
+    }
+}
+
+impl Transport for HarTransport {
+    fn fetch<'a>(&'a self, request: &'a TransportRequest) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let key = (
+                request.method.as_str().to_string(),
+                normalize_key(&request.url),
+            );
+            match self.responses.get(&key) {
+                Some(Ok(response)) => Ok(response.clone()),
+                Some(Err(message)) => Err(CrawlerError::invalid_response(message.clone())),
+                None => Err(CrawlerError::NotRecorded {
+                    method: key.0,
+                    url: request.url.clone(),
+                }),
+            }
+        })
+    }
+}
+
+/// Transport that serves response bodies from a directory tree
+///
+/// A URL `http://host:port/a/b?x=1` maps to `<root>/host_port/a/b__x_1`, with the
+/// port part omitted for default ports. An empty path or trailing slash maps to
+/// `index`. If the exact file does not exist, the same path with a `.json`
+/// extension is tried. The content type is derived from the file extension and
+/// defaults to `application/json`. Missing fixtures are served as 404 responses.
+pub struct FixtureTransport {
+    root: PathBuf,
+}
+
+impl FixtureTransport {
+    /// Create a transport serving fixtures from `root`
+    pub fn new<P: Into<PathBuf>>(root: P) -> Result<Self> {
+        let root = root.into();
+        if !root.is_dir() {
+            return Err(CrawlerError::config(format!(
+                "Fixture directory does not exist: {}",
+                root.display()
+            )));
+        }
+        Ok(Self { root })
+    }
+
+    /// The fixture file path for a URL, without extension fallback
+    pub fn fixture_path(&self, url: &str) -> Result<PathBuf> {
+        let parsed = Url::parse(url)?;
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| CrawlerError::invalid_response(format!("URL has no host: {}", url)))?;
+
+        let mut path = self.root.clone();
+        path.push(match parsed.port() {
+            Some(port) => format!("{}_{}", sanitize_segment(host), port),
+            None => sanitize_segment(host),
+        });
+
+        let segments: Vec<&str> = parsed
+            .path_segments()
+            .map(|s| s.collect())
+            .unwrap_or_default();
+        let (last, dirs) = segments.split_last().unwrap_or((&"", &[]));
+        for dir in dirs.iter().filter(|d| !d.is_empty()) {
+            path.push(sanitize_segment(dir));
+        }
+
+        let mut file_name = if last.is_empty() {
+            "index".to_string()
+        } else {
+            sanitize_segment(last)
+        };
+        if let Some(query) = parsed.query() {
+            file_name.push_str("__");
+            file_name.push_str(&sanitize_segment(query));
+        }
+        path.push(file_name);
+
+        Ok(path)
+    }
+}
+
+/// Replace characters that are awkward in file names with underscores
+fn sanitize_segment(segment: &str) -> String {
+    segment
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Guess a content type from a fixture file extension
+fn content_type_for(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("xml") => "application/xml",
+        Some("atom") => "application/atom+xml",
+        Some("html") | Some("htm") => "text/html",
+        Some("csv") => "text/csv",
+        Some("txt") => "text/plain",
+        _ => "application/json",
+    }
+}
+
+impl Transport for FixtureTransport {
+    fn fetch<'a>(&'a self, request: &'a TransportRequest) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let path = self.fixture_path(&request.url)?;
+            let mut with_json = path.clone().into_os_string();
+            with_json.push(".json");
+            let candidates = [path, PathBuf::from(with_json)];
+
+            let mut headers = HeaderMap::new();
+            for candidate in candidates.iter() {
+                if candidate.is_file() {
+                    let body = fs::read(candidate)?;
+                    headers.insert(
+                        CONTENT_TYPE,
+                        HeaderValue::from_static(content_type_for(candidate)),
+                    );
+                    return Ok(TransportResponse {
+                        status: 200,
+                        http_version: "HTTP/1.1".to_string(),
+                        headers,
+                        body,
+                    });
+                }
+            }
+
+            Ok(TransportResponse {
+                status: 404,
+                http_version: "HTTP/1.1".to_string(),
+                headers,
+                body: Vec::new(),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::har::{HarContent, HarEntry, HarRequest, HarResponse, HarTimings};
+
+    fn har_entry(url: &str, status: u16, body: &str) -> HarEntry {
+        HarEntry {
+            started_date_time: "2024-01-01T00:00:00Z".to_string(),
+            time: 1.0,
+            request: HarRequest::new("GET", url, "HTTP/1.1", Vec::new()),
+            response: HarResponse {
+                status,
+                status_text: String::new(),
+                http_version: "HTTP/1.1".to_string(),
+                cookies: Vec::new(),
+                headers: Vec::new(),
+                content: HarContent::from_body(body.as_bytes(), "application/hal+json", 0),
+                redirect_url: String::new(),
+                headers_size: -1,
+                body_size: body.len() as i64,
+            },
+            cache: Default::default(),
+            timings: HarTimings::new(1.0, 0.0),
+            comment: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_har_transport_replays_entries() {
+        let transport = HarTransport::new(Har::new(vec![
+            har_entry("http://example.com", 200, "{}"),
+            har_entry("http://example.com/", 500, "duplicate"),
+        ]));
+
+        // Both URLs normalize to the same key, so the first entry wins
+        let response = transport
+            .fetch(&TransportRequest::get("http://example.com/"))
+            .await
+            .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type(), "application/hal+json");
+        assert_eq!(response.body, b"{}");
+
+        let missing = transport
+            .fetch(&TransportRequest::get("http://example.com/other"))
+            .await;
+        assert!(matches!(missing, Err(CrawlerError::NotRecorded { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_har_transport_refuses_truncated_bodies() {
+        let mut entry = har_entry("http://example.com/big", 200, r#"{"items": [1, 2, 3]}"#);
+        entry.response.content =
+            HarContent::from_body(br#"{"items": [1, 2, 3]}"#, "application/json", 8);
+        let transport = HarTransport::new(Har::new(vec![entry]));
+
+        let error = transport
+            .fetch(&TransportRequest::get("http://example.com/big"))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("truncated to 8 of 20 bytes"));
+    }
+
+    #[tokio::test]
+    async fn test_har_transport_replays_binary_bodies() {
+        let gzip = b"\x1f\x8b\x08\x00\xff\xfe";
+        let mut entry = har_entry("http://example.com/archive", 200, "");
+        entry.response.content = HarContent::from_body(gzip, "application/gzip", 0);
+        let transport = HarTransport::new(Har::new(vec![entry]));
+
+        let response = transport
+            .fetch(&TransportRequest::get("http://example.com/archive"))
+            .await
+            .unwrap();
+        assert_eq!(response.body, gzip);
+    }
+
+    #[tokio::test]
+    async fn test_fixture_transport() {
+        let dir = tempfile::tempdir().unwrap();
+        let host_dir = dir.path().join("example.com_8080").join("api");
+        fs::create_dir_all(&host_dir).unwrap();
+        fs::write(host_dir.join("users.json"), r#"{"users": []}"#).unwrap();
+        fs::write(host_dir.join("feed.xml"), "<feed/>").unwrap();
+        fs::write(host_dir.join("search__q_rust"), "{}").unwrap();
+
+        let transport = FixtureTransport::new(dir.path()).unwrap();
+
+        let users = transport
+            .fetch(&TransportRequest::get("http://example.com:8080/api/users"))
+            .await
+            .unwrap();
+        assert_eq!(users.status, 200);
+        assert_eq!(users.content_type(), "application/json");
+        assert_eq!(users.body, br#"{"users": []}"#);
+
+        let feed = transport
+            .fetch(&TransportRequest::get(
+                "http://example.com:8080/api/feed.xml",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(feed.content_type(), "application/xml");
+
+        let search = transport
+            .fetch(&TransportRequest::get(
+                "http://example.com:8080/api/search?q=rust",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(search.status, 200);
+
+        let missing = transport
+            .fetch(&TransportRequest::get("http://example.com:8080/api/none"))
+            .await
+            .unwrap();
+        assert_eq!(missing.status, 404);
+    }
+
+    #[test]
+    fn test_fixture_path() {
+        let transport = FixtureTransport {
+            root: PathBuf::from("/fixtures"),
+        };
+
+        assert_eq!(
+            transport.fixture_path("https://example.com/").unwrap(),
+            PathBuf::from("/fixtures/example.com/index")
+        );
+        assert_eq!(
+            transport
+                .fixture_path("http://localhost:3000/a/b?page=2")
+                .unwrap(),
+            PathBuf::from("/fixtures/localhost_3000/a/b__page_2")
+        );
+    }
+}
+
+/// Canned-response transport standing in for a server in tests
+#[cfg(test)]
+pub(crate) mod stub {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// A canned response and the requests it answers
+    struct Route {
+        method: Option<reqwest::Method>,
+        url: String,
+        header: Option<(HeaderName, String)>,
+        response: TransportResponse,
+        served: AtomicBool,
+    }
+
+    impl Route {
+        fn matches(&self, request: &TransportRequest) -> bool {
+            self.url == request.url
+                && self.method.as_ref().is_none_or(|m| *m == request.method)
+                && self.header.as_ref().is_none_or(|(name, value)| {
+                    request.headers.get(name).and_then(|v| v.to_str().ok()) == Some(value)
+                })
+        }
+
+        /// Routes narrowed by a header, then by a method, win over plain URL routes
+        fn specificity(&self) -> u8 {
+            2 * u8::from(self.header.is_some()) + u8::from(self.method.is_some())
+        }
+    }
+
+    /// Requests received by a [`StubTransport`], shared with the test
+    #[derive(Clone, Default)]
+    pub(crate) struct RequestLog(Arc<Mutex<Vec<TransportRequest>>>);
+
+    impl RequestLog {
+        /// Every request received, in order
+        pub(crate) fn requests(&self) -> Vec<TransportRequest> {
+            self.0.lock().unwrap().clone()
+        }
+
+        /// `METHOD URL` of every request received, in order
+        pub(crate) fn lines(&self) -> Vec<String> {
+            self.requests()
+                .iter()
+                .map(|r| format!("{} {}", r.method, r.url))
+                .collect()
+        }
+    }
+
+    /// Serves canned responses by URL and records every request
+    ///
+    /// Targets are a URL or `METHOD URL`. When several routes answer a request
+    /// the most specific wins, and routes for the same request are served in
+    /// turn, the last one repeating. Anything else gets `200` with `{}`.
+    #[derive(Default)]
+    pub(crate) struct StubTransport {
+        routes: Vec<Route>,
+        log: RequestLog,
+    }
+
+    impl StubTransport {
+        pub(crate) fn new() -> Self {
+            Self::default()
+        }
+
+        /// Answer `target` with a JSON body
+        pub(crate) fn json(self, target: &str, body: serde_json::Value) -> Self {
+            self.respond(
+                target,
+                200,
+                &[("content-type", "application/json")],
+                body.to_string(),
+            )
+        }
+
+        /// Answer `target` with a status, headers and body
+        pub(crate) fn respond(
+            mut self,
+            target: &str,
+            status: u16,
+            headers: &[(&str, &str)],
+            body: impl Into<Vec<u8>>,
+        ) -> Self {
+            let (method, url) = match target.split_once(' ') {
+                Some((method, url)) => (Some(method.parse().unwrap()), url),
+                None => (None, target),
+            };
+            let mut response_headers = HeaderMap::new();
+            for (name, value) in headers {
+                response_headers.append(
+                    HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                    value.parse().unwrap(),
+                );
+            }
+            self.routes.push(Route {
+                method,
+                url: url.to_string(),
+                header: None,
+                response: TransportResponse {
+                    status,
+                    http_version: "HTTP/1.1".to_string(),
+                    headers: response_headers,
+                    body: body.into(),
+                },
+                served: AtomicBool::new(false),
+            });
+            self
+        }
+
+        /// Only use the last added response for requests carrying this header value
+        pub(crate) fn when_header(mut self, name: &str, value: &str) -> Self {
+            let route = self.routes.last_mut().expect("no route to narrow");
+            route.header = Some((name.parse().unwrap(), value.to_string()));
+            self
+        }
+
+        /// Handle to the requests this transport receives
+        pub(crate) fn log(&self) -> RequestLog {
+            self.log.clone()
+        }
+    }
+
+    impl Transport for StubTransport {
+        fn fetch<'a>(&'a self, request: &'a TransportRequest) -> TransportFuture<'a> {
+            self.log.0.lock().unwrap().push(request.clone());
+
+            let matching: Vec<&Route> = self.routes.iter().filter(|r| r.matches(request)).collect();
+            let best = matching.iter().map(|r| r.specificity()).max();
+            let candidates: Vec<&Route> = matching
+                .into_iter()
+                .filter(|r| Some(r.specificity()) == best)
+                .collect();
+            let route = candidates
+                .iter()
+                .find(|r| !r.served.swap(true, Ordering::SeqCst))
+                .or(candidates.last());
+
+            let response = match route {
+                Some(route) => route.response.clone(),
+                None => {
+                    let mut headers = HeaderMap::new();
+                    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                    TransportResponse {
+                        status: 200,
+                        http_version: "HTTP/1.1".to_string(),
+                        headers,
+                        body: b"{}".to_vec(),
+                    }
+                }
+            };
+            Box::pin(async move { Ok(response) })
+        }
+    }
+}
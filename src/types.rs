@@ -1,7 +1,25 @@
 //! Type definitions for the API crawler
 
+use crate::auth::{ApiKeyLocation, Auth, CredentialScope, REDACTED, is_sensitive_header};
+use crate::error::CrawlerError;
+use crate::filter::{RuleSkips, UrlFilters};
+use crate::graphql::{GraphqlConfig, GraphqlEndpoint};
+use crate::host::HostPattern;
+use crate::media::{DEFAULT_ACCEPT, MediaType};
+use crate::methods::{MethodProbeConfig, SupportedMethods};
+use crate::pagination::{PaginatedCollection, PaginationConfig};
+use crate::proxy::ProxyConfig;
+use crate::routes::RouteTemplate;
+use crate::safety::{ProbeMethod, SafetyConfig, check_read_only};
+use crate::sampling::{SampleStrategy, SampledPattern, SamplingConfig};
+use crate::schema::{EndpointSchema, SchemaConfig};
+use crate::sensitive::{BuiltinRule, SensitiveDataConfig, SensitiveFinding, SensitiveRule};
+use crate::session::LoginStep;
+use crate::tls::TlsConfig;
+use crate::xml::XmlSelector;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use url::Url;
 
 /// Helper function to check if a HashMap is empty (for serde skip_serializing_if)
@@ -10,7 +28,7 @@ fn is_empty_metadata(metadata: &HashMap<String, serde_json::Value>) -> bool {
 }
 
 /// Helper function to check if a String is empty (for serde skip_serializing_if)
-fn is_empty_string(s: &String) -> bool {
+fn is_empty_string(s: &str) -> bool {
     s.is_empty()
 }
 
@@ -46,6 +64,18 @@ pub struct ApiEndpoint {
     /// Additional metadata found in the response
     #[serde(skip_serializing_if = "is_empty_metadata")]
     pub metadata: HashMap<String, serde_json::Value>,
+
+    /// Methods the endpoint advertised in response to an OPTIONS probe
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supported_methods: Option<SupportedMethods>,
+
+    /// Media types the endpoint was found to serve when probed for representations
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub representations: Vec<String>,
+
+    /// Route template the href matches, e.g. `http://example.com/users/{id}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
 }
 
 impl ApiEndpoint {
@@ -60,6 +90,9 @@ impl ApiEndpoint {
             depth,
             parent_url: None,
             metadata: HashMap::new(),
+            supported_methods: None,
+            representations: Vec::new(),
+            template: None,
         }
     }
 
@@ -88,7 +121,8 @@ impl ApiEndpoint {
 }
 
 /// Configuration for the API crawler
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct CrawlerConfig {
     /// Maximum depth to crawl (0 means unlimited)
     pub max_depth: usize,
@@ -105,17 +139,85 @@ pub struct CrawlerConfig {
     /// User agent string for requests
     pub user_agent: String,
 
-    /// Additional headers to include in requests
+    /// Additional headers to include in requests; credential-bearing ones such as
+    /// `Authorization` are sent only to the start URL's site
     pub headers: HashMap<String, String>,
 
+    /// Headers and credentials sent only to matching hosts
+    pub credential_scopes: Vec<CredentialScope>,
+
     /// Delay between requests in milliseconds
     pub delay_ms: u64,
 
     /// Whether to follow redirects
     pub follow_redirects: bool,
 
-    /// Domains to restrict crawling to (empty means no restriction)
+    /// Hosts to restrict crawling to, as [`HostPattern::parse_domain`] rules:
+    /// `example.com` (and subdomains), `*.example.com`, `10.0.0.5`, `host:port`
     pub allowed_domains: HashSet<String>,
+
+    /// With no `allowed_domains`, only follow links on the start URL's site
+    pub same_site: bool,
+
+    /// Include/exclude, rel and method rules for following discovered links
+    pub filters: UrlFilters,
+
+    /// Read-only mode and handling of links that advertise unsafe methods
+    pub safety: SafetyConfig,
+
+    /// `Accept` header sent with every GET (empty to send none)
+    pub accept: String,
+
+    /// Probing discovered endpoints with OPTIONS for their supported methods
+    pub method_probe: MethodProbeConfig,
+
+    /// Media types to request from every discovered endpoint to see which it serves
+    pub representations: Vec<MediaType>,
+
+    /// Selectors yielding links from XML responses, in addition to Atom and AtomPub links
+    pub xml_selectors: Vec<XmlSelector>,
+
+    /// Introspection of detected GraphQL endpoints
+    pub graphql: GraphqlConfig,
+
+    /// Following pages of paginated collections
+    pub pagination: PaginationConfig,
+
+    /// Following only a sample of links that differ in an ID-like segment
+    pub sampling: SamplingConfig,
+
+    /// Inferring JSON Schemas from the response bodies of each route
+    pub schema: SchemaConfig,
+
+    /// Scanning response bodies for PII and secrets
+    pub sensitive: SensitiveDataConfig,
+
+    /// Whether to record the status, timing and error of every fetched URL in `CrawlResult::visits`
+    pub record_visits: bool,
+
+    /// Whether to record all traffic as HAR entries
+    pub record_har: bool,
+
+    /// Maximum response body size stored per HAR entry in bytes (0 means unlimited)
+    pub har_body_limit: usize,
+
+    /// Authentication for requests to the start URL's site not covered by a credential scope
+    pub auth: Option<Auth>,
+
+    /// Whether to keep cookies set by the server and send them back
+    pub cookies: bool,
+
+    /// File the cookie jar is loaded from and saved to after the crawl
+    pub cookie_file: Option<PathBuf>,
+
+    /// Request made before crawling to establish a session
+    pub login: Option<LoginStep>,
+
+    /// Client certificate, extra trusted roots and verification settings
+    pub tls: TlsConfig,
+
+    /// Proxy settings
+    pub proxy: ProxyConfig,
 }
 
 impl Default for CrawlerConfig {
@@ -127,9 +229,31 @@ impl Default for CrawlerConfig {
             max_urls: 1000,
             user_agent: "API-Crawler/1.0".to_string(),
             headers: HashMap::new(),
+            credential_scopes: Vec::new(),
             delay_ms: 100,
             follow_redirects: true,
             allowed_domains: HashSet::new(),
+            same_site: true,
+            filters: UrlFilters::default(),
+            safety: SafetyConfig::default(),
+            accept: DEFAULT_ACCEPT.to_string(),
+            method_probe: MethodProbeConfig::default(),
+            representations: Vec::new(),
+            xml_selectors: Vec::new(),
+            graphql: GraphqlConfig::default(),
+            pagination: PaginationConfig::default(),
+            sampling: SamplingConfig::default(),
+            schema: SchemaConfig::default(),
+            sensitive: SensitiveDataConfig::default(),
+            record_visits: false,
+            record_har: false,
+            har_body_limit: 0,
+            auth: None,
+            cookies: false,
+            cookie_file: None,
+            login: None,
+            tls: TlsConfig::default(),
+            proxy: ProxyConfig::default(),
         }
     }
 }
@@ -164,15 +288,345 @@ impl CrawlerConfig {
         self
     }
 
+    /// Follow links to any host when no `allowed_domains` are set
+    pub fn any_domain(mut self) -> Self {
+        self.same_site = false;
+        self
+    }
+
+    /// Refuse every request that isn't GET, HEAD or OPTIONS at the transport layer
+    pub fn read_only(mut self) -> Self {
+        self.safety.read_only = true;
+        self
+    }
+
+    /// Probe links that advertise unsafe methods instead of skipping them
+    pub fn probe_unsafe(mut self, probe: ProbeMethod) -> Self {
+        self.safety.probe_unsafe = Some(probe);
+        self
+    }
+
+    /// Probe every discovered endpoint with OPTIONS for its supported methods
+    pub fn probe_methods(mut self) -> Self {
+        self.method_probe.enabled = true;
+        self
+    }
+
+    /// Send method probes as CORS preflights from this origin (enables method probing)
+    pub fn cors_origin(mut self, origin: impl Into<String>) -> Self {
+        self.method_probe.enabled = true;
+        self.method_probe.cors_origin = Some(origin.into());
+        self
+    }
+
+    /// Set the `Accept` header sent with every GET
+    pub fn accept(mut self, accept: impl Into<String>) -> Self {
+        self.accept = accept.into();
+        self
+    }
+
+    /// Request each media type from every discovered endpoint and record which are served
+    pub fn probe_representations(
+        mut self,
+        media_types: impl IntoIterator<Item = MediaType>,
+    ) -> Self {
+        self.representations = media_types.into_iter().collect();
+        self
+    }
+
+    /// Also extract links matched by a selector from XML responses
+    pub fn xml_selector(mut self, selector: XmlSelector) -> Self {
+        self.xml_selectors.push(selector);
+        self
+    }
+
+    /// Send the introspection query to every detected GraphQL endpoint
+    pub fn introspect_graphql(mut self) -> Self {
+        self.graphql.introspect = true;
+        self
+    }
+
+    /// Set the maximum pages fetched per paginated collection (0 means unlimited)
+    pub fn max_pages(mut self, max_pages: usize) -> Self {
+        self.pagination.max_pages = max_pages;
+        self
+    }
+
+    /// Follow only `sample_size` instances of each ID-like URL pattern (0 means every instance)
+    pub fn sample(mut self, sample_size: usize, strategy: SampleStrategy) -> Self {
+        self.sampling.sample_size = sample_size;
+        self.sampling.strategy = strategy;
+        self
+    }
+
+    /// Seed random sampling for reproducible crawls
+    pub fn sample_seed(mut self, seed: u64) -> Self {
+        self.sampling.seed = Some(seed);
+        self
+    }
+
+    /// Infer a JSON Schema for each route from the JSON bodies it returns
+    pub fn infer_schemas(mut self) -> Self {
+        self.schema.infer = true;
+        self
+    }
+
+    /// Scan every JSON response body for PII and secrets
+    pub fn scan_sensitive_data(mut self) -> Self {
+        self.sensitive.enabled = true;
+        self
+    }
+
+    /// Also flag values matching a custom rule when scanning for sensitive data
+    pub fn sensitive_rule(mut self, rule: SensitiveRule) -> Self {
+        self.sensitive.rules.push(rule);
+        self
+    }
+
+    /// Don't apply a built-in sensitive data rule
+    pub fn disable_sensitive_rule(mut self, rule: BuiltinRule) -> Self {
+        self.sensitive.disable.push(rule);
+        self
+    }
+
+    /// Set the rules for following discovered links
+    pub fn filters(mut self, filters: UrlFilters) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// Record the outcome of every fetched URL in `CrawlResult::visits`
+    pub fn record_visits(mut self) -> Self {
+        self.record_visits = true;
+        self
+    }
+
+    /// Enable HAR recording with the given per-entry body size limit
+    pub fn record_har(mut self, body_limit: usize) -> Self {
+        self.record_har = true;
+        self.har_body_limit = body_limit;
+        self
+    }
+
     /// Add a custom header
     pub fn add_header(mut self, key: String, value: String) -> Self {
         self.headers.insert(key, value);
         self
     }
+
+    /// Set the authentication strategy
+    pub fn auth(mut self, auth: Auth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Keep cookies across requests
+    pub fn enable_cookies(mut self) -> Self {
+        self.cookies = true;
+        self
+    }
+
+    /// Persist cookies in a file across crawls (enables cookies)
+    pub fn cookie_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cookies = true;
+        self.cookie_file = Some(path.into());
+        self
+    }
+
+    /// Log in before crawling (enables cookies)
+    pub fn login(mut self, login: LoginStep) -> Self {
+        self.cookies = true;
+        self.login = Some(login);
+        self
+    }
+
+    /// Set the TLS options
+    pub fn tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    /// Set the proxy settings
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Add headers and credentials scoped to matching hosts
+    pub fn scope_credentials(mut self, scope: CredentialScope) -> Self {
+        self.credential_scopes.push(scope);
+        self
+    }
+
+    /// Check the configuration, reporting every problem found as one `Config` error
+    pub fn validate(&self) -> crate::error::Result<()> {
+        let problems = self.validation_errors();
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(crate::error::CrawlerError::config(format!(
+                "Invalid configuration:\n  - {}",
+                problems.join("\n  - ")
+            )))
+        }
+    }
+
+    /// Problems with this configuration, each prefixed with the offending field
+    pub fn validation_errors(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.max_concurrent_requests == 0 {
+            problems.push("max_concurrent_requests: must be at least 1".to_string());
+        }
+        if self.timeout_seconds == 0 {
+            problems.push("timeout_seconds: must be at least 1".to_string());
+        }
+        if reqwest::header::HeaderValue::from_str(&self.user_agent).is_err() {
+            problems.push("user_agent: not a valid header value".to_string());
+        }
+        if reqwest::header::HeaderValue::from_str(&self.accept).is_err() {
+            problems.push("accept: not a valid header value".to_string());
+        }
+
+        let mut domains: Vec<&String> = self.allowed_domains.iter().collect();
+        domains.sort();
+        for domain in domains {
+            if let Err(CrawlerError::Config { message }) = HostPattern::parse_domain(domain) {
+                problems.push(format!("allowed_domains: {}", message));
+            }
+        }
+
+        check_headers("headers", &self.headers, &mut problems);
+        for (i, scope) in self.credential_scopes.iter().enumerate() {
+            let field = format!("credential_scopes[{}]", i);
+            check_headers(&format!("{}.headers", field), &scope.headers, &mut problems);
+            if let Some(auth) = &scope.auth {
+                check_auth(&format!("{}.auth", field), auth, &mut problems);
+            }
+        }
+        if let Some(auth) = &self.auth {
+            check_auth("auth", auth, &mut problems);
+        }
+
+        if let Some(login) = &self.login {
+            if Url::parse(&login.url).is_err() {
+                problems.push(format!("login.url: invalid URL '{}'", login.url));
+            }
+            if reqwest::Method::from_bytes(login.method.to_ascii_uppercase().as_bytes()).is_err() {
+                problems.push(format!("login.method: invalid method '{}'", login.method));
+            }
+        }
+
+        if self.safety.read_only {
+            check_read_only(
+                self.login.as_ref().map(|login| login.method.as_str()),
+                &mut problems,
+            );
+        }
+
+        for media_type in &self.representations {
+            if media_type.r#type == "*" || media_type.subtype == "*" {
+                problems.push(format!(
+                    "representations: '{}' is a wildcard, not a representation",
+                    media_type
+                ));
+            }
+        }
+
+        if let Some(origin) = &self.method_probe.cors_origin {
+            match Url::parse(origin) {
+                Ok(url) if url.has_host() && url.path() == "/" && url.query().is_none() => {}
+                _ => problems.push(format!(
+                    "method_probe.cors_origin: '{}' is not an origin (expected scheme://host[:port])",
+                    origin
+                )),
+            }
+        }
+
+        if let Some(proxy) = &self.proxy.url {
+            match Url::parse(proxy) {
+                Ok(url) if matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h") => {}
+                Ok(url) => problems.push(format!(
+                    "proxy.url: unsupported scheme '{}' (expected http, https, socks5 or socks5h)",
+                    url.scheme()
+                )),
+                Err(e) => problems.push(format!("proxy.url: {}", e)),
+            }
+        }
+
+        for (i, path) in self.tls.ca_certificates.iter().enumerate() {
+            if !path.exists() {
+                problems.push(format!(
+                    "tls.ca_certificates[{}]: {} does not exist",
+                    i,
+                    path.display()
+                ));
+            }
+        }
+
+        problems
+    }
+
+    /// Copy of this configuration with credential-bearing header values redacted
+    ///
+    /// `Auth` secrets are already hidden by their `Debug` implementation.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        let scoped = config
+            .credential_scopes
+            .iter_mut()
+            .flat_map(|scope| scope.headers.iter_mut());
+        for (key, value) in config.headers.iter_mut().chain(scoped) {
+            if is_sensitive_header(key) {
+                *value = REDACTED.to_string();
+            }
+        }
+        config
+    }
+}
+
+/// Record invalid header names or values in a configured header map
+fn check_headers(field: &str, headers: &HashMap<String, String>, problems: &mut Vec<String>) {
+    let mut names: Vec<&String> = headers.keys().collect();
+    names.sort();
+    for name in names {
+        if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err() {
+            problems.push(format!("{}: invalid header name '{}'", field, name));
+        } else if reqwest::header::HeaderValue::from_str(&headers[name]).is_err() {
+            problems.push(format!("{}.{}: invalid header value", field, name));
+        }
+    }
+}
+
+/// Record problems with an authentication strategy that can be found without resolving secrets
+fn check_auth(field: &str, auth: &Auth, problems: &mut Vec<String>) {
+    match auth {
+        Auth::ApiKey { name, location, .. } => {
+            if name.is_empty() {
+                problems.push(format!("{}.api_key.name: must not be empty", field));
+            } else if *location == ApiKeyLocation::Header
+                && reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err()
+            {
+                problems.push(format!(
+                    "{}.api_key.name: invalid header name '{}'",
+                    field, name
+                ));
+            }
+        }
+        Auth::OAuth2(settings) => {
+            if Url::parse(&settings.token_url).is_err() {
+                problems.push(format!(
+                    "{}.oauth2.token_url: invalid URL '{}'",
+                    field, settings.token_url
+                ));
+            }
+        }
+        Auth::Basic { .. } | Auth::Bearer(_) => {}
+    }
 }
 
 /// Helper function to check if a Vec is empty (for serde skip_serializing_if)
-fn is_empty_errors(errors: &Vec<String>) -> bool {
+fn is_empty_errors(errors: &[String]) -> bool {
     errors.is_empty()
 }
 
@@ -205,6 +659,14 @@ pub struct CrawlStats {
     #[serde(skip_serializing_if = "is_zero_usize")]
     pub urls_skipped: usize,
 
+    /// Links not followed because of domain or filter rules, by rule
+    #[serde(default, skip_serializing_if = "RuleSkips::is_empty")]
+    pub skipped_by_rule: RuleSkips,
+
+    /// Number of endpoints whose supported methods were discovered by an OPTIONS probe
+    #[serde(default, skip_serializing_if = "is_zero_usize")]
+    pub methods_probed: usize,
+
     /// Maximum depth reached
     #[serde(skip_serializing_if = "is_zero_usize")]
     pub max_depth_reached: usize,
@@ -218,6 +680,46 @@ pub struct CrawlStats {
     pub errors: Vec<String>,
 }
 
+/// Outcome of fetching a single URL during the crawl
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlVisit {
+    /// The URL that was fetched
+    pub url: String,
+
+    /// The depth of this URL
+    pub depth: usize,
+
+    /// The parent URL that led to this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_url: Option<String>,
+
+    /// Probe method, when the URL was probed with HEAD or OPTIONS instead of fetched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+
+    /// HTTP status code, if a response was received
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+
+    /// Time taken to fetch and process the URL
+    pub duration_ms: u128,
+
+    /// Number of endpoints extracted from the response
+    #[serde(skip_serializing_if = "is_zero_usize")]
+    pub endpoints_found: usize,
+
+    /// Error message if the request failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl UrlVisit {
+    /// Whether this visit succeeded
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
 /// Complete result of the crawling process
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrawlResult {
@@ -233,6 +735,34 @@ pub struct CrawlResult {
     /// Statistics about the crawl
     pub stats: CrawlStats,
 
+    /// Every URL fetched during the crawl, in visit order, when `record_visits` is enabled
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub visits: Vec<UrlVisit>,
+
+    /// Paginated collections and the pages followed from each
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collections: Vec<PaginatedCollection>,
+
+    /// URL patterns whose instances were sampled, with estimated counts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sampled_patterns: Vec<SampledPattern>,
+
+    /// GraphQL endpoints detected during the crawl, with their schemas when introspected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub graphql: Vec<GraphqlEndpoint>,
+
+    /// Route templates inferred from the discovered URLs, with match counts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<RouteTemplate>,
+
+    /// JSON Schemas inferred from the response bodies of each route
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schemas: Vec<EndpointSchema>,
+
+    /// Sensitive data found in response bodies, with redacted samples
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub findings: Vec<SensitiveFinding>,
+
     /// Timestamp when crawling started
     pub started_at: chrono::DateTime<chrono::Utc>,
 
@@ -253,9 +783,16 @@ impl CrawlResult {
             endpoints: Vec::new(),
             url_mappings: HashMap::new(),
             stats: CrawlStats::default(),
+            visits: Vec::new(),
+            collections: Vec::new(),
+            sampled_patterns: Vec::new(),
+            graphql: Vec::new(),
+            routes: Vec::new(),
+            schemas: Vec::new(),
+            findings: Vec::new(),
             started_at: now,
             completed_at: now,
-            config_snapshot: format!("{:?}", config),
+            config_snapshot: format!("{:?}", config.redacted()),
         }
     }
 
@@ -268,11 +805,26 @@ impl CrawlResult {
         if let Some(parent) = &endpoint.parent_url {
             self.url_mappings
                 .entry(parent.clone())
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(endpoint);
         }
     }
 
+    /// The paginated collection starting at a URL, created if not yet recorded
+    pub fn collection_mut(&mut self, url: &str) -> &mut PaginatedCollection {
+        let index = match self.collections.iter().position(|c| c.url == url) {
+            Some(index) => index,
+            None => {
+                self.collections.push(PaginatedCollection {
+                    url: url.to_string(),
+                    ..PaginatedCollection::default()
+                });
+                self.collections.len() - 1
+            }
+        };
+        &mut self.collections[index]
+    }
+
     /// Mark the crawl as completed
     pub fn complete(&mut self) {
         self.completed_at = chrono::Utc::now();
@@ -316,6 +868,12 @@ pub struct QueueItem {
 
     /// The parent URL that led to this one
     pub parent_url: Option<String>,
+
+    /// Probe with HEAD or OPTIONS instead of fetching with GET
+    pub probe: Option<ProbeMethod>,
+
+    /// First page of the collection this URL is a further page of
+    pub collection: Option<String>,
 }
 
 impl QueueItem {
@@ -325,6 +883,28 @@ impl QueueItem {
             url,
             depth,
             parent_url,
+            probe: None,
+            collection: None,
+        }
+    }
+
+    /// Probe the URL instead of fetching it
+    pub fn with_probe(mut self, probe: Option<ProbeMethod>) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    /// Mark the URL as a further page of a collection
+    pub fn with_collection(mut self, collection: Option<String>) -> Self {
+        self.collection = collection;
+        self
+    }
+
+    /// Key identifying this request among visited ones; probes don't stand in for a GET
+    pub fn visit_key(&self) -> String {
+        match self.probe {
+            Some(probe) => format!("{} {}", probe.method(), self.url),
+            None => self.url.clone(),
         }
     }
 }
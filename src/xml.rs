@@ -0,0 +1,539 @@
+//! Link extraction from XML responses
+//!
+//! Built in are Atom and AtomPub:
+//!
+//! - every `<link href>` becomes an endpoint with its `rel` (`alternate` when
+//!   absent), `type` and `title`
+//! - every AtomPub `<collection href>` becomes a `collection` endpoint titled by
+//!   its `<atom:title>`, with its `<accept>` media types as metadata
+//! - `<content src>` becomes a `content` endpoint
+//!
+//! Other XML APIs are covered by [`XmlSelector`]s, a small XPath subset such as
+//! `//resource/@uri` or `/catalog/item[@kind='api']/location`. Relative URLs are
+//! resolved against `xml:base` and the response URL.
+
+use crate::error::{CrawlerError, Result};
+use crate::types::{ApiEndpoint, QueueItem};
+use roxmltree::{Document, Node, ParsingOptions};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+use tracing::debug;
+use url::Url;
+
+const ATOM_NS: &str = "http://www.w3.org/2005/Atom";
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// An XPath-like selector yielding URLs from an XML document
+///
+/// Steps are separated by `/`, or by `//` to match at any depth; a selector not
+/// starting with `/` matches anywhere. A step is an element name (namespace
+/// prefixes are ignored) or `*`, optionally followed by `[@attr]` or
+/// `[@attr='value']` predicates. A final `/@attr` selects an attribute;
+/// otherwise the element's text is used.
+///
+/// Each result's rel is the element's `rel` attribute, or its name.
+#[derive(Clone, PartialEq)]
+pub struct XmlSelector {
+    source: String,
+    steps: Vec<Step>,
+    attribute: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Step {
+    /// Matches at any depth below the previous step
+    descendant: bool,
+    /// Local name, or `None` for `*`
+    name: Option<String>,
+    /// `[@attr]` and `[@attr='value']` predicates
+    predicates: Vec<(String, Option<String>)>,
+}
+
+impl XmlSelector {
+    /// Parse a selector
+    pub fn parse(selector: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            CrawlerError::config(format!("Invalid XML selector '{}': {}", selector, reason))
+        };
+
+        let (mut rest, mut descendant) = match selector.strip_prefix("//") {
+            Some(rest) => (rest, true),
+            None => match selector.strip_prefix('/') {
+                Some(rest) => (rest, false),
+                None => (selector, true),
+            },
+        };
+
+        let mut steps = Vec::new();
+        let mut attribute = None;
+        loop {
+            if let Some(name) = rest.strip_prefix('@') {
+                if !is_name(name) {
+                    return Err(invalid("expected an attribute name after '@'"));
+                }
+                attribute = Some(name.to_string());
+                break;
+            }
+
+            let end = step_end(rest);
+            steps.push(parse_step(&rest[..end], descendant).map_err(|reason| invalid(&reason))?);
+            rest = &rest[end..];
+            if rest.is_empty() {
+                break;
+            }
+            (rest, descendant) = match rest.strip_prefix("//") {
+                Some(rest) => (rest, true),
+                None => (&rest[1..], false),
+            };
+        }
+
+        if steps.is_empty() {
+            return Err(invalid("expected at least one element step"));
+        }
+
+        Ok(Self {
+            source: selector.to_string(),
+            steps,
+            attribute,
+        })
+    }
+
+    /// Elements matched by the steps, in document order
+    fn select<'a, 'input>(&self, document: &'a Document<'input>) -> Vec<Node<'a, 'input>> {
+        let mut context = vec![document.root()];
+        for step in &self.steps {
+            let mut matched: Vec<Node> = Vec::new();
+            let mut seen = HashSet::new();
+            for node in &context {
+                let candidates: Box<dyn Iterator<Item = Node>> = if step.descendant {
+                    Box::new(node.descendants().skip(1))
+                } else {
+                    Box::new(node.children())
+                };
+                for candidate in candidates {
+                    if candidate.is_element()
+                        && step.matches(&candidate)
+                        && seen.insert(candidate.id())
+                    {
+                        matched.push(candidate);
+                    }
+                }
+            }
+            context = matched;
+        }
+        context.sort_by_key(|node| node.range().start);
+        context
+    }
+
+    /// Endpoints for every match
+    fn extract(&self, document: &Document, item: &QueueItem, base: &Url) -> Vec<ApiEndpoint> {
+        let mut endpoints = Vec::new();
+        for node in self.select(document) {
+            let value = match &self.attribute {
+                Some(attribute) => node.attribute(attribute.as_str()).map(str::to_string),
+                None => Some(element_text(&node)),
+            };
+            let Some(href) = value.and_then(|value| resolve(&node, base, &value)) else {
+                continue;
+            };
+
+            let rel = node
+                .attribute("rel")
+                .unwrap_or(node.tag_name().name())
+                .to_string();
+            let mut endpoint = link_endpoint(&node, item, href, rel);
+            endpoint =
+                endpoint.with_metadata("selector".to_string(), Value::String(self.source.clone()));
+            endpoints.push(endpoint);
+        }
+        endpoints
+    }
+}
+
+impl Step {
+    /// Whether an element satisfies this step's name and predicates
+    fn matches(&self, node: &Node) -> bool {
+        self.name
+            .as_deref()
+            .is_none_or(|name| node.tag_name().name() == name)
+            && self.predicates.iter().all(|(attribute, expected)| {
+                match (node.attribute(attribute.as_str()), expected) {
+                    (Some(actual), Some(expected)) => actual == expected,
+                    (Some(_), None) => true,
+                    (None, _) => false,
+                }
+            })
+    }
+}
+
+/// Byte offset where the step at the start of `s` ends (the next `/` outside brackets)
+fn step_end(s: &str) -> usize {
+    let mut depth = 0;
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (c, quote) {
+            ('\'' | '"', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('[', None) => depth += 1,
+            (']', None) => depth -= 1,
+            ('/', None) if depth == 0 => return i,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+/// Parse `name[@attr][@attr='value']`
+fn parse_step(step: &str, descendant: bool) -> std::result::Result<Step, String> {
+    let (name, mut predicates_source) = match step.find('[') {
+        Some(start) => (&step[..start], &step[start..]),
+        None => (step, ""),
+    };
+    let name = name.rsplit(':').next().unwrap_or(name);
+    let name = match name {
+        "*" => None,
+        name if is_name(name) => Some(name.to_string()),
+        "" => return Err("empty step".to_string()),
+        name => return Err(format!("invalid element name '{}'", name)),
+    };
+
+    let mut predicates = Vec::new();
+    while !predicates_source.is_empty() {
+        let close = predicates_source
+            .find(']')
+            .ok_or_else(|| "unclosed '['".to_string())?;
+        let predicate = predicates_source[1..close]
+            .trim()
+            .strip_prefix('@')
+            .ok_or_else(|| {
+                "only [@attr] and [@attr='value'] predicates are supported".to_string()
+            })?;
+        let (attribute, value) = match predicate.split_once('=') {
+            Some((attribute, value)) => {
+                let value = value.trim();
+                let unquoted = value
+                    .strip_prefix('\'')
+                    .and_then(|v| v.strip_suffix('\''))
+                    .or_else(|| value.strip_prefix('"').and_then(|v| v.strip_suffix('"')))
+                    .ok_or_else(|| format!("predicate value {} must be quoted", value))?;
+                (attribute.trim(), Some(unquoted.to_string()))
+            }
+            None => (predicate, None),
+        };
+        if !is_name(attribute) {
+            return Err(format!("invalid attribute name '{}'", attribute));
+        }
+        predicates.push((attribute.to_string(), value));
+        predicates_source = &predicates_source[close + 1..];
+        if !predicates_source.is_empty() && !predicates_source.starts_with('[') {
+            return Err(format!(
+                "unexpected '{}' after predicate",
+                predicates_source
+            ));
+        }
+    }
+
+    Ok(Step {
+        descendant,
+        name,
+        predicates,
+    })
+}
+
+/// Whether a string is a plausible XML name
+fn is_name(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+impl<'de> Deserialize<'de> for XmlSelector {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let selector = String::deserialize(deserializer)?;
+        Self::parse(&selector).map_err(CrawlerError::into_de_error)
+    }
+}
+
+impl fmt::Display for XmlSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl fmt::Debug for XmlSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "XmlSelector({})", self.source)
+    }
+}
+
+/// Extract endpoints from an XML document fetched for `item`
+pub fn extract_endpoints(
+    xml: &str,
+    item: &QueueItem,
+    selectors: &[XmlSelector],
+) -> Result<Vec<ApiEndpoint>> {
+    // Many feeds declare a DOCTYPE; roxmltree rejects one unless told otherwise
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let document = Document::parse_with_options(xml, options)?;
+    let base = Url::parse(&item.url)?;
+    let mut endpoints = Vec::new();
+
+    for node in document.descendants().filter(Node::is_element) {
+        match node.tag_name().name() {
+            "link" => {
+                if let Some(href) = node
+                    .attribute("href")
+                    .and_then(|h| resolve(&node, &base, h))
+                {
+                    let rel = node.attribute("rel").unwrap_or("alternate").to_string();
+                    endpoints.push(link_endpoint(&node, item, href, rel));
+                }
+            }
+            "collection" => {
+                if let Some(href) = node
+                    .attribute("href")
+                    .and_then(|h| resolve(&node, &base, h))
+                {
+                    endpoints.push(collection_endpoint(&node, item, href));
+                }
+            }
+            "content" if node.tag_name().namespace() == Some(ATOM_NS) => {
+                if let Some(href) = node.attribute("src").and_then(|h| resolve(&node, &base, h)) {
+                    endpoints.push(link_endpoint(&node, item, href, "content".to_string()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for selector in selectors {
+        endpoints.extend(selector.extract(&document, item, &base));
+    }
+
+    // The same link often appears on several entries
+    let mut seen = HashSet::new();
+    endpoints.retain(|e| seen.insert((e.href.clone(), e.rel.clone())));
+    Ok(endpoints)
+}
+
+/// An endpoint for a link-like element, taking `type`, `title` and extra attributes from it
+fn link_endpoint(node: &Node, item: &QueueItem, href: String, rel: String) -> ApiEndpoint {
+    let mut endpoint = ApiEndpoint::new(href, item.depth + 1)
+        .with_rel(Some(rel))
+        .with_parent(Some(item.url.clone()));
+    endpoint.r#type = node.attribute("type").map(str::to_string);
+    endpoint.title = node.attribute("title").map(str::to_string);
+
+    for attribute in node.attributes() {
+        if attribute.namespace().is_none()
+            && !matches!(attribute.name(), "href" | "src" | "rel" | "type" | "title")
+        {
+            endpoint = endpoint.with_metadata(
+                attribute.name().to_string(),
+                Value::String(attribute.value().to_string()),
+            );
+        }
+    }
+    endpoint
+}
+
+/// An endpoint for an AtomPub collection
+fn collection_endpoint(node: &Node, item: &QueueItem, href: String) -> ApiEndpoint {
+    let mut endpoint = ApiEndpoint::new(href, item.depth + 1)
+        .with_rel(Some("collection".to_string()))
+        .with_parent(Some(item.url.clone()));
+    endpoint.title = child_text(node, "title");
+
+    let accept: Vec<Value> = node
+        .children()
+        .filter(|child| child.tag_name().name() == "accept")
+        .map(|child| Value::String(element_text(&child)))
+        .collect();
+    if !accept.is_empty() {
+        endpoint = endpoint.with_metadata("accept".to_string(), Value::Array(accept));
+    }
+
+    if let Some(workspace) = node
+        .parent_element()
+        .filter(|parent| parent.tag_name().name() == "workspace")
+        .and_then(|workspace| child_text(&workspace, "title"))
+    {
+        endpoint = endpoint.with_metadata("workspace".to_string(), Value::String(workspace));
+    }
+    endpoint
+}
+
+/// Trimmed text of the first child element with a local name
+fn child_text(node: &Node, name: &str) -> Option<String> {
+    node.children()
+        .find(|child| child.is_element() && child.tag_name().name() == name)
+        .map(|child| element_text(&child))
+}
+
+/// All text inside an element, trimmed
+fn element_text(node: &Node) -> String {
+    node.descendants()
+        .filter(Node::is_text)
+        .filter_map(|text| text.text())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Resolve a reference against the `xml:base` in scope and the document URL
+fn resolve(node: &Node, document_url: &Url, reference: &str) -> Option<String> {
+    let reference = reference.trim();
+    if reference.is_empty() {
+        return None;
+    }
+
+    let bases: Vec<&str> = node
+        .ancestors()
+        .filter_map(|ancestor| ancestor.attribute((XML_NS, "base")))
+        .collect();
+    let mut base = document_url.clone();
+    for xml_base in bases.iter().rev() {
+        base = base.join(xml_base).ok()?;
+    }
+
+    match base.join(reference) {
+        Ok(url) => Some(url.to_string()),
+        Err(e) => {
+            debug!("Skipping unresolvable XML link '{}': {}", reference, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item() -> QueueItem {
+        QueueItem::new("http://example.com/api/feed".to_string(), 1, None)
+    }
+
+    #[test]
+    fn test_atom_and_atompub_links() {
+        let feed = r#"<?xml version="1.0" encoding="utf-8"?>
+            <feed xmlns="http://www.w3.org/2005/Atom" xml:base="http://example.com/api/">
+              <link rel="self" href="feed"/>
+              <link rel="next" href="feed?page=2" type="application/atom+xml" title="Next"/>
+              <entry xml:base="orders/">
+                <link href="1" hreflang="en"/>
+                <content type="application/json" src="1/body"/>
+              </entry>
+              <entry><link href="orders/1"/></entry>
+            </feed>"#;
+        let endpoints = extract_endpoints(feed, &item(), &[]).unwrap();
+        let links: Vec<(&str, &str)> = endpoints
+            .iter()
+            .map(|e| (e.href.as_str(), e.rel.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                ("http://example.com/api/feed", "self"),
+                ("http://example.com/api/feed?page=2", "next"),
+                ("http://example.com/api/orders/1", "alternate"),
+                ("http://example.com/api/orders/1/body", "content"),
+            ]
+        );
+        assert_eq!(endpoints[1].title.as_deref(), Some("Next"));
+        assert_eq!(endpoints[1].r#type.as_deref(), Some("application/atom+xml"));
+        assert_eq!(endpoints[2].metadata["hreflang"], "en");
+        assert_eq!(endpoints[2].depth, 2);
+        assert_eq!(
+            endpoints[2].parent_url.as_deref(),
+            Some("http://example.com/api/feed")
+        );
+
+        let service = r#"<service xmlns="http://www.w3.org/2007/app"
+                                  xmlns:atom="http://www.w3.org/2005/Atom">
+              <workspace>
+                <atom:title>Main</atom:title>
+                <collection href="/api/posts">
+                  <atom:title>Posts</atom:title>
+                  <accept>application/atom+xml;type=entry</accept>
+                </collection>
+              </workspace>
+            </service>"#;
+        let endpoints = extract_endpoints(service, &item(), &[]).unwrap();
+        assert_eq!(endpoints.len(), 1);
+        let posts = &endpoints[0];
+        assert_eq!(posts.href, "http://example.com/api/posts");
+        assert_eq!(posts.rel.as_deref(), Some("collection"));
+        assert_eq!(posts.title.as_deref(), Some("Posts"));
+        assert_eq!(
+            posts.metadata["accept"],
+            serde_json::json!(["application/atom+xml;type=entry"])
+        );
+        assert_eq!(posts.metadata["workspace"], "Main");
+
+        // Documents with a DOCTYPE, internal entities included, still parse
+        let doctype = r#"<?xml version="1.0"?>
+            <!DOCTYPE feed [<!ENTITY api "http://example.com/api">]>
+            <feed xmlns="http://www.w3.org/2005/Atom"><link rel="self" href="&api;/feed"/></feed>"#;
+        let endpoints = extract_endpoints(doctype, &item(), &[]).unwrap();
+        assert_eq!(endpoints[0].href, "http://example.com/api/feed");
+
+        assert!(matches!(
+            extract_endpoints("<feed><link>", &item(), &[]),
+            Err(CrawlerError::Xml(_))
+        ));
+    }
+
+    #[test]
+    fn test_selectors() {
+        let xml = r#"<catalog>
+              <item kind="api" rel="orders"><location> /api/orders </location></item>
+              <item kind="docs"><location>/docs</location></item>
+              <group><resource uri="http://example.com/api/users"/></group>
+            </catalog>"#;
+        let selectors = [
+            XmlSelector::parse("/catalog/item[@kind='api']/location").unwrap(),
+            XmlSelector::parse("resource/@uri").unwrap(),
+        ];
+        let endpoints = extract_endpoints(xml, &item(), &selectors).unwrap();
+        let links: Vec<(&str, &str)> = endpoints
+            .iter()
+            .map(|e| (e.href.as_str(), e.rel.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                ("http://example.com/api/orders", "location"),
+                ("http://example.com/api/users", "resource"),
+            ]
+        );
+        assert_eq!(endpoints[1].metadata["selector"], "resource/@uri");
+
+        for valid in ["//a:item[@x][@y=\"1\"]//*/@href", "/root", "a/b//c"] {
+            assert!(XmlSelector::parse(valid).is_ok(), "{}", valid);
+        }
+        for invalid in [
+            "",
+            "/",
+            "//item[",
+            "item[kind]",
+            "item[@k=v]",
+            "item/@",
+            "a//@x/b",
+        ] {
+            let error = XmlSelector::parse(invalid).unwrap_err().to_string();
+            assert!(
+                error.contains("Invalid XML selector"),
+                "{}: {}",
+                invalid,
+                error
+            );
+        }
+    }
+}